@@ -1,10 +1,9 @@
 use std::path::PathBuf;
 use sha1::{Sha1, Digest};
-use tauri::Manager;
 
 /// Returns `<app_data>/ai`. Creates the directory tree on first call.
 pub fn ai_root(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let base = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let base = crate::portable::resolve_config_root(app).map_err(|e| e.to_string())?;
     let root = base.join("ai");
     std::fs::create_dir_all(&root).map_err(|e| e.to_string())?;
     Ok(root)
@@ -0,0 +1,370 @@
+//! Making MerMark the default app for Markdown files and `mermark://`
+//! links, for when the installer's registration didn't take. Everything is
+//! per user: a desktop entry plus `xdg-mime` on Linux, `HKCU` classes on
+//! Windows, LaunchServices on macOS. Windows won't let an app take over a
+//! file type the user already picked a handler for, so there the last step
+//! is the user's, in the Settings page we open for them.
+
+use tauri::AppHandle;
+
+use crate::error::{AppError, AppResult};
+
+const SCHEME: &str = "mermark";
+
+/// Register MerMark for Markdown files and `mermark://` links.
+pub fn register(app: &AppHandle) -> AppResult<()> {
+    platform::register_scheme(app)?;
+    platform::register_files(app)
+}
+
+/// Whether MerMark opens both Markdown files and `mermark://` links now.
+pub fn is_default_handler(app: &AppHandle) -> AppResult<bool> {
+    Ok(platform::scheme_is_default(app)? && platform::files_are_default(app)?)
+}
+
+#[cfg(any(windows, target_os = "linux"))]
+fn scheme_error(e: tauri_plugin_deep_link::Error) -> AppError {
+    match e {
+        tauri_plugin_deep_link::Error::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => AppError::PermissionDenied {
+            path: None,
+            message: format!("not allowed to register {}:// links: {}", SCHEME, e),
+        },
+        e => AppError::Internal(format!("registering {}:// links: {}", SCHEME, e)),
+    }
+}
+
+#[cfg(any(windows, target_os = "linux"))]
+fn current_exe() -> AppResult<std::path::PathBuf> {
+    tauri::utils::platform::current_exe().map_err(|e| AppError::Internal(format!("locating the executable: {}", e)))
+}
+
+/// Program an `Exec=` line runs, without quotes or field codes.
+#[cfg(any(target_os = "linux", test))]
+fn exec_program(entry: &str) -> Option<&str> {
+    let exec = entry.lines().find_map(|line| line.trim().strip_prefix("Exec="))?.trim();
+    match exec.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next(),
+        None => exec.split_whitespace().next(),
+    }
+}
+
+/// Value of the first `REG_SZ` line of `reg query` output.
+#[cfg(any(windows, test))]
+fn reg_value(output: &str) -> Option<&str> {
+    output.lines().find_map(|line| {
+        let (_, value) = line.split_once("REG_SZ")?;
+        Some(value.trim())
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use tauri::{AppHandle, Manager};
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    use super::{exec_program, scheme_error, SCHEME};
+    use crate::error::{AppError, AppResult};
+
+    const DESKTOP_FILE: &str = "mermark-markdown.desktop";
+    const MIME_TYPES: &[&str] = &["text/markdown", "text/x-markdown"];
+
+    /// The AppImage itself when running from one, not its mount point.
+    fn program(app: &AppHandle) -> AppResult<PathBuf> {
+        match app.env().appimage {
+            Some(appimage) => Ok(PathBuf::from(appimage)),
+            None => super::current_exe(),
+        }
+    }
+
+    fn run(program: &str, args: &[&str]) -> AppResult<String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| AppError::Internal(format!("{} is needed to change default apps: {}", program, e)))?;
+        if !output.status.success() {
+            return Err(AppError::Internal(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    pub fn register_scheme(app: &AppHandle) -> AppResult<()> {
+        app.deep_link().register(SCHEME).map_err(scheme_error)
+    }
+
+    pub fn scheme_is_default(app: &AppHandle) -> AppResult<bool> {
+        app.deep_link().is_registered(SCHEME).map_err(scheme_error)
+    }
+
+    /// Write a hidden desktop entry for Markdown files, refresh the desktop
+    /// database and make the entry the default for the Markdown MIME types.
+    pub fn register_files(app: &AppHandle) -> AppResult<()> {
+        let dir = app.path().data_dir()?.join("applications");
+        std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+        let name = app.config().product_name.clone().unwrap_or_else(|| "MerMark".to_string());
+        let entry = format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec=\"{}\" %f\nTerminal=false\nMimeType={};\nNoDisplay=true\n",
+            name,
+            program(app)?.display(),
+            MIME_TYPES.join(";")
+        );
+        crate::files::write_atomic(&dir.join(DESKTOP_FILE), entry.as_bytes())?;
+        // Only speeds up lookups; xdg-mime works without it.
+        if let Err(e) = run("update-desktop-database", &[&dir.to_string_lossy()]) {
+            tracing::warn!(error = %e, "desktop database not refreshed");
+        }
+        let mut args = vec!["default", DESKTOP_FILE];
+        args.extend(MIME_TYPES);
+        run("xdg-mime", &args)?;
+        Ok(())
+    }
+
+    /// Our entry is the default, or whichever entry runs this program
+    /// (e.g. the one a .deb installed).
+    pub fn files_are_default(app: &AppHandle) -> AppResult<bool> {
+        let default = run("xdg-mime", &["query", "default", MIME_TYPES[0]])?;
+        let default = default.trim();
+        if default == DESKTOP_FILE {
+            return Ok(true);
+        }
+        if default.is_empty() {
+            return Ok(false);
+        }
+        let dirs = [app.path().data_dir()?, PathBuf::from("/usr/local/share"), PathBuf::from("/usr/share")];
+        let Some(entry) = dirs.iter().find_map(|dir| std::fs::read_to_string(dir.join("applications").join(default)).ok())
+        else {
+            return Ok(false);
+        };
+        let ours = program(app)?;
+        Ok(exec_program(&entry).is_some_and(|exec| {
+            let exec = Path::new(exec);
+            exec == ours || (exec.is_relative() && exec.file_name() == ours.file_name())
+        }))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::process::Command;
+    use tauri::AppHandle;
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    use super::{reg_value, scheme_error, SCHEME};
+    use crate::error::{AppError, AppResult};
+
+    const PROG_ID: &str = "MerMark.Markdown";
+    const EXTENSIONS: &[&str] = &["md", "markdown"];
+
+    fn reg(args: &[&str]) -> AppResult<Option<String>> {
+        let output = Command::new("reg")
+            .args(args)
+            .output()
+            .map_err(|e| AppError::Internal(format!("reg: {}", e)))?;
+        if output.status.success() {
+            return Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("Access is denied") {
+            return Err(AppError::PermissionDenied {
+                path: None,
+                message: "Windows denied the change; run MerMark as administrator once to register it".to_string(),
+            });
+        }
+        // `reg query` fails for a missing key, which just means "not set".
+        if args.first() == Some(&"query") {
+            return Ok(None);
+        }
+        Err(AppError::Internal(format!("reg {}: {}", args.join(" "), stderr.trim())))
+    }
+
+    pub fn register_scheme(app: &AppHandle) -> AppResult<()> {
+        app.deep_link().register(SCHEME).map_err(scheme_error)
+    }
+
+    pub fn scheme_is_default(app: &AppHandle) -> AppResult<bool> {
+        app.deep_link().is_registered(SCHEME).map_err(scheme_error)
+    }
+
+    /// Register the ProgID and offer it for each extension. Taking over an
+    /// extension the user already chose a handler for has to happen in
+    /// Settings, which is opened with an error saying so.
+    pub fn register_files(app: &AppHandle) -> AppResult<()> {
+        let exe = super::current_exe()?.display().to_string();
+        let class = format!(r"HKCU\Software\Classes\{}", PROG_ID);
+        reg(&["add", &class, "/ve", "/d", "Markdown Document", "/f"])
+            .and_then(|_| reg(&["add", &format!(r"{}\DefaultIcon", class), "/ve", "/d", &format!("{},0", exe), "/f"]))
+            .and_then(|_| {
+                let command = format!("\"{}\" \"%1\"", exe);
+                reg(&["add", &format!(r"{}\shell\open\command", class), "/ve", "/d", &command, "/f"])
+            })?;
+        for ext in EXTENSIONS {
+            let key = format!(r"HKCU\Software\Classes\.{}", ext);
+            reg(&["add", &key, "/ve", "/d", PROG_ID, "/f"])?;
+            reg(&["add", &format!(r"{}\OpenWithProgids", key), "/v", PROG_ID, "/t", "REG_NONE", "/f"])?;
+        }
+        if files_are_default(app)? {
+            return Ok(());
+        }
+        let _ = Command::new("explorer.exe").arg("ms-settings:defaultapps").spawn();
+        Err(AppError::PermissionDenied {
+            path: None,
+            message: "Windows only lets you pick the default app for .md yourself: choose MerMark in the Settings page that opened"
+                .to_string(),
+        })
+    }
+
+    /// The user's choice wins over the class default, so check it first.
+    pub fn files_are_default(_app: &AppHandle) -> AppResult<bool> {
+        for ext in EXTENSIONS {
+            let choice = format!(r"HKCU\Software\Microsoft\Windows\CurrentVersion\Explorer\FileExts\.{}\UserChoice", ext);
+            let prog_id = match reg(&["query", &choice, "/v", "ProgId"])? {
+                Some(output) => reg_value(&output).map(str::to_string),
+                None => reg(&["query", &format!(r"HKCU\Software\Classes\.{}", ext), "/ve"])?
+                    .and_then(|output| reg_value(&output).map(str::to_string)),
+            };
+            if prog_id.as_deref() != Some(PROG_ID) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::{c_char, c_void, CStr, CString};
+    use tauri::AppHandle;
+
+    use super::SCHEME;
+    use crate::error::{AppError, AppResult};
+
+    type CFStringRef = *const c_void;
+    const UTF8: u32 = 0x0800_0100;
+    const ROLES_ALL: u32 = 0xFFFF_FFFF;
+    /// Uniform type identifier for Markdown.
+    const MARKDOWN_UTI: &str = "net.daringfireball.markdown";
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFStringCreateWithCString(alloc: *const c_void, s: *const c_char, encoding: u32) -> CFStringRef;
+        fn CFStringGetCString(s: CFStringRef, buffer: *mut c_char, size: isize, encoding: u32) -> u8;
+        fn CFRelease(cf: *const c_void);
+    }
+
+    #[link(name = "CoreServices", kind = "framework")]
+    unsafe extern "C" {
+        fn LSSetDefaultRoleHandlerForContentType(content_type: CFStringRef, role: u32, handler: CFStringRef) -> i32;
+        fn LSCopyDefaultRoleHandlerForContentType(content_type: CFStringRef, role: u32) -> CFStringRef;
+        fn LSSetDefaultHandlerForURLScheme(scheme: CFStringRef, handler: CFStringRef) -> i32;
+        fn LSCopyDefaultHandlerForURLScheme(scheme: CFStringRef) -> CFStringRef;
+    }
+
+    /// Owned CFString, released on drop.
+    struct CfString(CFStringRef);
+
+    impl CfString {
+        fn new(s: &str) -> Self {
+            let c = CString::new(s).unwrap_or_default();
+            Self(unsafe { CFStringCreateWithCString(std::ptr::null(), c.as_ptr(), UTF8) })
+        }
+
+        fn read(&self) -> Option<String> {
+            if self.0.is_null() {
+                return None;
+            }
+            let mut buffer = [0 as c_char; 512];
+            let ok = unsafe { CFStringGetCString(self.0, buffer.as_mut_ptr(), buffer.len() as isize, UTF8) };
+            (ok != 0).then(|| unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_string_lossy().into_owned())
+        }
+    }
+
+    impl Drop for CfString {
+        fn drop(&mut self) {
+            if !self.0.is_null() {
+                unsafe { CFRelease(self.0) };
+            }
+        }
+    }
+
+    fn check(status: i32, what: &str) -> AppResult<()> {
+        match status {
+            0 => Ok(()),
+            status => Err(AppError::Internal(format!("LaunchServices refused {} (status {})", what, status))),
+        }
+    }
+
+    fn is_ours(app: &AppHandle, handler: CfString) -> bool {
+        handler.read().is_some_and(|id| id.eq_ignore_ascii_case(&app.config().identifier))
+    }
+
+    pub fn register_scheme(app: &AppHandle) -> AppResult<()> {
+        let (scheme, id) = (CfString::new(SCHEME), CfString::new(&app.config().identifier));
+        check(unsafe { LSSetDefaultHandlerForURLScheme(scheme.0, id.0) }, "the mermark:// handler")
+    }
+
+    pub fn scheme_is_default(app: &AppHandle) -> AppResult<bool> {
+        let scheme = CfString::new(SCHEME);
+        Ok(is_ours(app, CfString(unsafe { LSCopyDefaultHandlerForURLScheme(scheme.0) })))
+    }
+
+    pub fn register_files(app: &AppHandle) -> AppResult<()> {
+        let (uti, id) = (CfString::new(MARKDOWN_UTI), CfString::new(&app.config().identifier));
+        check(unsafe { LSSetDefaultRoleHandlerForContentType(uti.0, ROLES_ALL, id.0) }, "the Markdown handler")
+    }
+
+    pub fn files_are_default(app: &AppHandle) -> AppResult<bool> {
+        let uti = CfString::new(MARKDOWN_UTI);
+        Ok(is_ours(app, CfString(unsafe { LSCopyDefaultRoleHandlerForContentType(uti.0, ROLES_ALL) })))
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+mod platform {
+    use tauri::AppHandle;
+
+    use crate::error::{AppError, AppResult};
+
+    fn unsupported() -> AppError {
+        AppError::Disabled("default apps can't be changed on this platform".to_string())
+    }
+
+    pub fn register_scheme(_app: &AppHandle) -> AppResult<()> {
+        Err(unsupported())
+    }
+
+    pub fn scheme_is_default(_app: &AppHandle) -> AppResult<bool> {
+        Err(unsupported())
+    }
+
+    pub fn register_files(_app: &AppHandle) -> AppResult<()> {
+        Err(unsupported())
+    }
+
+    pub fn files_are_default(_app: &AppHandle) -> AppResult<bool> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_program_of_a_desktop_entry() {
+        assert_eq!(exec_program("[Desktop Entry]\nName=X\nExec=\"/opt/Mer Mark/app\" %f\n"), Some("/opt/Mer Mark/app"));
+        assert_eq!(exec_program("[Desktop Entry]\nExec=mer-mark-editor %U\n"), Some("mer-mark-editor"));
+        assert_eq!(exec_program("[Desktop Entry]\nName=X\n"), None);
+    }
+
+    #[test]
+    fn reads_a_registry_value() {
+        let output = "\r\nHKEY_CURRENT_USER\\Software\\Classes\\.md\r\n    (Default)    REG_SZ    MerMark.Markdown\r\n\r\n";
+        assert_eq!(reg_value(output), Some("MerMark.Markdown"));
+        assert_eq!(reg_value("ERROR: not found"), None);
+    }
+}
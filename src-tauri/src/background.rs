@@ -0,0 +1,114 @@
+//! App-wide background awareness. Once no editor window has had focus for
+//! `PAUSE_DELAY` the app counts as backgrounded and `background-throttle`
+//! tells every window to hold non-essential work (autosave ticks, reload
+//! prompts from the file watcher) until one is focused again. The delay
+//! keeps focus moving between two of our windows from pausing anything.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::Serialize;
+
+pub const PAUSE_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundThrottlePayload {
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusChange {
+    /// A window got focus while paused: tell everyone to resume.
+    Resume,
+    /// The last focused window lost focus: pause after `PAUSE_DELAY` unless
+    /// this generation has been superseded by then.
+    SchedulePause(u64),
+    Nothing,
+}
+
+#[derive(Default)]
+struct Inner {
+    focused: HashSet<String>,
+    paused: bool,
+    /// Bumped on every focus change so only the newest timer can pause.
+    generation: u64,
+}
+
+#[derive(Default)]
+pub struct Background(Mutex<Inner>);
+
+impl Background {
+    pub fn focus_changed(&self, label: &str, focused: bool) -> FocusChange {
+        let mut inner = self.0.lock().unwrap();
+        inner.generation += 1;
+        if focused {
+            inner.focused.insert(label.to_string());
+            if inner.paused {
+                inner.paused = false;
+                return FocusChange::Resume;
+            }
+            return FocusChange::Nothing;
+        }
+        inner.focused.remove(label);
+        if inner.focused.is_empty() && !inner.paused {
+            FocusChange::SchedulePause(inner.generation)
+        } else {
+            FocusChange::Nothing
+        }
+    }
+
+    /// Pause if nothing changed since `generation` was handed out.
+    pub fn expire(&self, generation: u64) -> bool {
+        let mut inner = self.0.lock().unwrap();
+        if inner.generation != generation || !inner.focused.is_empty() || inner.paused {
+            return false;
+        }
+        inner.paused = true;
+        true
+    }
+
+    /// Lift a pause without a focus change, e.g. when the setting is turned
+    /// off. Returns whether there was one.
+    pub fn resume(&self) -> bool {
+        let mut inner = self.0.lock().unwrap();
+        inner.generation += 1;
+        std::mem::take(&mut inner.paused)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.lock().unwrap().paused
+    }
+
+    /// Close hook: a closed window no longer counts as focused.
+    pub fn forget(&self, label: &str) {
+        self.0.lock().unwrap().focused.remove(label);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switching_between_windows_does_not_pause() {
+        let bg = Background::default();
+        assert_eq!(bg.focus_changed("main", true), FocusChange::Nothing);
+        let FocusChange::SchedulePause(stale) = bg.focus_changed("main", false) else { panic!() };
+        assert_eq!(bg.focus_changed("window-2", true), FocusChange::Nothing);
+        assert!(!bg.expire(stale));
+        assert!(!bg.is_paused());
+    }
+
+    #[test]
+    fn pauses_once_every_window_is_unfocused_and_resumes_on_focus() {
+        let bg = Background::default();
+        bg.focus_changed("main", true);
+        let FocusChange::SchedulePause(generation) = bg.focus_changed("main", false) else { panic!() };
+        assert!(bg.expire(generation));
+        assert!(bg.is_paused());
+        assert_eq!(bg.focus_changed("window-2", true), FocusChange::Resume);
+        assert!(!bg.is_paused());
+        assert!(!bg.resume());
+    }
+}
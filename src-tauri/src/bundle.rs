@@ -0,0 +1,364 @@
+//! Read-only HTML bundles: one page per selected note in a single folder,
+//! with a shared sidebar and the images they use copied to `assets/`.
+//!
+//! Planning happens here: output names, titles, and each note's Markdown with
+//! cross-note links pointed at the exported siblings. The frontend renders
+//! the planned Markdown through its normal HTML pipeline and hands the HTML
+//! back to be written.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::links::{self, LinkKind};
+use crate::markdown::{frontmatter, outline};
+
+pub const ASSETS_DIR: &str = "assets";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BundleOptions {
+    /// Sidebar heading and page title suffix; defaults to the folder name.
+    pub title: Option<String>,
+    pub copy_assets: bool,
+}
+
+impl Default for BundleOptions {
+    fn default() -> Self {
+        Self { title: None, copy_assets: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePage {
+    pub source: String,
+    /// File name inside the bundle, e.g. `meeting-notes.html`.
+    pub output: String,
+    pub title: String,
+    /// The note with its links rewritten for the bundle, ready to render.
+    pub markdown: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleAsset {
+    pub source: String,
+    /// Path relative to the bundle folder, e.g. `assets/chart.png`.
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePlan {
+    pub pages: Vec<BundlePage>,
+    pub assets: Vec<BundleAsset>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleProgress {
+    pub done: usize,
+    pub total: usize,
+    pub current: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleResult {
+    /// The page to open first.
+    pub index: String,
+    pub pages: usize,
+    pub assets: usize,
+}
+
+/// Assets collected while rewriting, one bundle name per source file.
+#[derive(Default)]
+struct AssetTable {
+    assets: Vec<BundleAsset>,
+    names: HashSet<String>,
+}
+
+impl AssetTable {
+    fn add(&mut self, source: &Path) -> String {
+        let source_str = source.to_string_lossy().into_owned();
+        if let Some(existing) = self.assets.iter().find(|a| a.source == source_str) {
+            return existing.target.clone();
+        }
+        let stem = source.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let ext = source.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+        let mut name = format!("{}{}", stem, ext);
+        let mut n = 2;
+        while !self.names.insert(name.to_lowercase()) {
+            name = format!("{}-{}{}", stem, n, ext);
+            n += 1;
+        }
+        let target = format!("{}/{}", ASSETS_DIR, name);
+        self.assets.push(BundleAsset { source: source_str, target: target.clone() });
+        target
+    }
+}
+
+struct PlannedSource<'a> {
+    path: PathBuf,
+    text: &'a str,
+    output: String,
+}
+
+fn page_title(text: &str, path: &Path) -> String {
+    frontmatter::split(text)
+        .and_then(|(raw, _)| frontmatter::get_key(raw, "title"))
+        .or_else(|| outline::generate_toc(text).into_iter().find(|e| e.level == 1).map(|e| e.text))
+        .unwrap_or_else(|| path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default())
+}
+
+/// `[label](dest)` around a Markdown link target starting at `start`:
+/// the span to replace and the label. Images are not note links.
+fn link_span(line: &str, start: usize) -> Option<(usize, usize, &str)> {
+    let before = &line[..start];
+    let before = before.strip_suffix('<').unwrap_or(before);
+    let bracket = before.strip_suffix("](")?.len();
+    let open = line[..bracket].rfind('[')?;
+    if line[..open].ends_with('!') {
+        return None;
+    }
+    let close = start + line[start..].find(')')?;
+    Some((open, close + 1, &line[open + 1..bracket]))
+}
+
+fn heading_anchor(heading: Option<&str>) -> String {
+    match heading.map(str::trim).filter(|h| !h.is_empty()) {
+        Some(h) => format!("#{}", outline::slugify(h, &mut HashSet::new())),
+        None => String::new(),
+    }
+}
+
+fn rewrite_line(line: &str, from: &Path, pages: &[PlannedSource], assets: &mut AssetTable, copy_assets: bool) -> String {
+    let mut out = line.to_string();
+    let mut found = links::extract_links(line);
+    found.sort_by_key(|l| l.start);
+    for link in found.into_iter().rev() {
+        match link.kind {
+            LinkKind::Wikilink => {
+                let Some(open) = line[..link.start].rfind("[[") else { continue };
+                let Some(close) = line[link.end..].find("]]").map(|c| link.end + c) else { continue };
+                // `![[...]]` embeds are left to the renderer.
+                if line[..open].ends_with('!') {
+                    continue;
+                }
+                let inner = &line[open + 2..close];
+                let (target, alias) = match inner.split_once('|') {
+                    Some((t, a)) => (t, Some(a.trim())),
+                    None => (inner, None),
+                };
+                let (name, heading) = match target.split_once('#') {
+                    Some((n, h)) => (n.trim(), Some(h)),
+                    None => (target.trim(), None),
+                };
+                let label = alias.filter(|a| !a.is_empty()).unwrap_or(name);
+                let replacement = match pages.iter().find(|p| links::wikilink_matches(name, &p.path)) {
+                    Some(page) => format!("[{}]({}{})", label, page.output, heading_anchor(heading)),
+                    None => label.to_string(),
+                };
+                out.replace_range(open..close + 2, &replacement);
+            }
+            LinkKind::Markdown => {
+                let Some(resolved) = links::resolve_markdown_target(from, &link.target) else { continue };
+                let name = resolved.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                if crate::is_workspace_markdown(&name) {
+                    if let Some(page) = pages.iter().find(|p| p.path == resolved) {
+                        out.replace_range(link.start..link.end, &page.output);
+                    } else if let Some((start, end, label)) = link_span(line, link.start) {
+                        out.replace_range(start..end, label);
+                    }
+                } else if copy_assets && resolved.is_file() {
+                    let target = assets.add(&resolved).replace(' ', "%20");
+                    out.replace_range(link.start..link.end, &target);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Plan a bundle from `(path, text)` pairs, in sidebar order.
+pub fn plan(docs: &[(PathBuf, String)], copy_assets: bool) -> BundlePlan {
+    let mut seen = HashSet::new();
+    let sources: Vec<PlannedSource> = docs
+        .iter()
+        .map(|(path, text)| {
+            let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            PlannedSource {
+                path: links::normalize_path(path),
+                text,
+                output: format!("{}.html", outline::slugify(&stem, &mut seen)),
+            }
+        })
+        .collect();
+
+    let mut assets = AssetTable::default();
+    let pages = sources
+        .iter()
+        .map(|source| {
+            let prose: HashSet<usize> = links::prose_lines(source.text).map(|(i, _)| i).collect();
+            let mut markdown = String::with_capacity(source.text.len());
+            for (i, segment) in source.text.split_inclusive('\n').enumerate() {
+                let line = segment.trim_end_matches(['\n', '\r']);
+                if prose.contains(&i) {
+                    markdown.push_str(&rewrite_line(line, &source.path, &sources, &mut assets, copy_assets));
+                    markdown.push_str(&segment[line.len()..]);
+                } else {
+                    markdown.push_str(segment);
+                }
+            }
+            BundlePage {
+                source: source.path.to_string_lossy().into_owned(),
+                output: source.output.clone(),
+                title: page_title(source.text, &source.path),
+                markdown,
+            }
+        })
+        .collect();
+    BundlePlan { pages, assets: assets.assets }
+}
+
+const STYLE: &str = "body{margin:0;display:flex;font-family:system-ui,sans-serif;line-height:1.6;color:#24292f}\
+nav{width:240px;flex-shrink:0;min-height:100vh;padding:24px 16px;box-sizing:border-box;background:#f6f8fa;border-right:1px solid #d0d7de}\
+nav h2{font-size:15px;margin:0 0 12px}nav ul{list-style:none;margin:0;padding:0}nav li{margin:4px 0}\
+nav a{color:#0969da;text-decoration:none}nav a.current{font-weight:600;color:#24292f}\
+main{flex:1;max-width:860px;padding:32px 48px}img{max-width:100%}\
+pre{background:#f6f8fa;padding:12px;overflow:auto}table{border-collapse:collapse}td,th{border:1px solid #d0d7de;padding:4px 8px}";
+
+fn render_page(plan: &BundlePlan, current: usize, body: &str, bundle_title: &str) -> String {
+    let esc = outline::xml_escape;
+    let mut nav = String::new();
+    for (i, page) in plan.pages.iter().enumerate() {
+        let class = if i == current { " class=\"current\"" } else { "" };
+        nav.push_str(&format!("<li><a href=\"{}\"{}>{}</a></li>", esc(&page.output), class, esc(&page.title)));
+    }
+    let page = &plan.pages[current];
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{} — {}</title>\n<style>{}</style>\n</head>\n<body>\n<nav><h2>{}</h2><ul>{}</ul></nav>\n<main>\n{}\n</main>\n</body>\n</html>\n",
+        esc(&page.title),
+        esc(bundle_title),
+        STYLE,
+        esc(bundle_title),
+        nav,
+        body
+    )
+}
+
+/// Write the rendered pages (`html[i]` belongs to `plan.pages[i]`) and copy
+/// the assets into `output_dir`. When no note became `index.html`, one is
+/// added that forwards to the first page.
+pub fn write(
+    plan: &BundlePlan,
+    html: &[String],
+    output_dir: &Path,
+    bundle_title: &str,
+    mut progress: impl FnMut(BundleProgress),
+) -> AppResult<BundleResult> {
+    if plan.pages.is_empty() {
+        return Err(AppError::InvalidInput("no documents selected for the bundle".into()));
+    }
+    if html.len() != plan.pages.len() {
+        return Err(AppError::InvalidInput(format!(
+            "expected {} rendered pages, got {}",
+            plan.pages.len(),
+            html.len()
+        )));
+    }
+    std::fs::create_dir_all(output_dir).map_err(|e| AppError::io(output_dir, e))?;
+    let total = plan.pages.len() + plan.assets.len();
+    let mut done = 0;
+    for (i, page) in plan.pages.iter().enumerate() {
+        let doc = render_page(plan, i, &html[i], bundle_title);
+        crate::files::write_atomic(&output_dir.join(&page.output), doc.as_bytes())?;
+        done += 1;
+        progress(BundleProgress { done, total, current: page.output.clone() });
+    }
+    for asset in &plan.assets {
+        let dest = output_dir.join(&asset.target);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+        std::fs::copy(&asset.source, &dest).map_err(|e| AppError::io(&asset.source, e))?;
+        done += 1;
+        progress(BundleProgress { done, total, current: asset.target.clone() });
+    }
+    let first = plan.pages[0].output.clone();
+    if !plan.pages.iter().any(|p| p.output == "index.html") {
+        let redirect = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta http-equiv=\"refresh\" content=\"0; url={0}\">\n\
+             <title>{1}</title>\n</head>\n<body><a href=\"{0}\">{1}</a></body>\n</html>\n",
+            outline::xml_escape(&first),
+            outline::xml_escape(bundle_title)
+        );
+        crate::files::write_atomic(&output_dir.join("index.html"), redirect.as_bytes())?;
+    }
+    Ok(BundleResult { index: "index.html".to_string(), pages: plan.pages.len(), assets: plan.assets.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docs(entries: &[(&str, &str)]) -> Vec<(PathBuf, String)> {
+        entries.iter().map(|(p, t)| (PathBuf::from(p), t.to_string())).collect()
+    }
+
+    #[test]
+    fn links_between_selected_notes_point_at_siblings() {
+        let plan = plan(
+            &docs(&[
+                ("/vault/Home.md", "# Welcome\nSee [[Plan#Next Steps|the plan]], [[Secret]] and [p](projects/Plan.md#x).\n"),
+                ("/vault/projects/Plan.md", "Back [home](../Home.md), [out](../Other.md) `[[Home]]`\n```\n[[Home]]\n```\n"),
+            ]),
+            true,
+        );
+        let outputs: Vec<(&str, &str)> = plan.pages.iter().map(|p| (p.output.as_str(), p.title.as_str())).collect();
+        assert_eq!(outputs, [("home.html", "Welcome"), ("plan.html", "Plan")]);
+        assert_eq!(
+            plan.pages[0].markdown,
+            "# Welcome\nSee [the plan](plan.html#next-steps), Secret and [p](plan.html#x).\n"
+        );
+        assert_eq!(plan.pages[1].markdown, "Back [home](home.html), out `[[Home]]`\n```\n[[Home]]\n```\n");
+    }
+
+    #[test]
+    fn assets_are_copied_under_unique_names() {
+        let dir = std::env::temp_dir().join(format!("mermark-bundle-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("a")).unwrap();
+        std::fs::create_dir_all(dir.join("b")).unwrap();
+        std::fs::write(dir.join("a/chart.png"), "png").unwrap();
+        std::fs::write(dir.join("b/chart.png"), "png").unwrap();
+        let a = dir.join("a/one.md");
+        let b = dir.join("b/two.md");
+        let plan = plan(
+            &[
+                (a, "![c](chart.png) ![again](chart.png) ![gone](missing.png)\n".to_string()),
+                (b, "![c](chart.png)\n".to_string()),
+            ],
+            true,
+        );
+        assert_eq!(
+            plan.pages[0].markdown,
+            "![c](assets/chart.png) ![again](assets/chart.png) ![gone](missing.png)\n"
+        );
+        assert_eq!(plan.pages[1].markdown, "![c](assets/chart-2.png)\n");
+
+        let out = dir.join("site");
+        let html = vec!["<p>one</p>".to_string(), "<p>two</p>".to_string()];
+        let mut events = 0;
+        let result = write(&plan, &html, &out, "Notes", |_| events += 1).unwrap();
+        assert_eq!((result.pages, result.assets, events), (2, 2, 4));
+        assert!(out.join("assets/chart-2.png").is_file());
+        let page = std::fs::read_to_string(out.join("one.html")).unwrap();
+        assert!(page.contains("<a href=\"one.html\" class=\"current\">one</a>"));
+        assert!(page.contains("<a href=\"two.html\">two</a>"));
+        assert!(std::fs::read_to_string(out.join("index.html")).unwrap().contains("url=one.html"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
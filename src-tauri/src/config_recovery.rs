@@ -0,0 +1,158 @@
+//! Defensive loading of the JSON files in `<app_data>`. A file that no longer
+//! parses (partial write, manual edit) is moved aside to
+//! `<name>.corrupt-<timestamp>` and the caller falls back to defaults, so a
+//! damaged config costs its contents instead of a clean start. Each recovery
+//! is announced with `config-recovered` and remembered for
+//! `validate_config_files`, since one found during startup happens before
+//! any window listens.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+/// Backups recorded this run, by file name.
+#[derive(Default)]
+pub struct Recoveries(pub Mutex<HashMap<String, PathBuf>>);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRecovered {
+    pub file: String,
+    pub backup: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigHealth {
+    Ok,
+    /// Not written yet; defaults apply.
+    Missing,
+    Corrupt,
+    Unreadable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigStatus {
+    pub file: String,
+    pub path: String,
+    pub health: ConfigHealth,
+    pub error: Option<String>,
+    /// Where the damaged copy went, if it was recovered this run.
+    pub backup: Option<String>,
+}
+
+type Validator = fn(&[u8]) -> Result<(), serde_json::Error>;
+
+fn parses_as<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<(), serde_json::Error> {
+    serde_json::from_slice::<T>(bytes).map(|_| ())
+}
+
+/// Files `validate_config_files` reports on, with the shape each must parse as.
+const CONFIG_FILES: &[(&str, Validator)] = &[
+    ("settings.json", parses_as::<crate::settings::AppSettings>),
+    ("window-state.json", parses_as::<crate::window_state::Store>),
+    ("positions.json", parses_as::<crate::positions::Store>),
+    ("recent-folders.json", parses_as::<Vec<String>>),
+    ("notes-index.json", parses_as::<crate::db::Index>),
+];
+
+/// `settings.json` → `settings.json.corrupt-20240131-154500`.
+fn backup_path(path: &Path, stamp: &str) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}.corrupt-{}", name, stamp))
+}
+
+/// Move a damaged file out of the way and return where it went.
+pub fn quarantine(path: &Path) -> std::io::Result<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut backup = backup_path(path, &stamp);
+    let mut n = 1;
+    while backup.exists() {
+        backup = backup_path(path, &format!("{}-{}", stamp, n));
+        n += 1;
+    }
+    std::fs::rename(path, &backup)?;
+    Ok(backup)
+}
+
+/// Called by a loader whose file failed to parse: back it up, record and
+/// announce the recovery. The loader then continues with defaults.
+pub fn recover(app: &tauri::AppHandle, path: &Path, error: &serde_json::Error) {
+    let file = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let backup = match quarantine(path) {
+        Ok(backup) => backup,
+        Err(e) => {
+            tracing::warn!(file = %file, "corrupt config could not be moved aside: {}", e);
+            return;
+        }
+    };
+    tracing::warn!(file = %file, backup = %backup.display(), "corrupt config replaced by defaults: {}", error);
+    if let Some(state) = app.try_state::<Recoveries>() {
+        state.0.lock().unwrap().insert(file.clone(), backup.clone());
+    }
+    let _ = app.emit("config-recovered", ConfigRecovered { file, backup: backup.to_string_lossy().into_owned() });
+}
+
+fn status(dir: &Path, file: &str, validate: Validator, backup: Option<&PathBuf>) -> ConfigStatus {
+    let path = dir.join(file);
+    let (health, error) = match std::fs::read(&path) {
+        Ok(bytes) => match validate(&bytes) {
+            Ok(()) => (ConfigHealth::Ok, None),
+            Err(e) => (ConfigHealth::Corrupt, Some(e.to_string())),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (ConfigHealth::Missing, None),
+        Err(e) => (ConfigHealth::Unreadable, Some(e.to_string())),
+    };
+    ConfigStatus {
+        file: file.to_string(),
+        path: path.to_string_lossy().into_owned(),
+        health,
+        error,
+        backup: backup.map(|b| b.to_string_lossy().into_owned()),
+    }
+}
+
+/// Health of every known config file in `dir`.
+pub fn validate(dir: &Path, recoveries: &HashMap<String, PathBuf>) -> Vec<ConfigStatus> {
+    CONFIG_FILES
+        .iter()
+        .map(|&(file, validator)| status(dir, file, validator, recoveries.get(file)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupt_files_are_moved_aside_and_reported() {
+        let dir = std::env::temp_dir().join(format!("mermark-recovery-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("settings.json"), r#"{"theme": "dark"}"#).unwrap();
+        std::fs::write(dir.join("window-state.json"), r#"{"main": {"open"#).unwrap();
+
+        let health = |statuses: &[ConfigStatus]| statuses.iter().map(|s| s.health).collect::<Vec<_>>();
+        let before = validate(&dir, &HashMap::new());
+        assert_eq!(
+            health(&before),
+            [ConfigHealth::Ok, ConfigHealth::Corrupt, ConfigHealth::Missing, ConfigHealth::Missing, ConfigHealth::Missing]
+        );
+
+        let backup = quarantine(&dir.join("window-state.json")).unwrap();
+        assert!(backup.file_name().unwrap().to_string_lossy().starts_with("window-state.json.corrupt-"));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), r#"{"main": {"open"#);
+        std::fs::write(dir.join("window-state.json"), "x").unwrap();
+        let second = quarantine(&dir.join("window-state.json")).unwrap();
+        assert_ne!(backup, second);
+
+        let recoveries = HashMap::from([("window-state.json".to_string(), backup.clone())]);
+        let after = validate(&dir, &recoveries);
+        assert_eq!(after[1].health, ConfigHealth::Missing);
+        assert_eq!(after[1].backup.as_deref(), Some(backup.to_string_lossy().as_ref()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,785 @@
+//! Notes index: title, tags and outgoing links of every Markdown file in the
+//! indexed workspace, so tag lists, backlinks and title lookups answer from
+//! memory instead of rescanning the folder. Each note has a document ID that
+//! belongs to its path rather than its content and follows the file through
+//! renames. Kept in `<app_data>/notes-index.json`; a recursive watcher on the
+//! indexed root keeps it current while the app runs.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::error::{AppError, AppResult};
+use crate::links::{self, LinkKind};
+use crate::markdown::{frontmatter, heading_level};
+use crate::{is_workspace_hidden, is_workspace_markdown};
+
+/// Watcher changes are written out at most this often.
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteLink {
+    pub kind: LinkKind,
+    /// As written: the wikilink name or the relative link destination.
+    pub target: String,
+    /// How many times the note links to it this way.
+    #[serde(default = "one")]
+    pub count: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    pub id: String,
+    pub path: String,
+    /// Front-matter `title`, else the first heading, else the file name.
+    pub title: String,
+    /// Lowercased, from front matter and inline `#tags`.
+    pub tags: Vec<String>,
+    pub links: Vec<NoteLink>,
+    /// File modification time (ms since the Unix epoch) when it was read, so
+    /// a refresh can skip files that haven't changed.
+    pub modified: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSummary {
+    pub id: String,
+    pub path: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub notes: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSummary {
+    pub root: String,
+    pub notes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    /// Note IDs.
+    pub from: String,
+    pub to: String,
+    /// Kind of the first link from `from` to `to`.
+    pub kind: LinkKind,
+    /// Every link between the two, for sizing the edge.
+    pub weight: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteGraph {
+    pub nodes: Vec<NoteSummary>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Index {
+    root: Option<String>,
+    /// Keyed by normalized path.
+    notes: HashMap<String, Note>,
+    /// Lowercased file stem to the paths of the notes with it, so a wikilink
+    /// resolves without a scan of every note. Rebuilt after loading.
+    #[serde(skip)]
+    by_stem: HashMap<String, Vec<String>>,
+}
+
+#[derive(Default)]
+pub struct NotesDb {
+    /// Loaded from disk on first use.
+    index: Mutex<Option<Index>>,
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    /// Path that left in a rename event still waiting for its other half.
+    renamed_from: Mutex<Option<PathBuf>>,
+    save_pending: AtomicBool,
+}
+
+fn key(path: &Path) -> String {
+    links::normalize_path(path).to_string_lossy().into_owned()
+}
+
+fn stem_key(path: &str) -> String {
+    Path::new(path).file_stem().unwrap_or_default().to_string_lossy().to_lowercase()
+}
+
+fn summary(note: &Note) -> NoteSummary {
+    NoteSummary { id: note.id.clone(), path: note.path.clone(), title: note.title.clone() }
+}
+
+fn modified_ms(path: &Path) -> i64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// `tags: [a, b]`, `tags: a, b` or a `- a` list under `tags:`.
+fn front_matter_tags(raw: &str) -> Vec<String> {
+    let Some(value) = frontmatter::get_key(raw, "tags") else { return Vec::new() };
+    let value = value.trim();
+    if !value.is_empty() {
+        let inner = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')).unwrap_or(value);
+        return inner.split(',').map(|t| t.trim().trim_matches(['"', '\'']).to_string()).collect();
+    }
+    raw.lines()
+        .skip_while(|line| !line.starts_with("tags"))
+        .skip(1)
+        .map_while(|line| line.trim_start().strip_prefix("- "))
+        .map(|t| t.trim().trim_matches(['"', '\'']).to_string())
+        .collect()
+}
+
+/// `#tag` and `#nested/tag` in a line of prose. The `#` must start a word,
+/// and an all-digit name (`#1`) is an issue number, not a tag.
+fn inline_tags(line: &str, out: &mut Vec<String>) {
+    let masked = links::mask_code_spans(line);
+    let mut prev = ' ';
+    for (i, ch) in masked.char_indices() {
+        if ch == '#' && (prev.is_whitespace() || prev == '(') {
+            let name: String = masked[i + 1..]
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+                .collect();
+            let name = name.trim_end_matches('/');
+            if name.chars().any(|c| !c.is_ascii_digit()) {
+                out.push(name.to_string());
+            }
+        }
+        prev = ch;
+    }
+}
+
+/// Title, tags and links of one note.
+fn parse(path: &Path, text: &str) -> (String, Vec<String>, Vec<NoteLink>) {
+    let (title, mut tags, body) = match frontmatter::split(text) {
+        Some((raw, body)) => (frontmatter::get_key(raw, "title"), front_matter_tags(raw), body),
+        None => (None, Vec::new(), text),
+    };
+    let mut heading = None;
+    let mut note_links = Vec::new();
+    for (_, line) in links::prose_lines(body) {
+        if let Some(level) = heading_level(line) {
+            heading.get_or_insert_with(|| line.trim_start()[level..].trim().trim_end_matches('#').trim().to_string());
+            continue;
+        }
+        inline_tags(line, &mut tags);
+        for link in links::extract_links(line) {
+            if link.kind == LinkKind::Markdown && !is_workspace_markdown(&link.target) {
+                continue;
+            }
+            match note_links.iter_mut().find(|l: &&mut NoteLink| l.kind == link.kind && l.target == link.target) {
+                Some(known) => known.count += 1,
+                None => note_links.push(NoteLink { kind: link.kind, target: link.target, count: 1 }),
+            }
+        }
+    }
+    let mut tags: Vec<String> = tags.into_iter().filter(|t| !t.is_empty()).map(|t| t.to_lowercase()).collect();
+    tags.sort();
+    tags.dedup();
+    let title = title
+        .filter(|t| !t.is_empty())
+        .or(heading.filter(|h| !h.is_empty()))
+        .unwrap_or_else(|| path.file_stem().unwrap_or_default().to_string_lossy().into_owned());
+    (title, tags, note_links)
+}
+
+impl Index {
+    fn insert(&mut self, note: Note) {
+        let paths = self.by_stem.entry(stem_key(&note.path)).or_default();
+        if !paths.contains(&note.path) {
+            paths.push(note.path.clone());
+        }
+        self.notes.insert(note.path.clone(), note);
+    }
+
+    fn take(&mut self, path: &str) -> Option<Note> {
+        let note = self.notes.remove(path)?;
+        let stem = stem_key(path);
+        if let Some(paths) = self.by_stem.get_mut(&stem) {
+            paths.retain(|p| p != path);
+            if paths.is_empty() {
+                self.by_stem.remove(&stem);
+            }
+        }
+        Some(note)
+    }
+
+    fn index_stems(&mut self) {
+        self.by_stem.clear();
+        for path in self.notes.keys() {
+            self.by_stem.entry(stem_key(path)).or_default().push(path.clone());
+        }
+    }
+
+    /// Add or refresh a note. A path seen before keeps its ID.
+    fn upsert(&mut self, path: &Path, text: &str, modified: i64) -> &Note {
+        let (title, tags, links) = parse(path, text);
+        let key = key(path);
+        let id = match self.notes.get(&key) {
+            Some(note) => note.id.clone(),
+            None => uuid::Uuid::new_v4().to_string(),
+        };
+        self.insert(Note { id, path: key.clone(), title, tags, links, modified });
+        &self.notes[&key]
+    }
+
+    /// Drop a note, or every note under a removed folder.
+    fn remove(&mut self, path: &Path) -> usize {
+        let gone = links::normalize_path(path);
+        let removed: Vec<String> = self.notes.keys().filter(|p| Path::new(p).starts_with(&gone)).cloned().collect();
+        for path in &removed {
+            self.take(path);
+        }
+        removed.len()
+    }
+
+    /// Move the notes at or under `from` to `to`, IDs and all.
+    fn rename(&mut self, from: &Path, to: &Path) -> usize {
+        let (from, to) = (links::normalize_path(from), links::normalize_path(to));
+        let moved: Vec<String> = self
+            .notes
+            .keys()
+            .filter(|path| Path::new(path).starts_with(&from))
+            .cloned()
+            .collect();
+        for old in &moved {
+            let mut note = self.take(old).unwrap();
+            let rest = Path::new(old).strip_prefix(&from).unwrap();
+            note.path = key(&to.join(rest));
+            self.insert(note);
+        }
+        moved.len()
+    }
+
+    fn by_path(&self, path: &Path) -> Option<&Note> {
+        self.notes.get(&key(path))
+    }
+
+    /// The note a link of `from` points at, if it is indexed. A wikilink
+    /// matching several files prefers one next to `from`, then the shortest
+    /// path.
+    fn resolve(&self, from: &Note, link: &NoteLink) -> Option<&Note> {
+        match link.kind {
+            LinkKind::Markdown => {
+                let target = links::resolve_markdown_target(Path::new(&from.path), &link.target)?;
+                self.by_path(&target)
+            }
+            LinkKind::Wikilink => {
+                let folder = Path::new(&from.path).parent();
+                self.by_stem
+                    .get(&links::wikilink_stem(&link.target)?)?
+                    .iter()
+                    .filter_map(|path| self.notes.get(path))
+                    .filter(|note| links::wikilink_matches(&link.target, Path::new(&note.path)))
+                    .min_by_key(|note| (Path::new(&note.path).parent() != folder, note.path.len(), &note.path))
+            }
+        }
+    }
+
+    fn sorted(mut notes: Vec<NoteSummary>) -> Vec<NoteSummary> {
+        notes.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()).then_with(|| a.path.cmp(&b.path)));
+        notes
+    }
+
+    fn links_from(&self, path: &Path) -> Vec<NoteSummary> {
+        let Some(note) = self.by_path(path) else { return Vec::new() };
+        let mut out: Vec<NoteSummary> = Vec::new();
+        for target in note.links.iter().filter_map(|link| self.resolve(note, link)) {
+            if !out.iter().any(|s| s.id == target.id) {
+                out.push(summary(target));
+            }
+        }
+        out
+    }
+
+    fn backlinks(&self, path: &Path) -> Vec<NoteSummary> {
+        let Some(target) = self.by_path(path) else { return Vec::new() };
+        let notes = self
+            .notes
+            .values()
+            .filter(|note| note.links.iter().any(|link| self.resolve(note, link).is_some_and(|n| n.id == target.id)))
+            .map(summary)
+            .collect();
+        Self::sorted(notes)
+    }
+
+    /// Notes under `root` and the links between them, one edge per ordered
+    /// pair. With `focus`, only the notes within `hops` links of it, in
+    /// either direction.
+    fn graph(&self, root: &Path, focus: Option<&Path>, hops: usize) -> AppResult<NoteGraph> {
+        let root = links::normalize_path(root);
+        let mut by_id: BTreeMap<&str, &Note> = BTreeMap::new();
+        for note in self.notes.values().filter(|n| Path::new(&n.path).starts_with(&root)) {
+            by_id.insert(&note.id, note);
+        }
+        let mut edges: Vec<GraphEdge> = Vec::new();
+        for note in by_id.values() {
+            for link in &note.links {
+                let Some(target) = self.resolve(note, link) else { continue };
+                if target.id == note.id || !by_id.contains_key(target.id.as_str()) {
+                    continue;
+                }
+                match edges.iter_mut().find(|e| e.from == note.id && e.to == target.id) {
+                    Some(edge) => edge.weight += link.count,
+                    None => edges.push(GraphEdge {
+                        from: note.id.clone(),
+                        to: target.id.clone(),
+                        kind: link.kind,
+                        weight: link.count,
+                    }),
+                }
+            }
+        }
+
+        if let Some(focus) = focus {
+            let start = self
+                .by_path(focus)
+                .filter(|n| by_id.contains_key(n.id.as_str()))
+                .ok_or_else(|| AppError::NotFound { path: focus.to_string_lossy().into_owned() })?;
+            let mut reached: HashSet<String> = HashSet::from([start.id.clone()]);
+            let mut frontier = vec![start.id.clone()];
+            for _ in 0..hops {
+                let mut next = Vec::new();
+                for edge in &edges {
+                    for (a, b) in [(&edge.from, &edge.to), (&edge.to, &edge.from)] {
+                        if frontier.contains(a) && reached.insert(b.clone()) {
+                            next.push(b.clone());
+                        }
+                    }
+                }
+                frontier = next;
+            }
+            by_id.retain(|id, _| reached.contains(*id));
+            edges.retain(|e| reached.contains(&e.from) && reached.contains(&e.to));
+        }
+
+        let nodes = by_id.values().map(|note| summary(note)).collect();
+        Ok(NoteGraph { nodes, edges })
+    }
+
+    /// Every tag with its note count, most used first. A nested tag
+    /// (`project/alpha`) also counts towards its parents.
+    fn tags(&self) -> Vec<TagCount> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for note in self.notes.values() {
+            let mut seen: Vec<&str> = Vec::new();
+            for tag in &note.tags {
+                for (i, _) in tag.match_indices('/').chain([(tag.len(), "")]) {
+                    if !seen.contains(&&tag[..i]) {
+                        seen.push(&tag[..i]);
+                    }
+                }
+            }
+            for tag in seen {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+        let mut tags: Vec<TagCount> = counts.into_iter().map(|(tag, notes)| TagCount { tag: tag.to_string(), notes }).collect();
+        tags.sort_by(|a, b| b.notes.cmp(&a.notes).then_with(|| a.tag.cmp(&b.tag)));
+        tags
+    }
+
+    /// Notes tagged `tag` or one of its nested tags, case-insensitive.
+    fn with_tag(&self, tag: &str) -> Vec<NoteSummary> {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        let nested = format!("{}/", tag);
+        let notes = self
+            .notes
+            .values()
+            .filter(|note| note.tags.iter().any(|t| *t == tag || t.starts_with(&nested)))
+            .map(summary)
+            .collect();
+        Self::sorted(notes)
+    }
+
+    /// Notes whose title contains `query`: exact matches first, then
+    /// prefixes, then the rest alphabetically.
+    fn find_by_title(&self, query: &str, limit: usize) -> Vec<NoteSummary> {
+        let query = query.trim().to_lowercase();
+        let mut hits: Vec<(u8, String, &Note)> = self
+            .notes
+            .values()
+            .filter_map(|note| {
+                let title = note.title.to_lowercase();
+                let rank = if title == query {
+                    0
+                } else if title.starts_with(&query) {
+                    1
+                } else if title.contains(&query) {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, title, note))
+            })
+            .collect();
+        hits.sort_by(|a, b| (a.0, &a.1, &a.2.path).cmp(&(b.0, &b.1, &b.2.path)));
+        hits.into_iter().take(limit).map(|(_, _, note)| summary(note)).collect()
+    }
+}
+
+fn store_file(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    Ok(dir.join("notes-index.json"))
+}
+
+fn load_index(app: &tauri::AppHandle) -> AppResult<Index> {
+    let path = store_file(app)?;
+    if !path.exists() {
+        return Ok(Index::default());
+    }
+    let bytes = std::fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+    let mut index: Index = serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+        crate::config_recovery::recover(app, &path, &e);
+        Index::default()
+    });
+    index.index_stems();
+    Ok(index)
+}
+
+/// Run `f` on the index, loading it from disk the first time.
+pub(crate) fn with_index<R>(app: &tauri::AppHandle, f: impl FnOnce(&mut Index) -> R) -> AppResult<R> {
+    let db = app.state::<NotesDb>();
+    let mut guard = db.index.lock().unwrap();
+    if guard.is_none() {
+        *guard = Some(load_index(app)?);
+    }
+    Ok(f(guard.as_mut().unwrap()))
+}
+
+fn save_now(app: &tauri::AppHandle) -> AppResult<()> {
+    let bytes = with_index(app, |index| serde_json::to_vec(index))??;
+    crate::files::write_atomic(&store_file(app)?, &bytes)
+}
+
+/// Write the index out after `SAVE_DELAY`, once for any number of changes.
+fn schedule_save(app: &tauri::AppHandle) {
+    if app.state::<NotesDb>().save_pending.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(SAVE_DELAY);
+        handle.state::<NotesDb>().save_pending.store(false, Ordering::SeqCst);
+        if let Err(e) = save_now(&handle) {
+            tracing::warn!("notes index not saved: {}", e);
+        }
+    });
+}
+
+/// Whether a watcher path is a note worth indexing: Markdown, and not
+/// inside a hidden folder or `node_modules` of the root.
+fn is_indexed_path(path: &Path, root: &Path) -> bool {
+    let Ok(rel) = path.strip_prefix(root) else { return false };
+    let name = rel.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    is_workspace_markdown(&name) && !rel.components().any(|c| is_workspace_hidden(&c.as_os_str().to_string_lossy()))
+}
+
+/// Read and index one note.
+pub fn index_file(app: &tauri::AppHandle, path: &Path) -> AppResult<NoteSummary> {
+    let max_bytes = crate::settings::current(app).max_open_bytes;
+    let text = crate::files::read_text(path, Some(max_bytes))?;
+    let modified = modified_ms(path);
+    let note = with_index(app, |index| summary(index.upsert(path, &text, modified)))?;
+    schedule_save(app);
+    Ok(note)
+}
+
+/// Forget a note, or every note under a folder. Returns how many went.
+pub fn remove_file(app: &tauri::AppHandle, path: &Path) -> AppResult<usize> {
+    let removed = with_index(app, |index| index.remove(path))?;
+    if removed > 0 {
+        schedule_save(app);
+    }
+    Ok(removed)
+}
+
+/// Carry the IDs of the notes at or under `from` over to `to`, after a move
+/// the app made itself. The watcher sees it too; handling it twice is fine.
+pub fn renamed(app: &tauri::AppHandle, from: &Path, to: &Path) -> AppResult<()> {
+    if with_index(app, |index| index.rename(from, to))? > 0 {
+        schedule_save(app);
+    }
+    Ok(())
+}
+
+/// The ID of the note at `path`, indexing it first if it is new.
+pub fn document_id(app: &tauri::AppHandle, path: &Path) -> AppResult<String> {
+    if let Some(id) = with_index(app, |index| index.by_path(path).map(|n| n.id.clone()))? {
+        return Ok(id);
+    }
+    Ok(index_file(app, path)?.id)
+}
+
+fn handle_event(app: &tauri::AppHandle, root: &Path, event: notify::Event) {
+    let db = app.state::<NotesDb>();
+    let result = match (event.kind, event.paths.as_slice()) {
+        (EventKind::Access(_), _) => Ok(()),
+        (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [from, to]) => {
+            renamed(app, from, to).and_then(|_| refresh_path(app, root, to))
+        }
+        (EventKind::Modify(ModifyKind::Name(mode)), [path]) => {
+            // Some platforms report the two halves of a move separately, or
+            // only that a name changed; which half is told by what's on disk.
+            let gone = mode == RenameMode::From || (mode != RenameMode::To && !path.exists());
+            if gone {
+                // A file moved out of the root never gets its other half, so
+                // an earlier unpaired one is dropped when the next arrives.
+                let unpaired = db.renamed_from.lock().unwrap().replace(path.clone());
+                unpaired.map_or(Ok(()), |p| remove_file(app, &p).map(|_| ()))
+            } else {
+                let from = db.renamed_from.lock().unwrap().take();
+                match from {
+                    Some(from) => renamed(app, &from, path).and_then(|_| refresh_path(app, root, path)),
+                    None => refresh_path(app, root, path),
+                }
+            }
+        }
+        (EventKind::Remove(_), paths) => paths.iter().try_for_each(|p| remove_file(app, p).map(|_| ())),
+        (_, paths) => paths.iter().try_for_each(|p| refresh_path(app, root, p)),
+    };
+    if let Err(e) = result {
+        tracing::debug!("notes index update failed: {}", e);
+    }
+}
+
+/// Re-read whatever is now at `path`: a note, a folder moved in, or nothing.
+fn refresh_path(app: &tauri::AppHandle, root: &Path, path: &Path) -> AppResult<()> {
+    if path.is_dir() {
+        let max_bytes = crate::settings::current(app).max_open_bytes;
+        for file in links::collect_markdown_files(path, max_bytes) {
+            index_file(app, &file)?;
+        }
+        return Ok(());
+    }
+    if !path.exists() {
+        return remove_file(app, path).map(|_| ());
+    }
+    if is_indexed_path(path, root) {
+        index_file(app, path)?;
+    } else {
+        // Moved into a hidden folder or out of the root.
+        remove_file(app, path)?;
+    }
+    Ok(())
+}
+
+fn watch(app: &tauri::AppHandle, root: &Path) -> AppResult<()> {
+    let handle = app.clone();
+    let watch_root = root.to_path_buf();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            handle_event(&handle, &watch_root, event);
+        }
+    })
+    .map_err(|e| AppError::Internal(format!("notes index watcher: {}", e)))?;
+    watcher
+        .watch(root, RecursiveMode::Recursive)
+        .map_err(|e| AppError::Internal(format!("watch {}: {}", root.display(), e)))?;
+    *app.state::<NotesDb>().watcher.lock().unwrap() = Some(watcher);
+    Ok(())
+}
+
+/// Index every note under `root`, replacing whatever was indexed before but
+/// keeping the IDs of files already known, and watch it from now on. Unless
+/// `full`, files unchanged since they were last read are not read again.
+fn refresh(app: &tauri::AppHandle, root: &Path, full: bool) -> AppResult<IndexSummary> {
+    let max_bytes = crate::settings::current(app).max_open_bytes;
+    let files = links::collect_markdown_files(root, max_bytes);
+    let mut previous = with_index(app, |index| index.notes.clone())?;
+    let mut next = Index { root: Some(key(root)), ..Index::default() };
+    for file in files {
+        let modified = modified_ms(&file);
+        match previous.remove(&key(&file)) {
+            Some(note) if !full && note.modified == modified => {
+                next.insert(note);
+                continue;
+            }
+            Some(note) => next.insert(note),
+            None => {}
+        }
+        if let Ok(text) = crate::files::read_text(&file, Some(max_bytes)) {
+            next.upsert(&file, &text, modified);
+        }
+    }
+    let summary = IndexSummary { root: key(root), notes: next.notes.len() };
+    with_index(app, |index| *index = next)?;
+    save_now(app)?;
+    watch(app, root)?;
+    Ok(summary)
+}
+
+/// Full refresh of the index from `root`, which becomes the watched root.
+pub fn rebuild(app: &tauri::AppHandle, root: &Path) -> AppResult<IndexSummary> {
+    if !root.is_dir() {
+        return Err(AppError::InvalidInput(format!("workspace path is not a directory: {}", root.display())));
+    }
+    refresh(app, root, true)
+}
+
+/// Startup: catch up on changes made while the app was closed and watch the
+/// root indexed last time, if any.
+pub fn resume(app: &tauri::AppHandle) -> AppResult<()> {
+    let Some(root) = with_index(app, |index| index.root.clone())? else { return Ok(()) };
+    let root = PathBuf::from(root);
+    if !root.is_dir() {
+        return Ok(());
+    }
+    refresh(app, &root, false).map(|_| ())
+}
+
+/// The link graph of `root`, indexing it first when it isn't the indexed
+/// workspace (or inside it).
+pub fn graph(app: &tauri::AppHandle, root: &Path, focus: Option<&Path>, hops: usize) -> AppResult<NoteGraph> {
+    let indexed = with_index(app, |index| index.root.clone())?;
+    if !indexed.is_some_and(|r| links::normalize_path(root).starts_with(r)) {
+        rebuild(app, root)?;
+    }
+    with_index(app, |index| index.graph(root, focus, hops))?
+}
+
+pub fn links_from(app: &tauri::AppHandle, path: &Path) -> AppResult<Vec<NoteSummary>> {
+    with_index(app, |index| index.links_from(path))
+}
+
+pub fn backlinks(app: &tauri::AppHandle, path: &Path) -> AppResult<Vec<NoteSummary>> {
+    with_index(app, |index| index.backlinks(path))
+}
+
+pub fn tags(app: &tauri::AppHandle) -> AppResult<Vec<TagCount>> {
+    with_index(app, |index| index.tags())
+}
+
+pub fn with_tag(app: &tauri::AppHandle, tag: &str) -> AppResult<Vec<NoteSummary>> {
+    with_index(app, |index| index.with_tag(tag))
+}
+
+pub fn find_by_title(app: &tauri::AppHandle, query: &str, limit: usize) -> AppResult<Vec<NoteSummary>> {
+    with_index(app, |index| index.find_by_title(query, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_title_tags_and_note_links() {
+        let text = "---\ntags:\n  - Project/Alpha\n  - draft\n---\n# Weekly *notes*\n\n\
+                    Status #todo and #1, see [[Plan|the plan]] and [spec](docs/spec.md#intro).\n\
+                    `#code` [site](https://example.com) ![img](a.png) url/#frag\n```\n#not-a-tag [[Nope]]\n```\n";
+        let (title, tags, links) = parse(Path::new("/w/week.md"), text);
+        assert_eq!(title, "Weekly *notes*");
+        assert_eq!(tags, ["draft", "project/alpha", "todo"]);
+        let targets: Vec<&str> = links.iter().map(|l| l.target.as_str()).collect();
+        assert_eq!(targets, ["Plan", "docs/spec.md"]);
+
+        let (title, tags, _) = parse(Path::new("/w/bare.md"), "---\ntitle: 'Bare'\ntags: [a, \"B\"]\n---\ntext\n");
+        assert_eq!((title.as_str(), tags), ("Bare", vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(parse(Path::new("/w/untitled.md"), "just text").0, "untitled");
+    }
+
+    #[test]
+    fn ids_survive_edits_and_renames_and_links_resolve() {
+        let mut index = Index::default();
+        let plan_id = index.upsert(Path::new("/w/plan.md"), "# Plan\n#project/alpha\n", 1).id.clone();
+        index.upsert(Path::new("/w/week.md"), "see [[plan]] and [again](plan.md) #project\n", 1);
+        index.upsert(Path::new("/w/sub/other.md"), "[up](../week.md)\n", 1);
+        assert_eq!(index.upsert(Path::new("/w/plan.md"), "# The Plan\n", 2).id, plan_id);
+
+        let back: Vec<String> = index.backlinks(Path::new("/w/plan.md")).into_iter().map(|n| n.path).collect();
+        assert_eq!(back, ["/w/week.md"]);
+        assert_eq!(index.links_from(Path::new("/w/week.md")).len(), 1);
+
+        assert_eq!(index.rename(Path::new("/w/plan.md"), Path::new("/w/archive/plan.md")), 1);
+        assert_eq!(index.by_path(Path::new("/w/archive/plan.md")).unwrap().id, plan_id);
+        // The wikilink still finds it by name; the relative link no longer does.
+        assert_eq!(index.links_from(Path::new("/w/week.md"))[0].id, plan_id);
+
+        assert_eq!(index.rename(Path::new("/w/sub"), Path::new("/w/moved")), 1);
+        assert_eq!(index.find_by_title("plan", 10)[0].title, "The Plan");
+        assert_eq!(index.tags(), [TagCount { tag: "project".to_string(), notes: 1 }]);
+        assert_eq!(index.with_tag("#Project").len(), 1);
+        assert_eq!(index.remove(Path::new("/w/archive")), 1);
+        assert!(index.backlinks(Path::new("/w/archive/plan.md")).is_empty());
+    }
+
+    #[test]
+    fn wikilinks_resolve_through_the_stem_map_after_a_reload() {
+        let mut index = Index::default();
+        index.upsert(Path::new("/w/Plan.md"), "# Plan\n", 1);
+        index.upsert(Path::new("/w/old/plan.md"), "# Old plan\n", 1);
+        index.upsert(Path::new("/w/week.md"), "[[PLAN]] [[old/plan.md]]\n", 1);
+        let titles = |index: &Index| -> Vec<String> {
+            index.links_from(Path::new("/w/week.md")).into_iter().map(|n| n.title).collect()
+        };
+        assert_eq!(titles(&index), ["Plan", "Old plan"]);
+
+        let mut reloaded: Index = serde_json::from_slice(&serde_json::to_vec(&index).unwrap()).unwrap();
+        assert!(reloaded.by_stem.is_empty());
+        reloaded.index_stems();
+        assert_eq!(titles(&reloaded), ["Plan", "Old plan"]);
+
+        reloaded.rename(Path::new("/w/Plan.md"), Path::new("/w/Roadmap.md"));
+        reloaded.remove(Path::new("/w/old"));
+        assert!(titles(&reloaded).is_empty());
+        assert!(reloaded.by_stem.get("plan").is_none());
+    }
+
+    #[test]
+    fn graph_merges_links_per_pair_and_limits_hops() {
+        let mut index = Index::default();
+        for (path, text) in [
+            ("/w/a.md", "[[b]] [[b]] [b](b.md) [[a]] [[missing]]
+"),
+            ("/w/b.md", "[[c]]
+"),
+            ("/w/c.md", "[[d]]
+"),
+            ("/w/d.md", "[[a]]
+"),
+            ("/elsewhere/e.md", "[[a]]
+"),
+        ] {
+            index.upsert(Path::new(path), text, 1);
+        }
+        let id = |path: &str| index.by_path(Path::new(path)).unwrap().id.clone();
+
+        let graph = index.graph(Path::new("/w"), None, 0).unwrap();
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.edges.len(), 4);
+        let a_to_b = graph.edges.iter().find(|e| e.from == id("/w/a.md")).unwrap();
+        assert_eq!((a_to_b.to.clone(), a_to_b.kind, a_to_b.weight), (id("/w/b.md"), LinkKind::Wikilink, 3));
+
+        let near = index.graph(Path::new("/w"), Some(Path::new("/w/b.md")), 1).unwrap();
+        let mut paths: Vec<String> = near.nodes.into_iter().map(|n| n.path).collect();
+        paths.sort();
+        assert_eq!(paths, ["/w/a.md", "/w/b.md", "/w/c.md"]);
+        assert_eq!(near.edges.len(), 2);
+        assert!(index.graph(Path::new("/w"), Some(Path::new("/elsewhere/e.md")), 1).is_err());
+    }
+}
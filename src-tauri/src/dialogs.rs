@@ -0,0 +1,124 @@
+//! Open/save dialogs that start in the folder the user last worked in. The
+//! last directory comes from the most recent dialog choice or opened file
+//! and is persisted to `<app_data>/last-directory.json`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::Deserialize;
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, FileDialogBuilder, FilePath};
+
+use crate::error::{AppError, AppResult};
+
+/// In-memory copy of the remembered directory; `None` until first used.
+#[derive(Default)]
+pub struct LastDirectory(pub Mutex<Option<PathBuf>>);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileFilter {
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+fn store_file(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    Ok(dir.join("last-directory.json"))
+}
+
+fn load(app: &tauri::AppHandle) -> Option<PathBuf> {
+    let bytes = std::fs::read(store_file(app).ok()?).ok()?;
+    serde_json::from_slice::<String>(&bytes).ok().map(PathBuf::from)
+}
+
+/// Remember the folder of `path` (a file, or a folder itself).
+pub fn remember(app: &tauri::AppHandle, path: &Path) -> AppResult<()> {
+    let dir = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    if dir.as_os_str().is_empty() {
+        return Ok(());
+    }
+    let state = app.state::<LastDirectory>();
+    let mut last = state.0.lock().unwrap();
+    if last.as_deref() == Some(dir) {
+        return Ok(());
+    }
+    *last = Some(dir.to_path_buf());
+    let bytes = serde_json::to_vec(&dir.to_string_lossy())?;
+    crate::files::write_atomic(&store_file(app)?, &bytes)
+}
+
+/// `requested` when it is a folder, otherwise the remembered one if it
+/// still exists.
+pub fn start_dir(app: &tauri::AppHandle, requested: Option<&str>) -> Option<PathBuf> {
+    if let Some(dir) = requested.map(PathBuf::from).filter(|d| d.is_dir()) {
+        return Some(dir);
+    }
+    let state = app.state::<LastDirectory>();
+    let mut last = state.0.lock().unwrap();
+    if last.is_none() {
+        *last = load(app);
+    }
+    last.clone().filter(|d| d.is_dir())
+}
+
+fn builder(app: &tauri::AppHandle, start: Option<PathBuf>, filters: &[FileFilter]) -> FileDialogBuilder<tauri::Wry> {
+    let mut dialog = app.dialog().file();
+    if let Some(dir) = start {
+        dialog = dialog.set_directory(dir);
+    }
+    for filter in filters {
+        let extensions: Vec<&str> = filter.extensions.iter().map(String::as_str).collect();
+        dialog = dialog.add_filter(&filter.name, &extensions);
+    }
+    dialog
+}
+
+fn to_path(file: FilePath) -> AppResult<PathBuf> {
+    file.into_path().map_err(|e| AppError::Internal(format!("dialog path: {}", e)))
+}
+
+/// Show the open dialog; `None` when cancelled. The chosen folder is
+/// remembered for next time.
+pub async fn open(
+    app: tauri::AppHandle,
+    start_dir: Option<String>,
+    multiple: bool,
+    filters: Vec<FileFilter>,
+) -> AppResult<Option<Vec<String>>> {
+    let start = self::start_dir(&app, start_dir.as_deref());
+    let dialog = builder(&app, start, &filters);
+    // The blocking pickers must stay off the main thread.
+    let picked = tokio::task::spawn_blocking(move || {
+        if multiple {
+            dialog.blocking_pick_files()
+        } else {
+            dialog.blocking_pick_file().map(|f| vec![f])
+        }
+    })
+    .await?;
+    let Some(files) = picked else { return Ok(None) };
+    let paths = files.into_iter().map(to_path).collect::<AppResult<Vec<_>>>()?;
+    if let Some(first) = paths.first() {
+        remember(&app, first)?;
+    }
+    Ok(Some(paths.into_iter().map(|p| p.to_string_lossy().into_owned()).collect()))
+}
+
+pub async fn save(
+    app: tauri::AppHandle,
+    suggested_name: Option<String>,
+    start_dir: Option<String>,
+    filters: Vec<FileFilter>,
+) -> AppResult<Option<String>> {
+    let start = self::start_dir(&app, start_dir.as_deref());
+    let mut dialog = builder(&app, start, &filters);
+    if let Some(name) = suggested_name {
+        dialog = dialog.set_file_name(name);
+    }
+    let Some(file) = tokio::task::spawn_blocking(move || dialog.blocking_save_file()).await? else {
+        return Ok(None);
+    };
+    let path = to_path(file)?;
+    remember(&app, &path)?;
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
@@ -0,0 +1,279 @@
+//! The error type returned by every Tauri command.
+//!
+//! Reaches the frontend as `{ kind, message, path? }` (plus variant-specific
+//! fields such as `size`/`limit`) so it can branch on `kind` instead of
+//! parsing message strings.
+
+#[derive(Debug)]
+pub enum AppError {
+    NotFound { path: String },
+    PermissionDenied { path: Option<String>, message: String },
+    AlreadyExists { path: String },
+    /// The target changed underneath us (e.g. modified on disk since load).
+    Conflict { path: Option<String>, message: String },
+    /// A safety check declined the request (e.g. launching an executable).
+    Refused { path: String, message: String },
+    /// Another write to the same path did not finish in time.
+    Busy { path: String },
+    FileTooLarge { path: String, size: u64, limit: u64 },
+    /// Internal editor markers (`__CURSOR__`, ...) were about to be saved.
+    SentinelLeak { path: String, markers: Vec<String> },
+    /// Content sniffing says this is binary; the UI asks before forcing it.
+    NotTextFile { path: String },
+    /// A save read back differently from what was written. Both are
+    /// content hashes.
+    VerificationFailed { path: String, expected: String, actual: String },
+    /// The display at this index went away (e.g. unplugged since listing).
+    MonitorNotFound { index: usize },
+    /// A remote service could not be reached (`status: None`) or answered
+    /// with an unexpected HTTP status.
+    Network { status: Option<u16>, message: String },
+    InvalidInput(String),
+    /// The feature is switched off in this deployment (e.g. single-window mode).
+    Disabled(String),
+    Io { path: Option<String>, message: String },
+    /// Anything else: join errors, webview failures, opaque module errors.
+    Internal(String),
+}
+
+impl AppError {
+    /// Classify an `io::Error` that happened while touching `path`.
+    pub fn io(path: impl AsRef<std::path::Path>, err: std::io::Error) -> Self {
+        let path = path.as_ref().to_string_lossy().into_owned();
+        match err.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound { path },
+            std::io::ErrorKind::PermissionDenied => AppError::PermissionDenied {
+                path: Some(path),
+                message: err.to_string(),
+            },
+            std::io::ErrorKind::AlreadyExists => AppError::AlreadyExists { path },
+            _ => AppError::Io { path: Some(path), message: err.to_string() },
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "notFound",
+            AppError::PermissionDenied { .. } => "permissionDenied",
+            AppError::AlreadyExists { .. } => "alreadyExists",
+            AppError::Conflict { .. } => "conflict",
+            AppError::Refused { .. } => "refused",
+            AppError::Busy { .. } => "busy",
+            AppError::FileTooLarge { .. } => "fileTooLarge",
+            AppError::SentinelLeak { .. } => "sentinelLeak",
+            AppError::NotTextFile { .. } => "notTextFile",
+            AppError::VerificationFailed { .. } => "verificationFailed",
+            AppError::MonitorNotFound { .. } => "monitorNotFound",
+            AppError::Network { .. } => "network",
+            AppError::InvalidInput(_) => "invalidInput",
+            AppError::Disabled(_) => "disabled",
+            AppError::Io { .. } => "io",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            AppError::NotFound { path }
+            | AppError::AlreadyExists { path }
+            | AppError::Busy { path }
+            | AppError::Refused { path, .. }
+            | AppError::FileTooLarge { path, .. }
+            | AppError::SentinelLeak { path, .. }
+            | AppError::VerificationFailed { path, .. }
+            | AppError::NotTextFile { path } => Some(path),
+            AppError::PermissionDenied { path, .. }
+            | AppError::Conflict { path, .. }
+            | AppError::Io { path, .. } => path.as_deref(),
+            AppError::MonitorNotFound { .. }
+            | AppError::Network { .. }
+            | AppError::InvalidInput(_)
+            | AppError::Disabled(_)
+            | AppError::Internal(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::NotFound { path } => write!(f, "not found: {}", path),
+            AppError::AlreadyExists { path } => write!(f, "already exists: {}", path),
+            AppError::Busy { path } => write!(f, "file is busy: {}", path),
+            AppError::FileTooLarge { size, limit, .. } => {
+                write!(f, "file is {} bytes, over the {} byte limit", size, limit)
+            }
+            AppError::SentinelLeak { markers, .. } => {
+                write!(f, "refusing to save internal editor markers: {}", markers.join(", "))
+            }
+            AppError::NotTextFile { path } => write!(f, "does not look like a text file: {}", path),
+            AppError::VerificationFailed { path, .. } => write!(f, "saved file does not match what was written: {}", path),
+            AppError::MonitorNotFound { index } => write!(f, "no monitor at index {}", index),
+            AppError::PermissionDenied { message, .. }
+            | AppError::Conflict { message, .. }
+            | AppError::Refused { message, .. }
+            | AppError::Network { message, .. }
+            | AppError::Io { message, .. } => f.write_str(message),
+            AppError::InvalidInput(message) | AppError::Disabled(message) | AppError::Internal(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl AppError {
+    /// The payload the frontend receives.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut map = serde_json::Map::new();
+        map.insert("kind".into(), self.kind().into());
+        map.insert("message".into(), self.to_string().into());
+        if let Some(path) = self.path() {
+            map.insert("path".into(), path.into());
+        }
+        if let AppError::FileTooLarge { size, limit, .. } = self {
+            map.insert("size".into(), (*size).into());
+            map.insert("limit".into(), (*limit).into());
+        }
+        if let AppError::SentinelLeak { markers, .. } = self {
+            map.insert("markers".into(), markers.clone().into());
+        }
+        if let AppError::VerificationFailed { expected, actual, .. } = self {
+            map.insert("expected".into(), expected.as_str().into());
+            map.insert("actual".into(), actual.as_str().into());
+        }
+        if let AppError::MonitorNotFound { index } = self {
+            map.insert("index".into(), (*index).into());
+        }
+        if let AppError::Network { status: Some(status), .. } = self {
+            map.insert("status".into(), (*status).into());
+        }
+        serde_json::Value::Object(map)
+    }
+}
+
+/// How a failed command's error reaches the frontend; Tauri converts it once
+/// per failed call, which makes this the place to log command errors.
+impl From<AppError> for tauri::ipc::InvokeError {
+    fn from(err: AppError) -> Self {
+        tracing::warn!(kind = err.kind(), path = err.path(), "{}", err);
+        tauri::ipc::InvokeError(err.to_json())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::PermissionDenied => AppError::PermissionDenied { path: None, message: err.to_string() },
+            _ => AppError::Io { path: None, message: err.to_string() },
+        }
+    }
+}
+
+/// Module-level helpers (AI storage, window state, ...) still report plain
+/// strings; they surface as `internal`.
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Internal(message)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(err: tauri::Error) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        AppError::Internal(format!("worker join: {}", err))
+    }
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_kind_message_and_path() {
+        let v = AppError::NotFound { path: "/a.md".into() }.to_json();
+        assert_eq!(v["kind"], "notFound");
+        assert_eq!(v["message"], "not found: /a.md");
+        assert_eq!(v["path"], "/a.md");
+    }
+
+    #[test]
+    fn commands_reject_with_the_same_payload() {
+        let err = AppError::Busy { path: "/a.md".into() };
+        let expected = err.to_json();
+        assert_eq!(tauri::ipc::InvokeError::from(err).0, expected);
+    }
+
+    #[test]
+    fn omits_path_when_absent() {
+        let v = AppError::InvalidInput("bad".into()).to_json();
+        assert_eq!(v["kind"], "invalidInput");
+        assert!(v.get("path").is_none());
+    }
+
+    #[test]
+    fn file_too_large_carries_size_and_limit() {
+        let v = AppError::FileTooLarge { path: "/x".into(), size: 10, limit: 4 }.to_json();
+        assert_eq!(v["kind"], "fileTooLarge");
+        assert_eq!(v["size"], 10);
+        assert_eq!(v["limit"], 4);
+    }
+
+    #[test]
+    fn sentinel_leak_lists_markers() {
+        let err = AppError::SentinelLeak { path: "/a.md".into(), markers: vec!["__CURSOR__".into()] };
+        let v = err.to_json();
+        assert_eq!(v["kind"], "sentinelLeak");
+        assert_eq!(v["markers"], serde_json::json!(["__CURSOR__"]));
+    }
+
+    #[test]
+    fn verification_failure_carries_both_hashes() {
+        let err = AppError::VerificationFailed { path: "/a.md".into(), expected: "e".into(), actual: "a".into() };
+        let v = err.to_json();
+        assert_eq!(v["kind"], "verificationFailed");
+        assert_eq!(v["path"], "/a.md");
+        assert_eq!(v["expected"], "e");
+        assert_eq!(v["actual"], "a");
+    }
+
+    #[test]
+    fn monitor_not_found_carries_index() {
+        let v = AppError::MonitorNotFound { index: 2 }.to_json();
+        assert_eq!(v["kind"], "monitorNotFound");
+        assert_eq!(v["index"], 2);
+    }
+
+    #[test]
+    fn network_errors_carry_the_http_status_when_there_is_one() {
+        let v = AppError::Network { status: Some(422), message: "rejected".into() }.to_json();
+        assert_eq!(v["kind"], "network");
+        assert_eq!(v["status"], 422);
+        let v = AppError::Network { status: None, message: "offline".into() }.to_json();
+        assert!(v.get("status").is_none());
+    }
+
+    #[test]
+    fn io_errors_are_classified_by_kind() {
+        let not_found = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        assert_eq!(AppError::io("/a", not_found).kind(), "notFound");
+        let denied = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "no");
+        assert_eq!(AppError::io("/a", denied).kind(), "permissionDenied");
+        let other = std::io::Error::other("disk on fire");
+        assert_eq!(AppError::io("/a", other).kind(), "io");
+    }
+}
@@ -0,0 +1,147 @@
+//! Opening non-Markdown link targets (PDFs, spreadsheets, images) with the
+//! OS default application.
+//!
+//! A document is untrusted input, so a link may only open files under a
+//! folder the user is already working in, and never a program unless the
+//! `allowExecutables` setting says so.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult};
+
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "com", "bat", "cmd", "msi", "ps1", "vbs", "vbe", "js", "jse", "wsf", "wsh", "scr",
+    "pif", "cpl", "lnk", "reg", "jar", "sh", "bash", "zsh", "fish", "command", "app", "run",
+    "bin", "appimage", "desktop", "py", "pl", "rb",
+    // Windows files whose handler runs code: HTML apps, console snap-ins,
+    // compiled help, setup scripts, PowerShell modules and shortcut formats.
+    "hta", "msc", "chm", "inf", "psm1", "url", "scf", "appref-ms", "settingcontent-ms",
+];
+
+pub fn is_executable(path: &Path) -> bool {
+    let by_extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| EXECUTABLE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false);
+    if by_extension {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        // Extensionless files with an exec bit are scripts or binaries.
+        if path.extension().is_none() {
+            if let Ok(meta) = std::fs::metadata(path) {
+                return meta.is_file() && meta.permissions().mode() & 0o111 != 0;
+            }
+        }
+    }
+    false
+}
+
+/// Validate a link target: it must exist, sit under one of `roots` (after
+/// resolving symlinks and `..`), and not be a program unless allowed.
+pub fn check_target(path: &str, roots: &[PathBuf], allow_executables: bool) -> AppResult<PathBuf> {
+    let target = std::fs::canonicalize(path).map_err(|e| AppError::io(path, e))?;
+    let inside = roots
+        .iter()
+        .filter_map(|root| std::fs::canonicalize(root).ok())
+        .any(|root| target.starts_with(root));
+    if !inside {
+        return Err(AppError::Refused {
+            path: path.to_string(),
+            message: format!("{} is outside the open documents and workspaces", path),
+        });
+    }
+    if !allow_executables && is_executable(&target) {
+        return Err(AppError::Refused {
+            path: path.to_string(),
+            message: format!("refusing to launch executable {}", path),
+        });
+    }
+    Ok(target)
+}
+
+/// Hand `path` to the platform's "open with default app" launcher.
+pub fn open_with_default_app(path: &Path) -> AppResult<()> {
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("explorer.exe")
+            .arg(path)
+            .spawn()
+            .map_err(|e| AppError::Internal(format!("explorer: {}", e)))?;
+        return Ok(());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(path)
+            .spawn()
+            .map_err(|e| AppError::Internal(format!("open: {}", e)))?;
+        return Ok(());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(path)
+            .spawn()
+            .map_err(|e| AppError::Internal(format!("xdg-open: {}", e)))?;
+        return Ok(());
+    }
+
+    #[allow(unreachable_code)]
+    Err(AppError::Internal("open_path_external: unsupported platform".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_targets_outside_roots_and_executables() {
+        let root = std::env::temp_dir().join(format!("mermark-external-{}", uuid::Uuid::new_v4()));
+        let docs = root.join("docs");
+        std::fs::create_dir_all(&docs).unwrap();
+        std::fs::write(docs.join("report.pdf"), "%PDF").unwrap();
+        std::fs::write(docs.join("setup.EXE"), "MZ").unwrap();
+        std::fs::write(root.join("secret.pdf"), "%PDF").unwrap();
+        let roots = [docs.clone()];
+
+        let ok = check_target(&docs.join("report.pdf").to_string_lossy(), &roots, false).unwrap();
+        assert!(ok.ends_with("report.pdf"));
+
+        let escaped = docs.join("../secret.pdf");
+        let err = check_target(&escaped.to_string_lossy(), &roots, false).unwrap_err();
+        assert_eq!(err.kind(), "refused");
+
+        let exe = docs.join("setup.EXE").to_string_lossy().into_owned();
+        assert_eq!(check_target(&exe, &roots, false).unwrap_err().kind(), "refused");
+        assert!(check_target(&exe, &roots, true).is_ok());
+
+        let missing = docs.join("nope.pdf").to_string_lossy().into_owned();
+        assert_eq!(check_target(&missing, &roots, false).unwrap_err().kind(), "notFound");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn windows_handlers_that_run_code_count_as_executables() {
+        for name in [
+            "app.hta",           // HTML application
+            "console.msc",       // management console snap-in
+            "manual.CHM",        // compiled help, can run script
+            "driver.inf",        // setup information
+            "module.psm1",       // PowerShell module
+            "site.url",          // internet shortcut
+            "explorer.scf",      // shell command file
+            "tool.appref-ms",    // ClickOnce shortcut
+            "panel.settingcontent-ms", // Settings shortcut
+        ] {
+            assert!(is_executable(Path::new(name)), "{}", name);
+        }
+        assert!(!is_executable(Path::new("notes.md")));
+        assert!(!is_executable(Path::new("report.pdf")));
+    }
+}
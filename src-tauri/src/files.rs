@@ -0,0 +1,595 @@
+//! Document I/O: size-guarded reads, atomic save, save-with-backup, binary
+//! asset writes and crash drafts.
+//!
+//! Reads and saves report an xxh3 hash of the content so the frontend can
+//! track dirtiness by comparing hashes, and so saves can refuse to clobber a
+//! file that changed on disk since it was loaded.
+//!
+//! Every writer serializes on a per-path async lock so autosave, backups and
+//! drafts for the same document can never interleave. The registry also
+//! remembers the mtime of our own last write per path, which is what the
+//! frontend watcher asks (`is_own_write`) to filter self-inflicted events.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
+use tokio::sync::OwnedMutexGuard;
+
+use crate::ai::paths::hash_path;
+use crate::error::{AppError, AppResult};
+use crate::markdown::invisible::SENTINELS;
+
+/// How long a writer waits for another write on the same path before giving
+/// up with a busy error instead of racing it.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default for the `maxOpenBytes` setting. Anything bigger is almost
+/// certainly not a hand-written Markdown file and would freeze the editor.
+pub const DEFAULT_MAX_OPEN_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Fail with `FileTooLarge` when `path` exceeds `limit` bytes. Checked from
+/// metadata, before any content is read.
+pub fn check_size(path: &Path, limit: u64) -> AppResult<u64> {
+    let size = std::fs::metadata(path).map_err(|e| AppError::io(path, e))?.len();
+    if size > limit {
+        return Err(AppError::FileTooLarge {
+            path: path.to_string_lossy().into_owned(),
+            size,
+            limit,
+        });
+    }
+    Ok(size)
+}
+
+/// Bytes sniffed by `looks_like_text`.
+const TEXT_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Cheap pre-open check: the first few KB contain no NUL byte and only a
+/// small share of control characters. Unreadable files pass, so the real
+/// read reports the actual I/O error.
+pub fn looks_like_text(path: &Path) -> bool {
+    match crate::read_file_capped(path, TEXT_SNIFF_BYTES) {
+        Ok(head) => is_probably_text(&head),
+        Err(_) => true,
+    }
+}
+
+fn is_probably_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    let control = head
+        .iter()
+        .filter(|&&b| (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c)) || b == 0x7f)
+        .count();
+    control * 10 <= head.len()
+}
+
+/// Read a UTF-8 text file. `limit: None` skips the size and binary-content
+/// guards (forced open after the user confirmed).
+pub fn read_text(path: &Path, limit: Option<u64>) -> AppResult<String> {
+    if let Some(limit) = limit {
+        check_size(path, limit)?;
+        if !looks_like_text(path) {
+            return Err(AppError::NotTextFile { path: path.to_string_lossy().into_owned() });
+        }
+    }
+    let bytes = std::fs::read(path).map_err(|e| AppError::io(path, e))?;
+    String::from_utf8(bytes).map_err(|_| AppError::InvalidInput(format!("not valid UTF-8: {}", path.display())))
+}
+
+/// Fast non-cryptographic content hash, as 16 hex digits.
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(bytes))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedText {
+    pub content: String,
+    pub hash: String,
+}
+
+impl LoadedText {
+    pub fn new(content: String) -> Self {
+        let hash = content_hash(content.as_bytes());
+        Self { content, hash }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    /// Both kinds occur; saving keeps whatever the buffer holds.
+    Mixed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextEncoding {
+    Utf8,
+    /// UTF-8 with a leading byte order mark, which stays in `content`.
+    Utf8Bom,
+}
+
+/// A fresh read of a document with everything needed to reset the buffer
+/// and its dirty baseline.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadedText {
+    pub content: String,
+    pub hash: String,
+    /// Milliseconds since the Unix epoch.
+    pub mtime: Option<i64>,
+    pub line_ending: LineEnding,
+    pub encoding: TextEncoding,
+}
+
+/// Files without any line break count as LF.
+pub fn detect_line_ending(text: &str) -> LineEnding {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    match (lf, crlf) {
+        (_, 0) => LineEnding::Lf,
+        (0, _) => LineEnding::Crlf,
+        _ => LineEnding::Mixed,
+    }
+}
+
+fn mtime_millis(time: SystemTime) -> Option<i64> {
+    let since = time.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+    i64::try_from(since.as_millis()).ok()
+}
+
+/// Re-read `path` with the same guards as opening it. Runs under the path's
+/// write lock so it never sees one of our saves half done, and records the
+/// mtime it read as our own so the watcher event for this change is not
+/// reported again. A deleted file fails with `NotFound`.
+pub async fn reload(locks: &FileLocks, path: &Path, limit: Option<u64>) -> AppResult<ReloadedText> {
+    let _guard = locks.acquire(path).await?;
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).map_err(|e| AppError::io(path, e))?;
+    let content = read_text(path, limit)?;
+    // Only claim the mtime if nothing wrote the file while we read it.
+    if std::fs::metadata(path).and_then(|m| m.modified()).ok() == Some(modified) {
+        locks.self_writes.lock().unwrap().insert(path.to_path_buf(), modified);
+    }
+    let encoding = if content.starts_with('\u{FEFF}') { TextEncoding::Utf8Bom } else { TextEncoding::Utf8 };
+    Ok(ReloadedText {
+        hash: content_hash(content.as_bytes()),
+        mtime: mtime_millis(modified),
+        line_ending: detect_line_ending(&content),
+        encoding,
+        content,
+    })
+}
+
+/// Fail with `Conflict` when the file on disk no longer hashes to
+/// `expected`. A file that does not exist yet never conflicts.
+fn check_unchanged(path: &Path, expected: Option<&str>) -> AppResult<()> {
+    let Some(expected) = expected else { return Ok(()) };
+    let current = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(AppError::io(path, e)),
+    };
+    if content_hash(&current) != expected {
+        return Err(AppError::Conflict {
+            path: Some(path.to_string_lossy().into_owned()),
+            message: format!("{} changed on disk since it was loaded", path.display()),
+        });
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct FileLocks {
+    locks: Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>,
+    // mtime observed right after each of our own writes.
+    self_writes: Mutex<HashMap<PathBuf, SystemTime>>,
+}
+
+impl FileLocks {
+    fn lock_for(&self, path: &Path) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.locks.lock().unwrap();
+        // Drop entries nobody is holding or waiting on so the map stays small.
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry(path.to_path_buf()).or_default().clone()
+    }
+
+    pub async fn acquire(&self, path: &Path) -> AppResult<OwnedMutexGuard<()>> {
+        self.acquire_within(path, LOCK_TIMEOUT).await
+    }
+
+    async fn acquire_within(&self, path: &Path, timeout: Duration) -> AppResult<OwnedMutexGuard<()>> {
+        let lock = self.lock_for(path);
+        tokio::time::timeout(timeout, lock.lock_owned())
+            .await
+            .map_err(|_| AppError::Busy { path: path.to_string_lossy().into_owned() })
+    }
+
+    /// `acquire` for synchronous callers on a blocking thread, such as the
+    /// workspace-wide link rewrites.
+    pub fn acquire_blocking(&self, path: &Path) -> AppResult<OwnedMutexGuard<()>> {
+        let lock = self.lock_for(path);
+        let deadline = std::time::Instant::now() + LOCK_TIMEOUT;
+        loop {
+            if let Ok(guard) = lock.clone().try_lock_owned() {
+                return Ok(guard);
+            }
+            if std::time::Instant::now() >= deadline {
+                return Err(AppError::Busy { path: path.to_string_lossy().into_owned() });
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// True while one of our writers holds the lock for `path`.
+    pub fn is_writing(&self, path: &Path) -> bool {
+        let locks = self.locks.lock().unwrap();
+        locks
+            .get(path)
+            .map(|lock| lock.try_lock().is_err())
+            .unwrap_or(false)
+    }
+
+    pub fn record_self_write(&self, path: &Path) {
+        if let Ok(modified) = std::fs::metadata(path).and_then(|m| m.modified()) {
+            self.self_writes.lock().unwrap().insert(path.to_path_buf(), modified);
+        }
+    }
+
+    /// Whether the file on disk is still exactly what we last wrote (or a
+    /// write of ours is in flight), i.e. a watcher event for it is ours.
+    pub fn is_own_write(&self, path: &Path) -> bool {
+        if self.is_writing(path) {
+            return true;
+        }
+        let recorded = self.self_writes.lock().unwrap().get(path).copied();
+        match (recorded, std::fs::metadata(path).and_then(|m| m.modified())) {
+            (Some(ours), Ok(current)) => ours == current,
+            _ => false,
+        }
+    }
+}
+
+/// Write `bytes` to a temp file next to `path`, fsync it, then rename over
+/// the target so readers never observe a half-written file. Missing parent
+/// folders are created; an existing file's permissions are kept.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> AppResult<()> {
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| AppError::InvalidInput(format!("not a file path: {}", path.display())))?
+        .to_string_lossy()
+        .into_owned();
+    let tmp = parent.join(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4()));
+
+    let written = (|| -> std::io::Result<()> {
+        let mut f = std::fs::File::create(&tmp)?;
+        f.write_all(bytes)?;
+        f.sync_all()?;
+        if let Ok(meta) = std::fs::metadata(path) {
+            std::fs::set_permissions(&tmp, meta.permissions())?;
+        }
+        std::fs::rename(&tmp, path)
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(AppError::io(path, e));
+    }
+    Ok(())
+}
+
+/// Largest asset `write_binary_atomic` accepts; pasted images and localized
+/// remote images are far below this.
+pub const MAX_BINARY_WRITE_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum BinaryWrite {
+    Written { path: String },
+    /// The target exists and `overwrite` was false; nothing was written.
+    WouldOverwrite { path: String },
+}
+
+/// Atomically write an asset (image paste, localized remote image). Same
+/// temp-file-rename, parent creation and per-path locking as document saves.
+pub async fn write_binary_atomic(
+    locks: &FileLocks,
+    path: &Path,
+    bytes: &[u8],
+    overwrite: bool,
+) -> AppResult<BinaryWrite> {
+    let path_str = path.to_string_lossy().into_owned();
+    let size = bytes.len() as u64;
+    if size > MAX_BINARY_WRITE_BYTES {
+        return Err(AppError::FileTooLarge { path: path_str, size, limit: MAX_BINARY_WRITE_BYTES });
+    }
+    let _guard = locks.acquire(path).await?;
+    if !overwrite && path.exists() {
+        return Ok(BinaryWrite::WouldOverwrite { path: path_str });
+    }
+    write_atomic(path, bytes)?;
+    locks.record_self_write(path);
+    tracing::info!(path = %path.display(), bytes = size, "wrote asset");
+    Ok(BinaryWrite::Written { path: path_str })
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Read `path` back and compare it with what was meant to be written.
+pub fn verify_written(path: &Path, content: &str) -> AppResult<()> {
+    let expected = content_hash(content.as_bytes());
+    let bytes = std::fs::read(path).map_err(|e| AppError::io(path, e))?;
+    let actual = content_hash(&bytes);
+    if actual != expected {
+        return Err(AppError::VerificationFailed { path: path.to_string_lossy().into_owned(), expected, actual });
+    }
+    Ok(())
+}
+
+/// Atomically replace `path` and return the hash of what was written.
+/// `expected_hash` is the hash of the content the editor loaded; when the
+/// disk no longer matches it the save fails with `Conflict`. With `verify`
+/// the file is read back under the same lock and must hash the same.
+pub async fn save_atomic(
+    locks: &FileLocks,
+    path: &Path,
+    content: &str,
+    expected_hash: Option<&str>,
+    verify: bool,
+) -> AppResult<String> {
+    let _guard = locks.acquire(path).await?;
+    check_unchanged(path, expected_hash)?;
+    write_atomic(path, content.as_bytes())?;
+    locks.record_self_write(path);
+    if verify {
+        verify_written(path, content)?;
+    }
+    tracing::info!(path = %path.display(), bytes = content.len(), verified = verify, "saved");
+    Ok(content_hash(content.as_bytes()))
+}
+
+/// Copy the current file to `<name>.bak` before replacing it atomically.
+pub async fn save_with_backup(
+    locks: &FileLocks,
+    path: &Path,
+    content: &str,
+    expected_hash: Option<&str>,
+) -> AppResult<String> {
+    let _guard = locks.acquire(path).await?;
+    check_unchanged(path, expected_hash)?;
+    if path.is_file() {
+        std::fs::copy(path, backup_path(path)).map_err(|e| AppError::io(path, e))?;
+    }
+    write_atomic(path, content.as_bytes())?;
+    locks.record_self_write(path);
+    tracing::info!(path = %path.display(), bytes = content.len(), "saved with backup");
+    Ok(content_hash(content.as_bytes()))
+}
+
+/// Keep editor-internal markers out of anything written to disk: with
+/// `sanitize` they are stripped, otherwise the write is refused.
+pub fn guard_sentinels(path: &str, content: String, sanitize: bool) -> AppResult<String> {
+    let markers: Vec<String> = SENTINELS
+        .iter()
+        .filter(|m| content.contains(*m))
+        .map(|m| m.to_string())
+        .collect();
+    if markers.is_empty() {
+        return Ok(content);
+    }
+    if !sanitize {
+        return Err(AppError::SentinelLeak { path: path.to_string(), markers });
+    }
+    tracing::warn!(path, ?markers, "stripped editor markers before saving");
+    Ok(markers.iter().fold(content, |acc, m| acc.replace(m.as_str(), "")))
+}
+
+/// `<app_data>/drafts`. Creates the directory on first call.
+pub fn drafts_dir(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?.join("drafts");
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    Ok(dir)
+}
+
+fn draft_file(app: &tauri::AppHandle, doc_path: &str) -> AppResult<PathBuf> {
+    Ok(drafts_dir(app)?.join(format!("{}.md", hash_path(doc_path))))
+}
+
+/// Persist unsaved buffer content for `doc_path`. Serializes on the document
+/// path so a draft flush never interleaves with a save of the same file.
+pub async fn write_draft(app: &tauri::AppHandle, locks: &FileLocks, doc_path: &str, content: &str) -> AppResult<()> {
+    let _guard = locks.acquire(Path::new(doc_path)).await?;
+    write_atomic(&draft_file(app, doc_path)?, content.as_bytes())
+}
+
+pub fn read_draft(app: &tauri::AppHandle, doc_path: &str) -> AppResult<Option<String>> {
+    let path = draft_file(app, doc_path)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    std::fs::read_to_string(&path).map(Some).map_err(|e| AppError::io(&path, e))
+}
+
+pub fn discard_draft(app: &tauri::AppHandle, doc_path: &str) -> AppResult<()> {
+    let path = draft_file(app, doc_path)?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| AppError::io(&path, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mermark-files-{}-{}", tag, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_atomic_replaces_content_and_leaves_no_temp_files() {
+        let dir = temp_dir("atomic");
+        let path = dir.join("doc.md");
+        write_atomic(&path, b"one").unwrap();
+        write_atomic(&path, b"two").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "two");
+        let entries: Vec<_> = std::fs::read_dir(&dir).unwrap().flatten().collect();
+        assert_eq!(entries.len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn read_text_refuses_files_over_the_limit_unless_forced() {
+        let dir = temp_dir("limit");
+        let path = dir.join("big.md");
+        std::fs::write(&path, "0123456789").unwrap();
+        match read_text(&path, Some(4)) {
+            Err(AppError::FileTooLarge { size, limit, .. }) => assert_eq!((size, limit), (10, 4)),
+            other => panic!("expected FileTooLarge, got {:?}", other),
+        }
+        assert_eq!(read_text(&path, None).unwrap(), "0123456789");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn binary_content_is_not_text() {
+        assert!(is_probably_text(b"# Title\n\ttabbed\r\n"));
+        assert!(is_probably_text(b""));
+        assert!(!is_probably_text(b"PK\x03\x04\0\0"));
+        assert!(!is_probably_text(&[0x01, 0x02, 0x03, b'a', 0x1b, 0x7f]));
+        let dir = temp_dir("binary-open");
+        let path = dir.join("image.md");
+        std::fs::write(&path, [0x89, b'P', b'N', b'G', 0, 0, 0, 0x0d]).unwrap();
+        assert!(matches!(read_text(&path, Some(1024)), Err(AppError::NotTextFile { .. })));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn write_atomic_creates_missing_parent() {
+        let dir = temp_dir("parent");
+        let path = dir.join("nested/deeper/doc.md");
+        write_atomic(&path, b"x").unwrap();
+        assert!(path.is_file());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn reload_reports_format_and_claims_the_change() {
+        let dir = temp_dir("reload");
+        let path = dir.join("doc.md");
+        std::fs::write(&path, "\u{FEFF}# A\r\nb\r\n").unwrap();
+        let locks = FileLocks::default();
+        assert!(!locks.is_own_write(&path));
+        let reloaded = reload(&locks, &path, Some(1024)).await.unwrap();
+        assert_eq!(reloaded.line_ending, LineEnding::Crlf);
+        assert_eq!(reloaded.encoding, TextEncoding::Utf8Bom);
+        assert_eq!(reloaded.hash, content_hash(reloaded.content.as_bytes()));
+        assert!(reloaded.mtime.is_some());
+        assert!(locks.is_own_write(&path));
+
+        std::fs::remove_file(&path).unwrap();
+        let err = reload(&locks, &path, Some(1024)).await.unwrap_err();
+        assert!(matches!(err, AppError::NotFound { .. }));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn line_endings_are_classified() {
+        assert_eq!(detect_line_ending("a\nb\n"), LineEnding::Lf);
+        assert_eq!(detect_line_ending("no break"), LineEnding::Lf);
+        assert_eq!(detect_line_ending("a\r\nb\r\n"), LineEnding::Crlf);
+        assert_eq!(detect_line_ending("a\r\nb\n"), LineEnding::Mixed);
+    }
+
+    #[tokio::test]
+    async fn second_writer_times_out_as_busy() {
+        let locks = FileLocks::default();
+        let path = Path::new("/tmp/mermark-lock-test.md");
+        let _held = locks.acquire(path).await.unwrap();
+        assert!(locks.is_writing(path));
+        let err = locks.acquire_within(path, Duration::from_millis(20)).await.unwrap_err();
+        assert!(matches!(err, AppError::Busy { .. }));
+    }
+
+    #[tokio::test]
+    async fn save_records_own_write_until_file_changes_externally() {
+        let dir = temp_dir("own");
+        let path = dir.join("doc.md");
+        let locks = FileLocks::default();
+        save_with_backup(&locks, &path, "first", None).await.unwrap();
+        save_with_backup(&locks, &path, "second", None).await.unwrap();
+        assert_eq!(std::fs::read_to_string(backup_path(&path)).unwrap(), "first");
+        assert!(locks.is_own_write(&path));
+        let later = SystemTime::now() + Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(!locks.is_own_write(&path));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn binary_write_reports_would_overwrite_unless_allowed() {
+        let dir = temp_dir("binary");
+        let path = dir.join("assets/pic.png");
+        let locks = FileLocks::default();
+        let first = write_binary_atomic(&locks, &path, &[1, 2, 3], false).await.unwrap();
+        assert!(matches!(first, BinaryWrite::Written { .. }));
+        let second = write_binary_atomic(&locks, &path, &[9], false).await.unwrap();
+        assert!(matches!(second, BinaryWrite::WouldOverwrite { .. }));
+        assert_eq!(std::fs::read(&path).unwrap(), [1, 2, 3]);
+        write_binary_atomic(&locks, &path, &[9], true).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), [9]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn save_refuses_when_disk_no_longer_matches_loaded_hash() {
+        let dir = temp_dir("conflict");
+        let path = dir.join("doc.md");
+        let locks = FileLocks::default();
+        let loaded = save_atomic(&locks, &path, "mine", None, false).await.unwrap();
+        assert_eq!(loaded, content_hash(b"mine"));
+        std::fs::write(&path, "theirs").unwrap();
+        let err = save_atomic(&locks, &path, "mine v2", Some(&loaded), false).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict { .. }));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "theirs");
+        let theirs = content_hash(b"theirs");
+        save_atomic(&locks, &path, "mine v2", Some(&theirs), true).await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn verification_compares_the_disk_with_the_intended_content() {
+        let dir = temp_dir("verify");
+        let path = dir.join("doc.md");
+        std::fs::write(&path, "café").unwrap();
+        verify_written(&path, "café").unwrap();
+        let err = verify_written(&path, "cafe").unwrap_err();
+        assert!(matches!(err, AppError::VerificationFailed { ref actual, .. } if *actual == content_hash("café".as_bytes())));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn sentinels_are_refused_or_stripped() {
+        let err = guard_sentinels("/a.md", "a__CURSOR__b".into(), false).unwrap_err();
+        assert!(matches!(err, AppError::SentinelLeak { ref markers, .. } if markers == &["__CURSOR__"]));
+        assert_eq!(guard_sentinels("/a.md", "a__CURSOR__b__BR__".into(), true).unwrap(), "ab");
+        assert_eq!(guard_sentinels("/a.md", "clean".into(), false).unwrap(), "clean");
+    }
+}
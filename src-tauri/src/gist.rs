@@ -0,0 +1,112 @@
+//! Share snippets as a GitHub gist. `build_payload` assembles the request
+//! body the gists API expects; `create` posts it with a personal access
+//! token the user pastes in. The token lives in managed state for the rest
+//! of the run and is never written to disk.
+
+use std::sync::Mutex;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::error::{AppError, AppResult};
+
+const GISTS_URL: &str = "https://api.github.com/gists";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GistFile {
+    pub name: String,
+    pub content: String,
+}
+
+/// Token from the last successful `create_gist`, in memory only.
+#[derive(Default)]
+pub struct GistToken(pub Mutex<Option<String>>);
+
+/// `{ description, public, files: { <name>: { content } } }`. GitHub rejects
+/// empty files and names with a slash, so those are refused here first.
+pub fn build_payload(files: &[GistFile], description: &str, public: bool) -> AppResult<String> {
+    if files.is_empty() {
+        return Err(AppError::InvalidInput("a gist needs at least one file".to_string()));
+    }
+    let mut entries = Map::new();
+    for file in files {
+        let name = file.name.trim();
+        if name.is_empty() || name.contains('/') || name.contains('\\') {
+            return Err(AppError::InvalidInput(format!("invalid gist file name: {:?}", file.name)));
+        }
+        if file.content.trim().is_empty() {
+            return Err(AppError::InvalidInput(format!("gist file is empty: {}", name)));
+        }
+        if entries.insert(name.to_string(), json!({ "content": file.content })).is_some() {
+            return Err(AppError::InvalidInput(format!("duplicate gist file name: {}", name)));
+        }
+    }
+    let body = json!({ "description": description, "public": public, "files": entries });
+    Ok(body.to_string())
+}
+
+fn network_error(e: reqwest::Error) -> AppError {
+    let message = if e.is_connect() || e.is_timeout() {
+        "GitHub could not be reached; check the network connection".to_string()
+    } else {
+        e.to_string()
+    };
+    AppError::Network { status: e.status().map(|s| s.as_u16()), message }
+}
+
+/// POST `payload` to the gists API and return the new gist's page URL.
+pub async fn create(payload: &str, token: &str) -> AppResult<String> {
+    let body: Value = serde_json::from_str(payload)
+        .map_err(|e| AppError::InvalidInput(format!("gist payload is not JSON: {}", e)))?;
+    let client = crate::ai::process::http_client(Some(std::time::Duration::from_secs(30)));
+    let resp = client
+        .post(GISTS_URL)
+        .bearer_auth(token)
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .header(reqwest::header::USER_AGENT, concat!("MerMark/", env!("CARGO_PKG_VERSION")))
+        .header("X-GitHub-Api-Version", "2022-11-28")
+        .json(&body)
+        .send()
+        .await
+        .map_err(network_error)?;
+    let status = resp.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(AppError::PermissionDenied {
+            path: None,
+            message: "GitHub rejected the token; it needs the gist scope".to_string(),
+        });
+    }
+    if !status.is_success() {
+        let detail: Value = resp.json().await.unwrap_or(Value::Null);
+        let message = detail["message"].as_str().unwrap_or("gist creation failed").to_string();
+        return Err(AppError::Network { status: Some(status.as_u16()), message: format!("GitHub: {}", message) });
+    }
+    let created: Value = resp.json().await.map_err(network_error)?;
+    created["html_url"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| AppError::Internal("GitHub response has no html_url".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, content: &str) -> GistFile {
+        GistFile { name: name.to_string(), content: content.to_string() }
+    }
+
+    #[test]
+    fn payload_matches_the_gists_api_shape() {
+        let payload = build_payload(&[file("notes.md", "# Hi\n"), file("a.mmd", "graph TD")], "Snippets", false).unwrap();
+        let v: Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(v["description"], "Snippets");
+        assert_eq!(v["public"], false);
+        assert_eq!(v["files"]["notes.md"]["content"], "# Hi\n");
+        assert_eq!(v["files"]["a.mmd"]["content"], "graph TD");
+
+        assert!(build_payload(&[], "", true).is_err());
+        assert!(build_payload(&[file("a/b.md", "x")], "", true).is_err());
+        assert!(build_payload(&[file("a.md", "  \n")], "", true).is_err());
+        assert!(build_payload(&[file("a.md", "x"), file("a.md", "y")], "", true).is_err());
+    }
+}
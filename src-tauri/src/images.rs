@@ -0,0 +1,161 @@
+//! Re-encoding images on insert: downscale, convert and drop metadata so
+//! pasted screenshots do not bloat the document folder or leak EXIF.
+
+use std::io::Cursor;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::files::BinaryWrite;
+
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetFormat {
+    /// Same format as the source (PNG for formats we cannot encode).
+    #[default]
+    Keep,
+    Png,
+    /// Lossless; `quality` does not apply.
+    Webp,
+    Jpeg,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImageOptions {
+    /// Downscale (never upscale) to fit within these bounds, keeping aspect.
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    pub format: TargetFormat,
+    /// JPEG quality 1-100.
+    pub quality: Option<u8>,
+    /// Re-encode even when nothing else changes, dropping EXIF and other
+    /// metadata (orientation is applied to the pixels first).
+    pub strip_metadata: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedImage {
+    #[serde(flatten)]
+    pub write: BinaryWrite,
+    pub width: u32,
+    pub height: u32,
+    /// Size of the file written, next to the size that came in.
+    pub bytes: u64,
+    pub original_bytes: u64,
+    /// File extension of the output.
+    pub format: &'static str,
+}
+
+pub struct Encoded {
+    pub bytes: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub extension: &'static str,
+}
+
+fn extension_of(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::WebP => "webp",
+        ImageFormat::Gif => "gif",
+        _ => "png",
+    }
+}
+
+fn bad_image(e: impl std::fmt::Display) -> AppError {
+    AppError::InvalidInput(format!("unreadable image: {}", e))
+}
+
+/// Apply `options` to encoded image `bytes`. When nothing needs to change the
+/// input comes back untouched rather than being re-encoded.
+pub fn process(bytes: &[u8], options: &ImageOptions) -> AppResult<Encoded> {
+    let reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    let source = reader.format().ok_or_else(|| bad_image("unknown format"))?;
+    let mut decoder = reader.into_decoder().map_err(bad_image)?;
+    let orientation = decoder.orientation().map_err(bad_image)?;
+    let (width, height) = decoder.dimensions();
+
+    let target = match options.format {
+        TargetFormat::Keep => match source {
+            ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP | ImageFormat::Gif => source,
+            _ => ImageFormat::Png,
+        },
+        TargetFormat::Png => ImageFormat::Png,
+        TargetFormat::Webp => ImageFormat::WebP,
+        TargetFormat::Jpeg => ImageFormat::Jpeg,
+    };
+    let max_w = options.max_width.unwrap_or(u32::MAX).max(1);
+    let max_h = options.max_height.unwrap_or(u32::MAX).max(1);
+    let needs_resize = width > max_w || height > max_h;
+
+    if !needs_resize && target == source && !options.strip_metadata {
+        return Ok(Encoded { bytes: bytes.to_vec(), width, height, extension: extension_of(target) });
+    }
+
+    let mut img = DynamicImage::from_decoder(decoder).map_err(bad_image)?;
+    img.apply_orientation(orientation);
+    if needs_resize {
+        img = img.resize(max_w, max_h, image::imageops::FilterType::Lanczos3);
+    }
+
+    let mut out = Cursor::new(Vec::new());
+    let encoded = match target {
+        ImageFormat::Jpeg => {
+            let quality = options.quality.unwrap_or(DEFAULT_JPEG_QUALITY).clamp(1, 100);
+            let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, quality);
+            // JPEG has no alpha channel.
+            DynamicImage::ImageRgb8(img.to_rgb8()).write_with_encoder(encoder)
+        }
+        other => img.write_to(&mut out, other),
+    };
+    encoded.map_err(|e| AppError::Internal(format!("image encode: {}", e)))?;
+    Ok(Encoded {
+        bytes: out.into_inner(),
+        width: img.width(),
+        height: img.height(),
+        extension: extension_of(target),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let img = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(width, height, image::Rgba([200, 10, 10, 255])));
+        let mut out = Cursor::new(Vec::new());
+        img.write_to(&mut out, ImageFormat::Png).unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn untouched_when_nothing_to_do() {
+        let input = png(40, 20);
+        let out = process(&input, &ImageOptions { max_width: Some(100), ..Default::default() }).unwrap();
+        assert_eq!(out.bytes, input);
+        assert_eq!((out.width, out.height, out.extension), (40, 20, "png"));
+    }
+
+    #[test]
+    fn downscales_keeping_aspect() {
+        let out = process(&png(400, 200), &ImageOptions { max_width: Some(100), ..Default::default() }).unwrap();
+        assert_eq!((out.width, out.height), (100, 50));
+    }
+
+    #[test]
+    fn converts_to_jpeg() {
+        let options = ImageOptions { format: TargetFormat::Jpeg, quality: Some(70), ..Default::default() };
+        let out = process(&png(30, 30), &options).unwrap();
+        assert_eq!(out.extension, "jpg");
+        assert_eq!(&out.bytes[..2], &[0xFF, 0xD8]);
+    }
+
+    #[test]
+    fn rejects_non_images() {
+        assert!(process(b"definitely not an image", &ImageOptions::default()).is_err());
+    }
+}
@@ -6,7 +6,39 @@ use tauri::{Manager, Emitter, WebviewUrl, WebviewWindowBuilder, RunEvent, Window
 use serde::{Deserialize, Serialize};
 use font_kit::source::SystemSource;
 
+use error::{AppError, AppResult};
+
 mod ai;
+mod associations;
+mod background;
+mod bundle;
+mod config_recovery;
+mod db;
+mod dialogs;
+mod error;
+mod external;
+mod files;
+mod gist;
+mod images;
+mod links;
+mod logging;
+mod positions;
+mod markdown;
+mod metrics;
+mod on_save;
+mod patch;
+mod pending_opens;
+mod portable;
+mod present;
+mod recent;
+mod replace;
+mod scan;
+mod selftest;
+mod sessions;
+mod settings;
+mod user_config;
+mod watch;
+mod window_state;
 
 // Store the file path to be opened (from CLI args or file association)
 pub struct OpenFileState(pub Mutex<Option<String>>);
@@ -25,12 +57,78 @@ fn is_supported_markdown_path(path: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Split launch arguments (without the executable) into Markdown files to
+/// open as tabs and folders to open as workspaces. Missing paths and other
+/// file types are skipped with a log line rather than failing the launch.
+fn classify_launch_args<'a>(args: impl IntoIterator<Item = &'a String>) -> (Vec<String>, Vec<String>) {
+    let mut files = Vec::new();
+    let mut folders = Vec::new();
+    for arg in args {
+        if arg.starts_with('-') {
+            continue;
+        }
+        let path = Path::new(arg);
+        if path.is_dir() {
+            folders.push(arg.clone());
+        } else if path.is_file() && is_supported_markdown_path(arg) {
+            files.push(arg.clone());
+        } else if !path.exists() {
+            tracing::warn!(%arg, "launch argument skipped: not found");
+        } else {
+            tracing::debug!(%arg, "launch argument skipped: not Markdown or a folder");
+        }
+    }
+    (files, folders)
+}
+
+/// Paths dropped onto a window, sorted by what the frontend does with them:
+/// Markdown opens as tabs, images go into the active document and the first
+/// folder becomes the workspace.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DroppedFiles {
+    markdown: Vec<String>,
+    images: Vec<String>,
+    folders: Vec<String>,
+    /// Anything else; the frontend ignores these.
+    other: Vec<String>,
+    /// Drop point in physical pixels, for placing images.
+    x: f64,
+    y: f64,
+}
+
+fn classify_drop(paths: &[PathBuf], x: f64, y: f64) -> DroppedFiles {
+    let mut dropped = DroppedFiles { x, y, ..Default::default() };
+    for path in paths {
+        let name = path.to_string_lossy().into_owned();
+        if path.is_dir() {
+            dropped.folders.push(name);
+        } else if is_supported_markdown_path(&name) {
+            dropped.markdown.push(name);
+        } else if is_workspace_image(&name) {
+            dropped.images.push(name);
+        } else {
+            dropped.other.push(name);
+        }
+    }
+    dropped
+}
+
 // Payload for transferring tabs between windows
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TabTransferPayload {
+    /// Echoed back in `complete_tab_transfer` so the source can match it.
+    pub transfer_id: String,
     pub file_path: String,
     pub source_window: String,
     pub target_window: String,
+    /// Make the transferred tab the target's active one. When false the
+    /// target keeps its current tab and is not focused.
+    pub should_activate: bool,
+    /// Source window's layout when this was its last tab; the target applies
+    /// it in place of its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<window_state::Layout>,
 }
 
 #[tauri::command]
@@ -64,10 +162,12 @@ fn unregister_open_file(
 #[tauri::command]
 fn unregister_window_files(
     registry: tauri::State<'_, OpenFilesRegistry>,
+    tabs: tauri::State<'_, sessions::WindowTabs>,
     window_label: String,
 ) {
     let mut files = registry.0.lock().unwrap();
     files.retain(|_, label| label != &window_label);
+    tabs.0.lock().unwrap().remove(&window_label);
 }
 
 // Check if a file is already open and return the window label if so
@@ -80,13 +180,25 @@ fn check_file_open(
     files.get(&file_path).cloned()
 }
 
+// Bring window to front even if minimized (#49)
+fn bring_to_front(window: &tauri::WebviewWindow) -> AppResult<()> {
+    if window.is_minimized().unwrap_or(false) {
+        let _ = window.unminimize();
+    }
+    if !window.is_visible().unwrap_or(true) {
+        let _ = window.show();
+    }
+    window.set_focus()?;
+    Ok(())
+}
+
 // Focus the window that has a specific file open
 #[tauri::command]
 async fn focus_window_with_file(
     app: tauri::AppHandle,
     registry: tauri::State<'_, OpenFilesRegistry>,
     file_path: String,
-) -> Result<bool, String> {
+) -> AppResult<bool> {
     let window_label = {
         let files = registry.0.lock().unwrap();
         files.get(&file_path).cloned()
@@ -94,16 +206,9 @@ async fn focus_window_with_file(
 
     if let Some(label) = window_label {
         if let Some(window) = app.get_webview_window(&label) {
-            // Bring window to front even if minimized (#49)
-            if window.is_minimized().unwrap_or(false) {
-                let _ = window.unminimize();
-            }
-            if !window.is_visible().unwrap_or(true) {
-                let _ = window.show();
-            }
-            window.set_focus().map_err(|e| e.to_string())?;
+            bring_to_front(&window)?;
             // Emit event to switch to the tab with this file
-            window.emit("focus-file", file_path).map_err(|e| e.to_string())?;
+            window.emit("focus-file", file_path)?;
             return Ok(true);
         }
     }
@@ -115,6 +220,26 @@ const PRINT_WINDOW_LABEL: &str = "window-print";
 // Custom URI scheme that serves the print-ready HTML from memory.
 const PRINT_SCHEME: &str = "mermarkprint";
 
+/// Hidden helper windows that never count as editor windows.
+fn is_helper_window(label: &str) -> bool {
+    label == PRINT_WINDOW_LABEL || label == selftest::WINDOW_LABEL || present::is_present_label(label)
+}
+
+// Custom schemes resolve to `scheme://localhost` on macOS/Linux but
+// `http://scheme.localhost` on Windows/Android.
+fn print_url(path: &str) -> AppResult<tauri::Url> {
+    let url = if cfg!(any(windows, target_os = "android")) {
+        format!("http://{PRINT_SCHEME}.localhost/{path}")
+    } else {
+        format!("{PRINT_SCHEME}://localhost/{path}")
+    };
+    tauri::Url::parse(&url).map_err(|e| AppError::Internal(e.to_string()))
+}
+
+// Last known OS appearance ("light" / "dark"). Read at startup and refreshed
+// from `ThemeChanged` while windows follow the system theme.
+pub struct SystemThemeState(pub Mutex<String>);
+
 // Holds the print-ready HTML served to the print window by the custom protocol.
 pub struct PrintHtmlState(pub Mutex<Option<String>>);
 
@@ -122,7 +247,7 @@ pub struct PrintHtmlState(pub Mutex<Option<String>>);
 fn get_all_windows(app: tauri::AppHandle) -> Vec<String> {
     app.webview_windows()
         .keys()
-        .filter(|label| *label != PRINT_WINDOW_LABEL)
+        .filter(|label| !is_helper_window(label))
         .cloned()
         .collect()
 }
@@ -136,23 +261,16 @@ fn get_all_windows(app: tauri::AppHandle) -> Vec<String> {
 /// load JS injection (which rendered blank). `async` keeps window creation off
 /// the main thread, since creating a webview from a sync command can deadlock.
 #[tauri::command]
-async fn print_document(app: tauri::AppHandle, html: String) -> Result<(), String> {
+async fn print_document(app: tauri::AppHandle, html: String) -> AppResult<()> {
     *app.state::<PrintHtmlState>().0.lock().unwrap() = Some(html);
 
     if let Some(existing) = app.get_webview_window(PRINT_WINDOW_LABEL) {
         let _ = existing.close();
     }
 
-    // Custom schemes resolve to `scheme://localhost` on macOS/Linux but
-    // `http://scheme.localhost` on Windows/Android.
-    let url = if cfg!(any(windows, target_os = "android")) {
-        format!("http://{PRINT_SCHEME}.localhost/")
-    } else {
-        format!("{PRINT_SCHEME}://localhost/")
-    };
-    let url = tauri::Url::parse(&url).map_err(|e| e.to_string())?;
+    let url = print_url("")?;
 
-    WebviewWindowBuilder::new(&app, PRINT_WINDOW_LABEL, WebviewUrl::CustomProtocol(url))
+    portable::with_data_dir(WebviewWindowBuilder::new(&app, PRINT_WINDOW_LABEL, WebviewUrl::CustomProtocol(url)))
         .title("MerMark — Print / PDF")
         .inner_size(900.0, 1100.0)
         .center()
@@ -164,12 +282,307 @@ async fn print_document(app: tauri::AppHandle, html: String) -> Result<(), Strin
                 let _ = window.print();
             }
         })
-        .build()
-        .map_err(|e| e.to_string())?;
+        .build()?;
 
     Ok(())
 }
 
+/// Hidden diagnostic for bug reports. `frontend_stages` carries the HTML and
+/// Mermaid render results measured in the webview; the backend adds its own
+/// transforms, the print webview and optional tool detection.
+#[tauri::command]
+async fn run_self_test(
+    app: tauri::AppHandle,
+    frontend_stages: Option<Vec<selftest::StageResult>>,
+) -> selftest::SelfTestReport {
+    let mut stages = frontend_stages.unwrap_or_default();
+    stages.push(selftest::markdown_stage());
+    stages.push(selftest::webview_stage(&app).await);
+    selftest::SelfTestReport {
+        app_version: app.package_info().version.to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        stages,
+        tools: vec![selftest::tool("pandoc").await],
+    }
+}
+
+/// Markdown for just `range` of a document, for exporting one section. The
+/// slice is widened to whole fenced blocks and keeps its enclosing heading;
+/// the PDF dialog renders it through the normal HTML/print pipeline.
+#[tauri::command]
+fn export_range_markdown(markdown: String, range: markdown::range::LineRange) -> AppResult<String> {
+    markdown::range::slice(&markdown, range)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FrontmatterSplit {
+    /// Markdown to render, without the front matter block.
+    body: String,
+    /// `title` from the front matter, for `<title>` and the page header.
+    title: Option<String>,
+    /// Where the metadata was written, when a document path was given.
+    meta_path: Option<String>,
+}
+
+/// Export option `splitFrontmatter`: strip YAML front matter before
+/// rendering and, given the document path, save it to a sibling
+/// `<name>.meta.yaml`. Documents without front matter come back unchanged.
+#[tauri::command]
+fn split_frontmatter(markdown: String, doc_path: Option<String>) -> AppResult<FrontmatterSplit> {
+    let Some((raw, body)) = markdown::frontmatter::split(&markdown) else {
+        return Ok(FrontmatterSplit { body: markdown, title: None, meta_path: None });
+    };
+    let meta_path = match doc_path {
+        Some(doc) => {
+            let meta = markdown::frontmatter::meta_path(Path::new(&doc));
+            files::write_atomic(&meta, format!("{}\n", raw).as_bytes())?;
+            Some(meta.to_string_lossy().into_owned())
+        }
+        None => None,
+    };
+    Ok(FrontmatterSplit {
+        body: body.to_string(),
+        title: markdown::frontmatter::get_key(raw, "title"),
+        meta_path,
+    })
+}
+
+/// Headings in document order with the anchors the exporters assign.
+#[tauri::command]
+fn generate_toc(content: String) -> Vec<markdown::outline::TocEntry> {
+    markdown::outline::generate_toc(&content)
+}
+
+/// In-document `#slug` links, each flagged by whether a heading produces
+/// that slug.
+#[tauri::command]
+fn check_anchor_links(content: String) -> Vec<markdown::outline::AnchorLink> {
+    markdown::outline::check_anchor_links(&content)
+}
+
+/// Word count and reading time; fenced code is not read.
+#[tauri::command]
+fn document_stats(content: String) -> markdown::stats::DocumentStats {
+    markdown::stats::document_stats(&content)
+}
+
+/// Guess the note's language from a prefix of its prose, so spell check can
+/// use the right dictionary. Short notes come back as `und`.
+#[tauri::command]
+fn detect_language(content: String) -> markdown::language::DetectedLanguage {
+    markdown::language::detect(&content)
+}
+
+/// Word counts per heading section, for balancing a long document.
+#[tauri::command]
+fn section_stats(content: String) -> Vec<markdown::stats::SectionStats> {
+    markdown::stats::section_stats(&content)
+}
+
+/// Save the heading outline as nested JSON or OPML for outliners and
+/// mind-mapping tools. A front-matter `title` becomes the root node.
+#[tauri::command]
+fn export_outline(
+    content: String,
+    format: markdown::outline::OutlineFormat,
+    output_path: String,
+) -> AppResult<()> {
+    let outline = markdown::outline::outline(&content);
+    let output = Path::new(&output_path);
+    let bytes = match format {
+        markdown::outline::OutlineFormat::Json => serde_json::to_vec_pretty(&outline)?,
+        markdown::outline::OutlineFormat::Opml => {
+            let name = output.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            markdown::outline::to_opml(&outline, &name).into_bytes()
+        }
+    };
+    files::write_atomic(output, &bytes)
+}
+
+/// Unified diff from the file on disk to the unsaved buffer, for sending a
+/// change for review. A file not yet saved is diffed against nothing. The
+/// patch is returned, and also written to `output_path` when one is given.
+#[tauri::command]
+async fn export_patch(path: String, current_content: String, output_path: Option<String>) -> AppResult<String> {
+    tokio::task::spawn_blocking(move || {
+        let file = Path::new(&path);
+        let on_disk = if file.exists() { Some(files::read_text(file, None)?) } else { None };
+        let name = file.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| path.clone());
+        let diff = patch::unified_diff(&name, on_disk.as_deref(), &current_content);
+        if let Some(output) = output_path {
+            files::write_atomic(Path::new(&output), diff.as_bytes())?;
+        }
+        Ok(diff)
+    })
+    .await?
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FormatResult {
+    /// Whether the input was already formatted.
+    formatted: bool,
+    /// The formatted document; omitted in check mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+}
+
+/// "Format Document". With `check` it only reports whether `content` is
+/// already formatted, for a pre-save hook.
+#[tauri::command]
+fn format_markdown(
+    content: String,
+    options: Option<markdown::format::FormatOptions>,
+    check: Option<bool>,
+) -> AppResult<FormatResult> {
+    let options = options.unwrap_or_default();
+    if !matches!(options.bullet, '-' | '*' | '+') {
+        return Err(AppError::InvalidInput(format!("unsupported list marker {:?}", options.bullet)));
+    }
+    let output = markdown::format::format(&content, &options);
+    let formatted = output == content;
+    Ok(FormatResult {
+        formatted,
+        content: if check.unwrap_or(false) { None } else { Some(output) },
+    })
+}
+
+/// "Tidy tables": pad every GFM table, leaving all other lines untouched.
+#[tauri::command]
+fn format_tables(content: String) -> String {
+    markdown::tables::format_all(&content)
+}
+
+/// Zero-width, stray BOM, bidi control and leftover sentinel characters, for
+/// the "Show invisible characters" panel.
+#[tauri::command]
+fn find_invisible_chars(content: String) -> Vec<markdown::invisible::InvisibleChar> {
+    markdown::invisible::find(&content)
+}
+
+#[tauri::command]
+fn strip_invisible_chars(content: String, which: Vec<markdown::invisible::Category>) -> String {
+    markdown::invisible::strip(&content, &which)
+}
+
+/// Pasted spreadsheet data (CSV/TSV) as a padded GFM table.
+#[tauri::command]
+fn csv_to_markdown_table(data: String, delimiter: char, has_header: bool) -> String {
+    markdown::csv::to_table(&data, delimiter, has_header)
+}
+
+#[tauri::command]
+async fn import_csv_file(
+    app: tauri::AppHandle,
+    path: String,
+    delimiter: char,
+    has_header: bool,
+) -> AppResult<String> {
+    let limit = settings::current(&app).max_open_bytes;
+    tokio::task::spawn_blocking(move || {
+        let data = files::read_text(Path::new(&path), Some(limit))?;
+        Ok(markdown::csv::to_table(&data, delimiter, has_header))
+    })
+    .await?
+}
+
+/// Rewrite every link to inline or reference style; the editor applies the
+/// result as one edit.
+#[tauri::command]
+fn convert_links(content: String, to: markdown::link_style::LinkStyle) -> String {
+    markdown::link_style::convert(&content, to)
+}
+
+/// Write each level-`level` section of `content` to its own file in
+/// `output_dir`, named from the heading slug; text before the first such
+/// heading goes to `index.md`. Existing files are never overwritten, a
+/// `-2`, `-3`, ... suffix is added instead. With `dry_run` nothing is
+/// written and the paths that would be created come back.
+#[tauri::command]
+async fn split_by_heading(
+    content: String,
+    level: usize,
+    output_dir: String,
+    dry_run: Option<bool>,
+) -> AppResult<Vec<String>> {
+    if !(1..=6).contains(&level) {
+        return Err(AppError::InvalidInput(format!("heading level must be 1-6, got {}", level)));
+    }
+    let dry_run = dry_run.unwrap_or(false);
+    tokio::task::spawn_blocking(move || {
+        let parts = markdown::split::split(&content, level);
+        if !parts.iter().any(|p| p.stem != "index") {
+            return Err(AppError::InvalidInput(format!("the document has no level-{} headings", level)));
+        }
+        let dir = Path::new(&output_dir);
+        if !dry_run {
+            std::fs::create_dir_all(dir).map_err(|e| AppError::io(dir, e))?;
+        }
+        let mut created: Vec<PathBuf> = Vec::new();
+        for part in parts {
+            let name = markdown::split::available_name(&part.stem, |name| {
+                let path = dir.join(name);
+                path.exists() || created.contains(&path)
+            });
+            let path = dir.join(name);
+            if !dry_run {
+                files::write_atomic(&path, part.content.as_bytes())?;
+            }
+            created.push(path);
+        }
+        Ok(created.into_iter().map(|p| p.to_string_lossy().into_owned()).collect())
+    })
+    .await?
+}
+
+/// Flatten `{{include: file.md}}` transclusions of the document at
+/// `doc_path` into one Markdown text, for export or sharing. Includes nest
+/// up to `max_depth` levels (default 8) and may only read files under the
+/// workspace `root` or the document's folder; missing, cyclic, too-deep or
+/// outside ones stay visible as an HTML comment.
+#[tauri::command]
+async fn resolve_includes(
+    app: tauri::AppHandle,
+    content: String,
+    doc_path: String,
+    root: Option<String>,
+    max_depth: Option<usize>,
+) -> AppResult<markdown::include::ResolvedIncludes> {
+    let max_bytes = settings::current(&app).max_open_bytes;
+    let max_depth = max_depth.unwrap_or(markdown::include::DEFAULT_MAX_DEPTH);
+    Ok(tokio::task::spawn_blocking(move || {
+        markdown::include::resolve(&content, Path::new(&doc_path), root.as_deref().map(Path::new), max_depth, max_bytes)
+    })
+    .await?)
+}
+
+/// Concatenate `paths` in order into one document for a new buffer. See
+/// `markdown::join::JoinOptions` for the separator, heading demotion and
+/// where relative links are rebased to.
+#[tauri::command]
+async fn join_files(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    options: Option<markdown::join::JoinOptions>,
+) -> AppResult<String> {
+    if paths.is_empty() {
+        return Err(AppError::InvalidInput("no files to join".to_string()));
+    }
+    let max_bytes = settings::current(&app).max_open_bytes;
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let docs = paths
+            .iter()
+            .map(|p| Ok((PathBuf::from(p), files::read_text(Path::new(p), Some(max_bytes))?)))
+            .collect::<AppResult<Vec<_>>>()?;
+        let target = options.target_path.as_ref().map_or_else(|| docs[0].0.clone(), PathBuf::from);
+        let docs: Vec<(&Path, &str)> = docs.iter().map(|(p, text)| (p.as_path(), text.as_str())).collect();
+        Ok(markdown::join::join(&docs, &options, &target))
+    })
+    .await?
+}
+
 #[tauri::command]
 fn get_current_window_label(window: tauri::Window) -> String {
     window.label().to_string()
@@ -181,23 +594,522 @@ async fn transfer_tab_to_window(
     file_path: String,
     source_window: String,
     target_window: String,
-) -> Result<(), String> {
+    carry_layout: Option<bool>,
+    should_activate: Option<bool>,
+) -> AppResult<String> {
+    ensure_multi_window(&app)?;
+    let target = window_by_label(&app, &target_window)?;
+    let layout = if carry_layout.unwrap_or(false) {
+        Some(window_state::hand_off_layout(&app, &source_window, &target_window)?)
+    } else {
+        None
+    };
+    let should_activate = should_activate.unwrap_or(true);
+    let transfer_id = uuid::Uuid::new_v4().to_string();
     let payload = TabTransferPayload {
+        transfer_id: transfer_id.clone(),
         file_path,
         source_window,
-        target_window: target_window.clone(),
+        target_window,
+        should_activate,
+        layout,
     };
 
-    if let Some(target) = app.get_webview_window(&target_window) {
-        target.emit("tab-transfer", payload).map_err(|e| e.to_string())?;
-        target.set_focus().map_err(|e| e.to_string())?;
-    } else {
-        return Err(format!("Window {} not found", target_window));
+    target.emit("tab-transfer", payload)?;
+    if should_activate {
+        target.set_focus()?;
+    }
+
+    Ok(transfer_id)
+}
+
+/// Target's answer to `tab-transfer`, forwarded to the source window.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TabTransferAck {
+    pub transfer_id: String,
+    pub file_path: String,
+    pub source_window: String,
+    pub target_window: String,
+    /// Index of the tab in the target pane that now holds the file, `None`
+    /// if the target could not open it.
+    pub tab_index: Option<usize>,
+    pub activated: bool,
+}
+
+/// Called by the target once it has opened a transferred tab. The source
+/// may already be gone if it closed on its last tab; that is not an error.
+#[tauri::command]
+fn complete_tab_transfer(app: tauri::AppHandle, ack: TabTransferAck) -> AppResult<()> {
+    if app.get_webview_window(&ack.source_window).is_some() {
+        let target = tauri::EventTarget::webview_window(ack.source_window.clone());
+        app.emit_to(target, "tab-transfer-complete", ack)?;
+    }
+    Ok(())
+}
+
+/// Refuse anything that would open or feed a second window while
+/// `singleWindowMode` is on.
+fn ensure_multi_window(app: &tauri::AppHandle) -> AppResult<()> {
+    if settings::current(app).single_window_mode {
+        return Err(AppError::Disabled("multiple windows are disabled (single-window mode)".into()));
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeMode {
+    single_window: bool,
+    close_window_on_last_transfer: bool,
+}
+
+/// Which window features are available, so the frontend can hide the
+/// "New Window" and "Move Tab to Window" menu items.
+#[tauri::command]
+fn get_runtime_mode(app: tauri::AppHandle) -> RuntimeMode {
+    let settings = settings::current(&app);
+    RuntimeMode {
+        single_window: settings.single_window_mode,
+        close_window_on_last_transfer: settings.close_window_on_last_transfer,
+    }
+}
+
+fn window_by_label(app: &tauri::AppHandle, label: &str) -> AppResult<tauri::WebviewWindow> {
+    app.get_webview_window(label)
+        .ok_or_else(|| AppError::InvalidInput(format!("Window {} not found", label)))
+}
+
+#[tauri::command]
+fn get_window_state(app: tauri::AppHandle, window_label: String) -> AppResult<window_state::WindowState> {
+    window_state::get(&app, &window_label)
+}
+
+// Pin a window above others. Persisted per window and returns the resulting
+// flag so the menu checkbox stays in sync.
+#[tauri::command]
+async fn set_always_on_top(app: tauri::AppHandle, window_label: String, on_top: bool) -> AppResult<bool> {
+    let window = window_by_label(&app, &window_label)?;
+    window.set_always_on_top(on_top)?;
+    let state = window_state::update(&app, &window_label, |s| s.always_on_top = on_top)?;
+    Ok(state.always_on_top)
+}
+
+/// Zoom the whole UI of a window (0.5 to 3.0, clamped). Remembered for the
+/// window and for the display it is on, so it comes back on relaunch, with
+/// sessions, and when the window moves back to that display.
+#[tauri::command]
+async fn set_window_zoom(app: tauri::AppHandle, window_label: String, factor: f64) -> AppResult<f64> {
+    let window = window_by_label(&app, &window_label)?;
+    window_state::set_zoom(&window, factor)
+}
+
+/// The zoom the window has on its current display.
+#[tauri::command]
+fn get_window_zoom(app: tauri::AppHandle, window_label: String) -> AppResult<f64> {
+    let window = window_by_label(&app, &window_label)?;
+    window_state::current_zoom(&window)
+}
+
+#[tauri::command]
+async fn set_fullscreen(app: tauri::AppHandle, window_label: String, on: bool) -> AppResult<bool> {
+    let window = window_by_label(&app, &window_label)?;
+    window.set_fullscreen(on)?;
+    Ok(window.is_fullscreen()?)
+}
+
+// ============== Watch Suspension ==============
+
+fn notify_watch_suspended(app: &tauri::AppHandle, label: &str, suspended: bool) {
+    let payload = watch::WatchSuspendedPayload { suspended };
+    let _ = app.emit_to(tauri::EventTarget::webview_window(label), "watch-suspended", payload);
+}
+
+/// Pause external-change handling in a window while a bulk command rewrites
+/// files. Nests with other suspensions; returns the new depth. Watching comes
+/// back on its own after `watch::SUSPEND_TIMEOUT` if nobody resumes it.
+#[tauri::command]
+fn suspend_watch(
+    app: tauri::AppHandle,
+    suspensions: tauri::State<'_, watch::WatchSuspensions>,
+    window_label: String,
+) -> AppResult<u32> {
+    window_by_label(&app, &window_label)?;
+    let (depth, generation) = suspensions.suspend(&window_label);
+    if depth == 1 {
+        notify_watch_suspended(&app, &window_label, true);
+    }
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(watch::SUSPEND_TIMEOUT).await;
+        if handle.state::<watch::WatchSuspensions>().expire(&window_label, generation) {
+            tracing::warn!(window = %window_label, "watch suspension timed out; resuming");
+            notify_watch_suspended(&handle, &window_label, false);
+        }
+    });
+    Ok(depth)
+}
+
+/// Undo one `suspend_watch`; the window resumes watching at depth 0.
+#[tauri::command]
+fn resume_watch(
+    app: tauri::AppHandle,
+    suspensions: tauri::State<'_, watch::WatchSuspensions>,
+    window_label: String,
+) -> u32 {
+    let was_suspended = suspensions.is_suspended(&window_label);
+    let depth = suspensions.resume(&window_label);
+    if was_suspended && depth == 0 {
+        notify_watch_suspended(&app, &window_label, false);
+    }
+    depth
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorInfo {
+    index: usize,
+    name: Option<String>,
+    /// Physical pixels, in the virtual desktop's coordinate space.
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+}
+
+#[tauri::command]
+fn list_monitors(app: tauri::AppHandle) -> AppResult<Vec<MonitorInfo>> {
+    Ok(app
+        .available_monitors()?
+        .into_iter()
+        .enumerate()
+        .map(|(index, m)| MonitorInfo {
+            index,
+            name: m.name().cloned(),
+            x: m.position().x,
+            y: m.position().y,
+            width: m.size().width,
+            height: m.size().height,
+            scale_factor: m.scale_factor(),
+        })
+        .collect())
+}
+
+/// Position and size that fit a `size` window centered in a work area
+/// given as `(x, y, width, height)`, shrinking it if the area is smaller.
+fn fit_in_area(area: (i32, i32, u32, u32), size: (u32, u32)) -> ((i32, i32), (u32, u32)) {
+    let (x, y, width, height) = area;
+    let (w, h) = (size.0.min(width), size.1.min(height));
+    let left = x + ((width - w) / 2) as i32;
+    let top = y + ((height - h) / 2) as i32;
+    ((left, top), (w, h))
+}
+
+/// Initial window sizes the UI offers, as `(preset, (width, height))` in
+/// logical pixels. `default` is the regular editor window.
+const WINDOW_PRESETS: &[(&str, (f64, f64))] = &[
+    ("default", (1200.0, 800.0)),
+    ("slides-16-9", (1280.0, 720.0)),
+    ("slides-4-3", (1024.0, 768.0)),
+    ("mobile-preview", (390.0, 844.0)),
+];
+
+/// Below this a window can't show the toolbar.
+const MIN_WINDOW_SIZE: (f64, f64) = (320.0, 240.0);
+
+#[derive(Serialize)]
+struct WindowPreset {
+    id: &'static str,
+    width: f64,
+    height: f64,
+}
+
+#[tauri::command]
+fn list_window_presets() -> Vec<WindowPreset> {
+    WINDOW_PRESETS
+        .iter()
+        .map(|&(id, (width, height))| WindowPreset { id, width, height })
+        .collect()
+}
+
+/// Requested initial size: explicit `width`/`height` win over the preset's,
+/// and nothing requested means `None`. Kept above `MIN_WINDOW_SIZE`.
+fn requested_window_size(
+    preset: Option<&str>,
+    width: Option<f64>,
+    height: Option<f64>,
+) -> AppResult<Option<window_state::WindowSize>> {
+    let base = match preset {
+        Some(id) => Some(
+            WINDOW_PRESETS
+                .iter()
+                .find(|(name, _)| *name == id)
+                .map(|&(_, size)| size)
+                .ok_or_else(|| AppError::InvalidInput(format!("unknown window preset: {}", id)))?,
+        ),
+        None => None,
+    };
+    if base.is_none() && width.is_none() && height.is_none() {
+        return Ok(None);
+    }
+    let (default_w, default_h) = base.unwrap_or(WINDOW_PRESETS[0].1);
+    let pick = |v: Option<f64>, fallback: f64, min: f64| match v {
+        Some(v) if !v.is_finite() => Err(AppError::InvalidInput(format!("invalid window size: {}", v))),
+        Some(v) => Ok(v.max(min)),
+        None => Ok(fallback),
+    };
+    Ok(Some(window_state::WindowSize {
+        width: pick(width, default_w, MIN_WINDOW_SIZE.0)?,
+        height: pick(height, default_h, MIN_WINDOW_SIZE.1)?,
+    }))
+}
+
+/// Shrink and re-center a freshly built window that came out larger than
+/// its monitor's work area.
+fn clamp_to_work_area(window: &tauri::WebviewWindow) -> AppResult<()> {
+    let Some(monitor) = window.current_monitor()? else { return Ok(()) };
+    let area = monitor.work_area();
+    let size = window.outer_size()?;
+    if size.width <= area.size.width && size.height <= area.size.height {
+        return Ok(());
+    }
+    let ((x, y), (width, height)) = fit_in_area(
+        (area.position.x, area.position.y, area.size.width, area.size.height),
+        (size.width, size.height),
+    );
+    // `set_size` sets the inner size; take the frame back off.
+    let inner = window.inner_size()?;
+    let frame = (size.width - inner.width, size.height - inner.height);
+    window.set_size(tauri::PhysicalSize::new(width - frame.0.min(width), height - frame.1.min(height)))?;
+    window.set_position(tauri::PhysicalPosition::new(x, y))?;
+    Ok(())
+}
+
+/// Move a window onto the monitor at `monitor_index` (as listed by
+/// `list_monitors`), centered in its work area or maximized there.
+#[tauri::command]
+async fn move_window_to_monitor(
+    app: tauri::AppHandle,
+    label: String,
+    monitor_index: usize,
+    maximize: bool,
+) -> AppResult<()> {
+    let window = window_by_label(&app, &label)?;
+    let monitor = app
+        .available_monitors()?
+        .into_iter()
+        .nth(monitor_index)
+        .ok_or(AppError::MonitorNotFound { index: monitor_index })?;
+    let area = monitor.work_area();
+    // A maximized window ignores moves, so restore it first.
+    if window.is_maximized()? {
+        window.unmaximize()?;
+    }
+    let size = window.outer_size()?;
+    let ((x, y), (width, height)) = fit_in_area(
+        (area.position.x, area.position.y, area.size.width, area.size.height),
+        (size.width, size.height),
+    );
+    window.set_position(tauri::PhysicalPosition::new(x, y))?;
+    if (width, height) != (size.width, size.height) {
+        window.set_size(tauri::PhysicalSize::new(width, height))?;
+    }
+    if maximize {
+        window.maximize()?;
+    }
+    Ok(())
+}
+
+// Distraction-free mode: the backend owns the OS fullscreen transition and
+// emits `zen-mode { on }` so the frontend hides sidebar and tabs.
+#[tauri::command]
+async fn toggle_zen(app: tauri::AppHandle, window_label: String) -> AppResult<bool> {
+    let window = window_by_label(&app, &window_label)?;
+    let on = !app
+        .state::<window_state::ZenWindows>()
+        .0
+        .lock()
+        .unwrap()
+        .contains(&window_label);
+    window_state::set_zen(&window, on)
+}
+
+#[tauri::command]
+fn get_layout(app: tauri::AppHandle, window_label: String) -> AppResult<window_state::Layout> {
+    Ok(window_state::get(&app, &window_label)?.layout.sanitized())
+}
+
+#[tauri::command]
+fn save_layout(
+    app: tauri::AppHandle,
+    window_label: String,
+    layout: window_state::Layout,
+) -> AppResult<window_state::Layout> {
+    let layout = layout.sanitized();
+    // An emptied window on its way out must not overwrite what it handed off.
+    if window_state::is_handed_off(&app, &window_label) {
+        return Ok(layout);
+    }
+    window_state::update(&app, &window_label, |s| s.layout = layout)?;
+    Ok(layout)
+}
+
+/// Back to the default layout, for when a panel ends up dragged off-screen.
+#[tauri::command]
+fn reset_layout(app: tauri::AppHandle, window_label: String) -> AppResult<window_state::Layout> {
+    let state = window_state::update(&app, &window_label, |s| s.layout = window_state::Layout::default())?;
+    Ok(state.layout)
+}
+
+// ============== Settings + system theme ==============
+
+#[tauri::command]
+fn get_settings(app: tauri::AppHandle) -> settings::AppSettings {
+    settings::current(&app)
+}
+
+#[tauri::command]
+async fn update_settings(app: tauri::AppHandle, new_settings: settings::AppSettings) -> AppResult<settings::AppSettings> {
+    settings::save(&app, &new_settings)?;
+    *app.state::<settings::SettingsState>().0.lock().unwrap() = new_settings.clone();
+    if let Some(log) = app.try_state::<logging::LogState>() {
+        log.apply(&new_settings)?;
+    }
+    for window in app.webview_windows().values() {
+        let _ = window.set_theme(new_settings.theme.window_theme());
+    }
+    if !new_settings.pause_background_when_unfocused && app.state::<background::Background>().resume() {
+        let _ = app.emit("background-throttle", background::BackgroundThrottlePayload { paused: false });
+    }
+    Ok(new_settings)
+}
+
+/// Whether config lives beside the executable (`portable.txt` or
+/// `--portable`), so the UI can show it.
+#[tauri::command]
+fn is_portable() -> bool {
+    portable::is_portable()
+}
+
+/// Make MerMark the handler for `.md`/`.markdown` files and `mermark://`
+/// links, for when the installer's registration didn't take. On Windows a
+/// type the user already chose an app for needs their confirmation, so this
+/// opens Settings and returns `PermissionDenied` explaining the last step.
+#[tauri::command]
+async fn register_file_associations(app: tauri::AppHandle) -> AppResult<()> {
+    tokio::task::spawn_blocking(move || associations::register(&app)).await?
+}
+
+/// Whether MerMark is the default for Markdown files and `mermark://` links.
+#[tauri::command]
+async fn is_default_handler(app: tauri::AppHandle) -> AppResult<bool> {
+    tokio::task::spawn_blocking(move || associations::is_default_handler(&app)).await?
+}
+
+/// Health of the JSON files under the app data folder, for the diagnostics
+/// screen; files recovered this run carry the path of their backup.
+#[tauri::command]
+fn validate_config_files(app: tauri::AppHandle) -> AppResult<Vec<config_recovery::ConfigStatus>> {
+    let dir = portable::resolve_config_root(&app)?;
+    let recoveries = app.state::<config_recovery::Recoveries>().0.lock().unwrap().clone();
+    Ok(config_recovery::validate(&dir, &recoveries))
+}
+
+#[tauri::command]
+fn get_system_theme(state: tauri::State<'_, SystemThemeState>) -> String {
+    state.0.lock().unwrap().clone()
+}
+
+/// Whether background work is currently held, for windows that open while
+/// the app is in the background.
+#[tauri::command]
+fn is_background_paused(background: tauri::State<'_, background::Background>) -> bool {
+    background.is_paused()
+}
+
+/// Focus bookkeeping behind `background-throttle`; see `background.rs`.
+fn on_focus_changed(app: &tauri::AppHandle, label: &str, focused: bool) {
+    // Looking at a present mirror still counts as using the app.
+    if is_helper_window(label) && !present::is_present_label(label) {
+        return;
     }
+    match app.state::<background::Background>().focus_changed(label, focused) {
+        background::FocusChange::Resume => {
+            let _ = app.emit("background-throttle", background::BackgroundThrottlePayload { paused: false });
+        }
+        background::FocusChange::SchedulePause(generation) => {
+            if !settings::current(app).pause_background_when_unfocused {
+                return;
+            }
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(background::PAUSE_DELAY).await;
+                if app.state::<background::Background>().expire(generation) {
+                    let _ = app.emit("background-throttle", background::BackgroundThrottlePayload { paused: true });
+                }
+            });
+        }
+        background::FocusChange::Nothing => {}
+    }
+}
+
+// ============== Troubleshooting log ==============
+
+fn log_state(app: &tauri::AppHandle) -> AppResult<tauri::State<'_, logging::LogState>> {
+    app.try_state::<logging::LogState>()
+        .ok_or_else(|| AppError::Internal("logging is not initialized".into()))
+}
 
+/// Change the log level now and remember it in settings.
+#[tauri::command]
+fn set_log_level(app: tauri::AppHandle, level: logging::LogLevel) -> AppResult<()> {
+    log_state(&app)?.set_level(level)?;
+    let state = app.state::<settings::SettingsState>();
+    let mut current = state.0.lock().unwrap();
+    current.log_level = level;
+    settings::save(&app, &current)?;
     Ok(())
 }
 
+#[tauri::command]
+fn get_log_path(app: tauri::AppHandle) -> AppResult<String> {
+    Ok(log_state(&app)?.path.to_string_lossy().into_owned())
+}
+
+#[tauri::command]
+fn open_log_file(app: tauri::AppHandle) -> AppResult<()> {
+    let path = log_state(&app)?.path.clone();
+    if !path.is_file() {
+        return Err(AppError::NotFound { path: path.to_string_lossy().into_owned() });
+    }
+    external::open_with_default_app(&path)
+}
+
+// ============== Command metrics ==============
+
+/// Batch of `invoke` round trips timed by a webview; dropped unless
+/// `collectMetrics` is on.
+#[tauri::command]
+fn record_command_timings(
+    app: tauri::AppHandle,
+    metrics: tauri::State<'_, metrics::Metrics>,
+    timings: Vec<metrics::CommandTiming>,
+) {
+    if settings::current(&app).collect_metrics {
+        metrics.record(&timings);
+    }
+}
+
+#[tauri::command]
+fn get_command_metrics(metrics: tauri::State<'_, metrics::Metrics>) -> Vec<metrics::CommandMetric> {
+    metrics.summary()
+}
+
+#[tauri::command]
+fn clear_metrics(metrics: tauri::State<'_, metrics::Metrics>) {
+    metrics.clear();
+}
+
 // ============== AI commands (storage + health) ==============
 
 use ai::types::{AccessMap, AuditEntry, CliKind, HealthStatus, SessionMapping, SnapshotIndexEntry};
@@ -208,13 +1120,13 @@ async fn ai_health_check(cli: CliKind, override_path: Option<String>) -> HealthS
 }
 
 #[tauri::command]
-async fn ai_ollama_models(base_url: Option<String>) -> Result<Vec<String>, String> {
-    ai::process::ollama::list_models(base_url.as_deref()).await
+async fn ai_ollama_models(base_url: Option<String>) -> AppResult<Vec<String>> {
+    Ok(ai::process::ollama::list_models(base_url.as_deref()).await?)
 }
 
 #[tauri::command]
-async fn ai_openai_models(base_url: Option<String>) -> Result<Vec<String>, String> {
-    ai::process::openai::list_models(base_url.as_deref()).await
+async fn ai_openai_models(base_url: Option<String>) -> AppResult<Vec<String>> {
+    Ok(ai::process::openai::list_models(base_url.as_deref()).await?)
 }
 
 #[tauri::command]
@@ -223,93 +1135,93 @@ async fn ai_codex_models() -> Vec<ai::process::codex::CodexModelOption> {
 }
 
 #[tauri::command]
-fn ai_access_load(app: tauri::AppHandle, doc_path: String) -> Result<AccessMap, String> {
-    ai::access_map::load(&app, &doc_path)
+fn ai_access_load(app: tauri::AppHandle, doc_path: String) -> AppResult<AccessMap> {
+    Ok(ai::access_map::load(&app, &doc_path)?)
 }
 
 #[tauri::command]
-fn ai_access_save(app: tauri::AppHandle, doc_path: String, map: AccessMap) -> Result<(), String> {
-    ai::access_map::save(&app, &doc_path, &map)
+fn ai_access_save(app: tauri::AppHandle, doc_path: String, map: AccessMap) -> AppResult<()> {
+    Ok(ai::access_map::save(&app, &doc_path, &map)?)
 }
 
 #[tauri::command]
-fn ai_access_migrate(app: tauri::AppHandle, old_path: String, new_path: String) -> Result<(), String> {
-    ai::access_map::migrate(&app, &old_path, &new_path)
+fn ai_access_migrate(app: tauri::AppHandle, old_path: String, new_path: String) -> AppResult<()> {
+    Ok(ai::access_map::migrate(&app, &old_path, &new_path)?)
 }
 
 #[tauri::command]
-fn ai_session_get(app: tauri::AppHandle, doc_path: String) -> Result<Option<SessionMapping>, String> {
-    ai::sessions::get(&app, &doc_path)
+fn ai_session_get(app: tauri::AppHandle, doc_path: String) -> AppResult<Option<SessionMapping>> {
+    Ok(ai::sessions::get(&app, &doc_path)?)
 }
 
 #[tauri::command]
-fn ai_session_upsert(app: tauri::AppHandle, mapping: SessionMapping) -> Result<(), String> {
-    ai::sessions::upsert(&app, mapping)
+fn ai_session_upsert(app: tauri::AppHandle, mapping: SessionMapping) -> AppResult<()> {
+    Ok(ai::sessions::upsert(&app, mapping)?)
 }
 
 #[tauri::command]
-fn ai_session_remove(app: tauri::AppHandle, doc_path: String) -> Result<(), String> {
-    ai::sessions::remove(&app, &doc_path)
+fn ai_session_remove(app: tauri::AppHandle, doc_path: String) -> AppResult<()> {
+    Ok(ai::sessions::remove(&app, &doc_path)?)
 }
 
 #[tauri::command]
-fn ai_session_migrate(app: tauri::AppHandle, old_path: String, new_path: String) -> Result<(), String> {
-    ai::sessions::migrate(&app, &old_path, &new_path)
+fn ai_session_migrate(app: tauri::AppHandle, old_path: String, new_path: String) -> AppResult<()> {
+    Ok(ai::sessions::migrate(&app, &old_path, &new_path)?)
 }
 
 #[tauri::command]
-fn ai_session_recover_by_hash(app: tauri::AppHandle, content_hash: String, cli: CliKind) -> Result<Option<SessionMapping>, String> {
-    ai::sessions::recover_by_hash(&app, &content_hash, cli)
+fn ai_session_recover_by_hash(app: tauri::AppHandle, content_hash: String, cli: CliKind) -> AppResult<Option<SessionMapping>> {
+    Ok(ai::sessions::recover_by_hash(&app, &content_hash, cli)?)
 }
 
 #[tauri::command]
-fn ai_snapshot_list(app: tauri::AppHandle, doc_path: String) -> Result<Vec<SnapshotIndexEntry>, String> {
-    ai::snapshots::list(&app, &doc_path)
+fn ai_snapshot_list(app: tauri::AppHandle, doc_path: String) -> AppResult<Vec<SnapshotIndexEntry>> {
+    Ok(ai::snapshots::list(&app, &doc_path)?)
 }
 
 #[tauri::command]
-fn ai_snapshot_create(app: tauri::AppHandle, doc_path: String, content: String, source_session_id: Option<String>, keep: usize) -> Result<SnapshotIndexEntry, String> {
-    ai::snapshots::create(&app, &doc_path, &content, source_session_id, keep)
+fn ai_snapshot_create(app: tauri::AppHandle, doc_path: String, content: String, source_session_id: Option<String>, keep: usize) -> AppResult<SnapshotIndexEntry> {
+    Ok(ai::snapshots::create(&app, &doc_path, &content, source_session_id, keep)?)
 }
 
 #[tauri::command]
-fn ai_snapshot_restore(app: tauri::AppHandle, doc_path: String, id: String) -> Result<String, String> {
-    ai::snapshots::restore(&app, &doc_path, &id)
+fn ai_snapshot_restore(app: tauri::AppHandle, doc_path: String, id: String) -> AppResult<String> {
+    Ok(ai::snapshots::restore(&app, &doc_path, &id)?)
 }
 
 #[tauri::command]
-fn ai_snapshot_set_pinned(app: tauri::AppHandle, doc_path: String, id: String, pinned: bool) -> Result<(), String> {
-    ai::snapshots::set_pinned(&app, &doc_path, &id, pinned)
+fn ai_snapshot_set_pinned(app: tauri::AppHandle, doc_path: String, id: String, pinned: bool) -> AppResult<()> {
+    Ok(ai::snapshots::set_pinned(&app, &doc_path, &id, pinned)?)
 }
 
 #[tauri::command]
-fn ai_snapshot_delete(app: tauri::AppHandle, doc_path: String, id: String) -> Result<(), String> {
-    ai::snapshots::delete(&app, &doc_path, &id)
+fn ai_snapshot_delete(app: tauri::AppHandle, doc_path: String, id: String) -> AppResult<()> {
+    Ok(ai::snapshots::delete(&app, &doc_path, &id)?)
 }
 
 #[tauri::command]
-fn ai_snapshot_export(app: tauri::AppHandle, doc_path: String, id: String, dest: String) -> Result<(), String> {
-    ai::snapshots::export(&app, &doc_path, &id, std::path::Path::new(&dest))
+fn ai_snapshot_export(app: tauri::AppHandle, doc_path: String, id: String, dest: String) -> AppResult<()> {
+    Ok(ai::snapshots::export(&app, &doc_path, &id, std::path::Path::new(&dest))?)
 }
 
 #[tauri::command]
-fn ai_snapshot_migrate(app: tauri::AppHandle, old_path: String, new_path: String) -> Result<(), String> {
-    ai::snapshots::migrate(&app, &old_path, &new_path)
+fn ai_snapshot_migrate(app: tauri::AppHandle, old_path: String, new_path: String) -> AppResult<()> {
+    Ok(ai::snapshots::migrate(&app, &old_path, &new_path)?)
 }
 
 #[tauri::command]
-fn ai_audit_append(app: tauri::AppHandle, entry: AuditEntry) -> Result<(), String> {
-    ai::audit::append(&app, entry)
+fn ai_audit_append(app: tauri::AppHandle, entry: AuditEntry) -> AppResult<()> {
+    Ok(ai::audit::append(&app, entry)?)
 }
 
 #[tauri::command]
-fn ai_audit_read(app: tauri::AppHandle, since: Option<String>, until: Option<String>) -> Result<Vec<AuditEntry>, String> {
-    ai::audit::read(&app, since.as_deref(), until.as_deref())
+fn ai_audit_read(app: tauri::AppHandle, since: Option<String>, until: Option<String>) -> AppResult<Vec<AuditEntry>> {
+    Ok(ai::audit::read(&app, since.as_deref(), until.as_deref())?)
 }
 
 #[tauri::command]
-fn ai_audit_clear(app: tauri::AppHandle) -> Result<(), String> {
-    ai::audit::clear(&app)
+fn ai_audit_clear(app: tauri::AppHandle) -> AppResult<()> {
+    Ok(ai::audit::clear(&app)?)
 }
 
 #[tauri::command]
@@ -319,8 +1231,8 @@ async fn ai_send(
     registry: tauri::State<'_, ai::process::ChildRegistry>,
     req: ai::process::AiSendRequest,
     request_id: String,
-) -> Result<String, String> {
-    ai::process::spawn(app, window.label().to_string(), registry, req, request_id).await
+) -> AppResult<String> {
+    Ok(ai::process::spawn(app, window.label().to_string(), registry, req, request_id).await?)
 }
 
 #[tauri::command]
@@ -343,17 +1255,295 @@ fn ai_image_save(
     app: tauri::AppHandle,
     bytes: Vec<u8>,
     extension: String,
-) -> Result<String, String> {
+) -> AppResult<String> {
     let dir = ai::paths::images_dir(&app)?;
     let safe_ext = extension.trim().trim_start_matches('.').to_ascii_lowercase();
     let allowed = matches!(safe_ext.as_str(), "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp");
     let ext = if allowed { safe_ext.as_str() } else { "png" };
     let name = format!("{}.{}", uuid::Uuid::new_v4(), ext);
     let path = dir.join(name);
-    std::fs::write(&path, &bytes).map_err(|e| format!("write image failed: {}", e))?;
+    std::fs::write(&path, &bytes).map_err(|e| AppError::io(&path, e))?;
     Ok(path.to_string_lossy().into_owned())
 }
 
+// ============== Document read / save commands ==============
+
+/// Read a document for the editor. Files over the `maxOpenBytes` setting are
+/// refused with `FileTooLarge`, and binary-looking ones with `NotTextFile`,
+/// so the UI can warn first; `force` skips both once the user has confirmed.
+#[tauri::command]
+async fn read_text_file(
+    app: tauri::AppHandle,
+    path: String,
+    force: Option<bool>,
+) -> AppResult<files::LoadedText> {
+    let limit = if force.unwrap_or(false) {
+        None
+    } else {
+        Some(settings::current(&app).max_open_bytes)
+    };
+    let loaded = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || files::read_text(Path::new(&path), limit).map(files::LoadedText::new)
+    })
+    .await??;
+    // Dialogs start next to the last document opened.
+    let _ = dialogs::remember(&app, Path::new(&path));
+    Ok(loaded)
+}
+
+/// Re-read a document after an external change, with its hash, mtime, line
+/// ending and encoding for resetting the buffer. The change it picks up is
+/// marked as seen, so the watcher does not report it again.
+#[tauri::command]
+async fn reload_file(
+    app: tauri::AppHandle,
+    locks: tauri::State<'_, files::FileLocks>,
+    path: String,
+) -> AppResult<files::ReloadedText> {
+    let limit = settings::current(&app).max_open_bytes;
+    files::reload(&locks, Path::new(&path), Some(limit)).await
+}
+
+/// Open dialog starting in `start_dir`, or the remembered folder when none
+/// is given. Returns the chosen path(s), or `None` when cancelled. The
+/// document open/save dialogs (`services/fileDialogs.ts`) go through here
+/// so the last folder sticks.
+#[tauri::command]
+async fn open_file_dialog(
+    app: tauri::AppHandle,
+    start_dir: Option<String>,
+    multiple: Option<bool>,
+    filters: Option<Vec<dialogs::FileFilter>>,
+) -> AppResult<Option<Vec<String>>> {
+    dialogs::open(app, start_dir, multiple.unwrap_or(false), filters.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn save_file_dialog(
+    app: tauri::AppHandle,
+    suggested_name: Option<String>,
+    start_dir: Option<String>,
+    filters: Option<Vec<dialogs::FileFilter>>,
+) -> AppResult<Option<String>> {
+    dialogs::save(app, suggested_name, start_dir, filters.unwrap_or_default()).await
+}
+
+/// Hash the editor buffer the same way reads and saves do, so dirtiness is a
+/// hash comparison against the baseline rather than a full string compare.
+#[tauri::command]
+fn content_hash(content: String) -> String {
+    files::content_hash(content.as_bytes())
+}
+
+// Both save commands return the new content hash. Passing `expected_hash`
+// (the baseline from the last read/save) turns on the on-disk conflict check.
+// Content holding editor sentinels (`__CURSOR__`, ...) fails with
+// `sentinelLeak` unless `sanitize` asks for them to be stripped. `verify`
+// reads the file back and fails with `verificationFailed` on a mismatch.
+#[tauri::command]
+async fn save_file_atomic(
+    locks: tauri::State<'_, files::FileLocks>,
+    path: String,
+    content: String,
+    expected_hash: Option<String>,
+    sanitize: Option<bool>,
+    verify: Option<bool>,
+) -> AppResult<String> {
+    let content = files::guard_sentinels(&path, content, sanitize.unwrap_or(false))?;
+    files::save_atomic(&locks, Path::new(&path), &content, expected_hash.as_deref(), verify.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn save_file_with_backup(
+    locks: tauri::State<'_, files::FileLocks>,
+    path: String,
+    content: String,
+    expected_hash: Option<String>,
+    sanitize: Option<bool>,
+) -> AppResult<String> {
+    let content = files::guard_sentinels(&path, content, sanitize.unwrap_or(false))?;
+    files::save_with_backup(&locks, Path::new(&path), &content, expected_hash.as_deref()).await
+}
+
+/// Call after a successful save: runs the on-save command of the file's
+/// workspace, debounced, with its output on `build-output`. Returns whether
+/// a command is configured for it.
+#[tauri::command]
+fn run_on_save(app: tauri::AppHandle, path: String) -> bool {
+    on_save::schedule(&app, Path::new(&path))
+}
+
+#[tauri::command]
+fn get_on_save_command(app: tauri::AppHandle, root: String) -> Option<Vec<String>> {
+    settings::current(&app).on_save_commands.get(&root).cloned()
+}
+
+/// Set the argv run after saves under `root`; an empty list removes it.
+/// `{file}` and `{root}` in an argument are replaced per run, and when
+/// neither is used both are passed as the last two arguments.
+#[tauri::command]
+fn set_on_save_command(app: tauri::AppHandle, root: String, argv: Vec<String>) -> AppResult<()> {
+    if argv.first().is_some_and(|program| program.trim().is_empty()) {
+        return Err(AppError::InvalidInput("the on-save command needs a program".to_string()));
+    }
+    let state = app.state::<settings::SettingsState>();
+    let mut current = state.0.lock().unwrap();
+    let mut updated = current.clone();
+    if argv.is_empty() {
+        updated.on_save_commands.remove(&root);
+    } else {
+        updated.on_save_commands.insert(root, argv);
+    }
+    settings::save(&app, &updated)?;
+    *current = updated;
+    Ok(())
+}
+
+#[tauri::command]
+async fn write_binary_atomic(
+    locks: tauri::State<'_, files::FileLocks>,
+    path: String,
+    bytes: Vec<u8>,
+    overwrite: bool,
+) -> AppResult<files::BinaryWrite> {
+    files::write_binary_atomic(&locks, Path::new(&path), &bytes, overwrite).await
+}
+
+/// Save a pasted or imported image next to the document after applying
+/// `options` (downscale, convert, strip metadata). The extension of
+/// `dest_path` follows the output format; the response carries the final
+/// path, dimensions and byte sizes.
+#[tauri::command]
+async fn import_image(
+    locks: tauri::State<'_, files::FileLocks>,
+    bytes: Vec<u8>,
+    dest_path: String,
+    options: Option<images::ImageOptions>,
+    overwrite: Option<bool>,
+) -> AppResult<images::ImportedImage> {
+    let original_bytes = bytes.len() as u64;
+    let options = options.unwrap_or_default();
+    let encoded = tokio::task::spawn_blocking(move || images::process(&bytes, &options)).await??;
+    let dest = Path::new(&dest_path);
+    let keeps_extension = dest
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case(encoded.extension) || (encoded.extension == "jpg" && e.eq_ignore_ascii_case("jpeg")))
+        .unwrap_or(false);
+    let dest = if keeps_extension { dest.to_path_buf() } else { dest.with_extension(encoded.extension) };
+    let write = files::write_binary_atomic(&locks, &dest, &encoded.bytes, overwrite.unwrap_or(false)).await?;
+    Ok(images::ImportedImage {
+        write,
+        width: encoded.width,
+        height: encoded.height,
+        bytes: encoded.bytes.len() as u64,
+        original_bytes,
+        format: encoded.extension,
+    })
+}
+
+#[tauri::command]
+async fn write_draft(
+    app: tauri::AppHandle,
+    locks: tauri::State<'_, files::FileLocks>,
+    doc_path: String,
+    content: String,
+    sanitize: Option<bool>,
+) -> AppResult<()> {
+    let content = files::guard_sentinels(&doc_path, content, sanitize.unwrap_or(false))?;
+    files::write_draft(&app, &locks, &doc_path, &content).await
+}
+
+#[tauri::command]
+fn read_draft(app: tauri::AppHandle, doc_path: String) -> AppResult<Option<String>> {
+    files::read_draft(&app, &doc_path)
+}
+
+#[tauri::command]
+fn discard_draft(app: tauri::AppHandle, doc_path: String) -> AppResult<()> {
+    files::discard_draft(&app, &doc_path)
+}
+
+/// `open-file` payload for opens that carry flags; plain opens still send
+/// just the path string.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenFilePayload {
+    path: String,
+    readonly: bool,
+    /// Window meant to handle the open; unset means whoever receives it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    window: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reveal: Option<RevealMatch>,
+}
+
+/// Where to scroll once the file is showing. `start`/`end` are the match's
+/// byte range in the file, only sent while it still holds the matched text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RevealMatch {
+    line: usize,
+    column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<usize>,
+}
+
+impl RevealMatch {
+    /// Keep the byte range only if `text` still has the hit's match there;
+    /// after an edit the line number is the best guess left.
+    fn for_hit(hit: &ContentSearchHit, text: &str) -> Self {
+        let unchanged = text
+            .get(hit.start..hit.end)
+            .is_some_and(|found| found.eq_ignore_ascii_case(&hit.matched));
+        RevealMatch {
+            line: hit.line,
+            column: if unchanged { hit.column } else { 1 },
+            start: unchanged.then_some(hit.start),
+            end: unchanged.then_some(hit.end),
+        }
+    }
+}
+
+// Open a file for viewing only. The frontend disables editing and keeps the
+// tab out of autosave/drafts; since we never write it, it never enters the
+// self-write set, while external changes are still watched for reload.
+#[tauri::command]
+fn open_readonly(window: tauri::WebviewWindow, path: String) -> AppResult<()> {
+    if !Path::new(&path).is_file() {
+        return Err(AppError::NotFound { path });
+    }
+    window.emit("open-file", OpenFilePayload { path, readonly: true, window: None, reveal: None })?;
+    Ok(())
+}
+
+// Per-document place memory. Both are no-ops while `rememberPositions` is
+// off, so the frontend can call them unconditionally.
+#[tauri::command]
+fn save_position(app: tauri::AppHandle, path: String, scroll: f64, cursor: u64) -> AppResult<()> {
+    if !settings::current(&app).remember_positions {
+        return Ok(());
+    }
+    positions::save(&app, &path, scroll, cursor)
+}
+
+#[tauri::command]
+fn get_position(app: tauri::AppHandle, path: String) -> AppResult<Option<positions::Position>> {
+    if !settings::current(&app).remember_positions {
+        return Ok(None);
+    }
+    positions::get(&app, &path)
+}
+
+// Lets the frontend watcher drop change events caused by our own saves.
+#[tauri::command]
+fn is_own_write(locks: tauri::State<'_, files::FileLocks>, path: String) -> bool {
+    locks.is_own_write(Path::new(&path))
+}
+
 // ============== Workspace (folder browser) commands ==============
 
 #[derive(Serialize)]
@@ -391,9 +1581,8 @@ fn is_workspace_hidden(name: &str) -> bool {
     name.starts_with('.') || name == "node_modules"
 }
 
-fn read_workspace_subtree(path: &Path, depth: usize) -> Result<WorkspaceNode, String> {
-    let metadata = std::fs::metadata(path)
-        .map_err(|e| format!("read metadata for {}: {}", path.display(), e))?;
+fn read_workspace_subtree(path: &Path, depth: usize) -> AppResult<WorkspaceNode> {
+    let metadata = std::fs::metadata(path).map_err(|e| AppError::io(path, e))?;
     let name = path
         .file_name()
         .map(|n| n.to_string_lossy().into_owned())
@@ -417,13 +1606,12 @@ fn read_workspace_subtree(path: &Path, depth: usize) -> Result<WorkspaceNode, St
     }
 
     if !metadata.is_dir() {
-        return Err(format!("path is neither file nor directory: {}", path.display()));
+        return Err(AppError::InvalidInput(format!("path is neither file nor directory: {}", path.display())));
     }
 
     let mut children: Vec<WorkspaceNode> = Vec::new();
     if depth < WORKSPACE_TREE_MAX_DEPTH {
-        let entries = std::fs::read_dir(path)
-            .map_err(|e| format!("read_dir {}: {}", path.display(), e))?;
+        let entries = std::fs::read_dir(path).map_err(|e| AppError::io(path, e))?;
         let mut folders: Vec<PathBuf> = Vec::new();
         let mut files: Vec<PathBuf> = Vec::new();
         for entry in entries.flatten() {
@@ -474,36 +1662,91 @@ fn read_workspace_subtree(path: &Path, depth: usize) -> Result<WorkspaceNode, St
 }
 
 #[tauri::command]
-async fn read_workspace_tree(root: String) -> Result<WorkspaceNode, String> {
+async fn read_workspace_tree(root: String) -> AppResult<WorkspaceNode> {
     // Walking a large folder is CPU/IO bound and can take seconds. Run it on
     // tokio's blocking pool so the Tauri command thread (and the renderer
     // IPC) stays responsive — the UI shows its loading state in the meantime.
     tokio::task::spawn_blocking(move || {
         let path = Path::new(&root);
         if !path.exists() {
-            return Err(format!("workspace path does not exist: {}", root));
+            return Err(AppError::NotFound { path: root });
         }
         if !path.is_dir() {
-            return Err(format!("workspace path is not a directory: {}", root));
+            return Err(AppError::InvalidInput(format!("workspace path is not a directory: {}", root)));
         }
         read_workspace_subtree(path, 0)
     })
-    .await
-    .map_err(|e| format!("worker join: {}", e))?
+    .await?
+}
+
+// Streaming alternative to `read_workspace_tree` for huge vaults: entries
+// arrive as `scan-entry` batches on the calling window, followed by
+// `scan-complete`. Resolves with the same summary once the walk ends.
+#[tauri::command]
+async fn scan_workspace(
+    window: tauri::WebviewWindow,
+    scans: tauri::State<'_, scan::ScanRegistry>,
+    root: String,
+    scan_id: String,
+) -> AppResult<scan::ScanComplete> {
+    let path = PathBuf::from(&root);
+    if !path.is_dir() {
+        return Err(AppError::NotFound { path: root });
+    }
+    let cancel = scans.register(&scan_id);
+    let emitter = window.clone();
+    let id = scan_id.clone();
+    let walked = tokio::task::spawn_blocking(move || {
+        scan::walk(&path, &cancel, |entries| {
+            let _ = emitter.emit("scan-entry", scan::ScanBatch { scan_id: id.clone(), entries });
+        })
+    })
+    .await;
+    scans.finish(&scan_id);
+    let (total, cancelled) = walked?;
+    let complete = scan::ScanComplete { scan_id, total, cancelled };
+    window.emit("scan-complete", complete.clone())?;
+    Ok(complete)
+}
+
+#[tauri::command]
+fn cancel_scan(scans: tauri::State<'_, scan::ScanRegistry>, scan_id: String) -> bool {
+    scans.cancel(&scan_id)
+}
+
+/// Open a linked file (PDF, spreadsheet, image, ...) with its default app.
+/// Only targets under a folder holding an open document or a recent
+/// workspace qualify; executables are refused unless `allowExecutables`.
+#[tauri::command]
+fn open_path_external(
+    app: tauri::AppHandle,
+    registry: tauri::State<'_, OpenFilesRegistry>,
+    path: String,
+) -> AppResult<()> {
+    let mut roots: Vec<PathBuf> = registry
+        .0
+        .lock()
+        .unwrap()
+        .keys()
+        .filter_map(|doc| Path::new(doc).parent().map(Path::to_path_buf))
+        .collect();
+    roots.extend(recent::folders(&app)?.into_iter().map(PathBuf::from));
+    let target = external::check_target(&path, &roots, settings::current(&app).allow_executables)?;
+    external::open_with_default_app(&target)
 }
 
 #[tauri::command]
-fn create_md_file(parent: String, name: String) -> Result<String, String> {
+fn create_md_file(parent: String, name: String) -> AppResult<String> {
     let parent_path = Path::new(&parent);
     if !parent_path.is_dir() {
-        return Err(format!("parent is not a directory: {}", parent));
+        return Err(AppError::InvalidInput(format!("parent is not a directory: {}", parent)));
     }
     let trimmed = name.trim();
     if trimmed.is_empty() {
-        return Err("file name cannot be empty".into());
+        return Err(AppError::InvalidInput("file name cannot be empty".into()));
     }
     if trimmed.contains('/') || trimmed.contains('\\') {
-        return Err("file name cannot contain path separators".into());
+        return Err(AppError::InvalidInput("file name cannot contain path separators".into()));
     }
     let final_name = if is_workspace_markdown(trimmed) {
         trimmed.to_string()
@@ -512,69 +1755,117 @@ fn create_md_file(parent: String, name: String) -> Result<String, String> {
     };
     let full = parent_path.join(&final_name);
     if full.exists() {
-        return Err(format!("file already exists: {}", full.display()));
+        return Err(AppError::AlreadyExists { path: full.to_string_lossy().into_owned() });
     }
-    std::fs::write(&full, "").map_err(|e| format!("create file: {}", e))?;
+    std::fs::write(&full, "").map_err(|e| AppError::io(&full, e))?;
     Ok(full.to_string_lossy().into_owned())
 }
 
 #[tauri::command]
-fn create_folder(parent: String, name: String) -> Result<String, String> {
+fn create_folder(parent: String, name: String) -> AppResult<String> {
     let parent_path = Path::new(&parent);
     if !parent_path.is_dir() {
-        return Err(format!("parent is not a directory: {}", parent));
+        return Err(AppError::InvalidInput(format!("parent is not a directory: {}", parent)));
     }
     let trimmed = name.trim();
     if trimmed.is_empty() {
-        return Err("folder name cannot be empty".into());
+        return Err(AppError::InvalidInput("folder name cannot be empty".into()));
     }
     if trimmed.contains('/') || trimmed.contains('\\') {
-        return Err("folder name cannot contain path separators".into());
+        return Err(AppError::InvalidInput("folder name cannot contain path separators".into()));
     }
     let full = parent_path.join(trimmed);
     if full.exists() {
-        return Err(format!("folder already exists: {}", full.display()));
+        return Err(AppError::AlreadyExists { path: full.to_string_lossy().into_owned() });
     }
-    std::fs::create_dir(&full).map_err(|e| format!("create folder: {}", e))?;
+    std::fs::create_dir(&full).map_err(|e| AppError::io(&full, e))?;
     Ok(full.to_string_lossy().into_owned())
 }
 
 #[tauri::command]
-fn rename_path(from: String, to: String) -> Result<(), String> {
+fn rename_path(app: tauri::AppHandle, from: String, to: String) -> AppResult<()> {
     let from_path = Path::new(&from);
     let to_path = Path::new(&to);
     if !from_path.exists() {
-        return Err(format!("source does not exist: {}", from));
+        return Err(AppError::NotFound { path: from });
     }
     if to_path.exists() {
-        return Err(format!("destination already exists: {}", to));
+        return Err(AppError::AlreadyExists { path: to });
+    }
+    std::fs::rename(from_path, to_path).map_err(|e| AppError::io(from_path, e))?;
+    if let Err(e) = db::renamed(&app, from_path, to_path) {
+        tracing::warn!("notes index not updated after rename: {}", e);
     }
-    std::fs::rename(from_path, to_path).map_err(|e| format!("rename: {}", e))?;
     Ok(())
 }
 
 #[tauri::command]
-fn delete_path(path: String) -> Result<(), String> {
+fn delete_path(app: tauri::AppHandle, path: String) -> AppResult<()> {
     let target = Path::new(&path);
     if !target.exists() {
-        return Err(format!("path does not exist: {}", path));
+        return Err(AppError::NotFound { path });
     }
-    let metadata = std::fs::metadata(target).map_err(|e| format!("stat: {}", e))?;
+    let metadata = std::fs::metadata(target).map_err(|e| AppError::io(target, e))?;
     if metadata.is_dir() {
-        std::fs::remove_dir_all(target).map_err(|e| format!("remove dir: {}", e))?;
+        std::fs::remove_dir_all(target).map_err(|e| AppError::io(target, e))?;
     } else {
-        std::fs::remove_file(target).map_err(|e| format!("remove file: {}", e))?;
+        std::fs::remove_file(target).map_err(|e| AppError::io(target, e))?;
+    }
+    if let Err(e) = db::remove_file(&app, target) {
+        tracing::warn!("notes index not updated after delete: {}", e);
     }
     Ok(())
 }
 
+// ============== Recent folders ==============
+
+#[tauri::command]
+fn add_recent_folder(app: tauri::AppHandle, path: String) -> AppResult<Vec<String>> {
+    recent::add_folder(&app, &path)
+}
+
+#[tauri::command]
+fn get_recent_folders(app: tauri::AppHandle) -> AppResult<Vec<String>> {
+    recent::folders(&app)
+}
+
+#[tauri::command]
+fn clear_recent_folders(app: tauri::AppHandle) -> AppResult<()> {
+    recent::clear_folders(&app)
+}
+
+// ============== User snippets, keymap and templates ==============
+
+/// Last good copy of `snippets.json` or `keymap.json` (`which` is
+/// `snippets` / `keymap`); re-fetch on `config-reloaded`.
+#[tauri::command]
+fn get_user_config(
+    config: tauri::State<'_, user_config::UserConfig>,
+    which: String,
+) -> Option<serde_json::Value> {
+    config.get(&which)
+}
+
+#[tauri::command]
+fn list_templates(app: tauri::AppHandle) -> AppResult<Vec<user_config::Template>> {
+    user_config::templates(&app)
+}
+
 // ============== Content search across open workspaces ==============
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct ContentSearchHit {
     path: String,
     /// 1-based line number where the match was found.
     line: usize,
+    /// 1-based character column of the match within the line.
+    column: usize,
+    /// Byte range of the match in the file, for `open_at_match`.
+    start: usize,
+    end: usize,
+    /// The matched text as it appeared in the file.
+    matched: String,
     /// The matching line, trimmed and capped to 240 chars for display.
     snippet: String,
 }
@@ -590,16 +1881,18 @@ struct ContentSearchHit {
 ///   - Total wall-clock budget: 4 s, then early-stop with whatever we have.
 #[tauri::command]
 async fn search_workspace_content(
+    app: tauri::AppHandle,
     roots: Vec<String>,
     query: String,
-) -> Result<Vec<ContentSearchHit>, String> {
+) -> AppResult<Vec<ContentSearchHit>> {
+    let max_open_bytes = settings::current(&app).max_open_bytes;
     let q = query.trim().to_string();
     if q.is_empty() {
         return Ok(Vec::new());
     }
     let q_lower = q.to_ascii_lowercase();
 
-    tokio::task::spawn_blocking(move || -> Result<Vec<ContentSearchHit>, String> {
+    tokio::task::spawn_blocking(move || -> AppResult<Vec<ContentSearchHit>> {
         let start = std::time::Instant::now();
         let budget = std::time::Duration::from_secs(4);
         const MAX_FILES: usize = 5_000;
@@ -641,6 +1934,10 @@ async fn search_workspace_content(
                 if !file_type.is_file() || !is_workspace_markdown(&name) {
                     continue;
                 }
+                // A "markdown" file too big to open is mislabeled data.
+                if entry.metadata().map(|m| m.len() > max_open_bytes).unwrap_or(true) {
+                    continue;
+                }
                 files_visited += 1;
                 if files_visited > MAX_FILES {
                     break;
@@ -655,8 +1952,13 @@ async fn search_workspace_content(
                     Ok(s) => s,
                     Err(_) => continue,
                 };
-                for (idx, line) in text.lines().enumerate() {
-                    if line.to_ascii_lowercase().contains(&q_lower) {
+                let mut line_start = 0;
+                for (idx, segment) in text.split_inclusive('\n').enumerate() {
+                    let offset = line_start;
+                    line_start += segment.len();
+                    let line = segment.trim_end_matches(['\n', '\r']);
+                    // ASCII lowercasing keeps byte offsets, so `at` indexes `line` too.
+                    if let Some(at) = line.to_ascii_lowercase().find(&q_lower) {
                         let trimmed = line.trim();
                         let snippet = if trimmed.len() > 240 {
                             format!("{}…", &trimmed.chars().take(240).collect::<String>())
@@ -666,6 +1968,10 @@ async fn search_workspace_content(
                         hits.push(ContentSearchHit {
                             path: path.to_string_lossy().into_owned(),
                             line: idx + 1,
+                            column: line[..at].chars().count() + 1,
+                            start: offset + at,
+                            end: offset + at + q_lower.len(),
+                            matched: line[at..at + q_lower.len()].to_string(),
                             snippet,
                         });
                         if hits.len() >= MAX_HITS {
@@ -676,39 +1982,278 @@ async fn search_workspace_content(
             }
         }
 
-        Ok(hits)
-    })
-    .await
-    .map_err(|e| format!("worker join: {}", e))?
+        Ok(hits)
+    })
+    .await?
+}
+
+/// Jump to a `search_workspace_content` hit: the window that already has
+/// the file open handles it (brought to front), otherwise the caller opens
+/// it. The re-read tells whether the hit's byte range is still valid.
+#[tauri::command]
+async fn open_at_match(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    registry: tauri::State<'_, OpenFilesRegistry>,
+    hit: ContentSearchHit,
+) -> AppResult<()> {
+    let path = PathBuf::from(&hit.path);
+    let limit = settings::current(&app).max_open_bytes;
+    let text = tokio::task::spawn_blocking(move || files::read_text(&path, Some(limit))).await??;
+    let reveal = RevealMatch::for_hit(&hit, &text);
+    let owner = registry.0.lock().unwrap().get(&hit.path).cloned();
+    let target = match owner.and_then(|label| app.get_webview_window(&label)) {
+        Some(owner) => {
+            bring_to_front(&owner)?;
+            owner
+        }
+        None => window,
+    };
+    let payload = OpenFilePayload {
+        path: hit.path,
+        readonly: false,
+        window: Some(target.label().to_string()),
+        reveal: Some(reveal),
+    };
+    app.emit_to(tauri::EventTarget::webview_window(target.label()), "open-file", payload)?;
+    Ok(())
+}
+
+fn read_file_capped(path: &Path, max_bytes: usize) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut f = std::fs::File::open(path)?;
+    let mut buf = Vec::with_capacity(max_bytes.min(64 * 1024));
+    let mut chunk = [0u8; 8192];
+    while buf.len() < max_bytes {
+        let n = f.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        let take = n.min(max_bytes - buf.len());
+        buf.extend_from_slice(&chunk[..take]);
+        if take < n {
+            break;
+        }
+    }
+    Ok(buf)
+}
+
+// ============== Backlinks between notes ==============
+
+/// Find every `[[wikilink]]` or relative Markdown link under `root` that
+/// points at `target_path`. Walks the workspace with the same limits as the
+/// content search.
+#[tauri::command]
+async fn find_backlinks(app: tauri::AppHandle, target_path: String, root: String) -> AppResult<Vec<links::BacklinkHit>> {
+    let max_bytes = settings::current(&app).max_open_bytes;
+    tokio::task::spawn_blocking(move || {
+        let root_path = Path::new(&root);
+        if !root_path.is_dir() {
+            return Err(AppError::InvalidInput(format!("workspace path is not a directory: {}", root)));
+        }
+        Ok(links::find_backlinks(Path::new(&target_path), root_path, max_bytes))
+    })
+    .await?
+}
+
+/// Rewrite references to `old_path` so they point at `new_path`. Meant to be
+/// offered after `rename_path` so links across the workspace keep working.
+/// With `dry_run` nothing is written; the planned changes are returned for
+/// review and a second call without it applies them.
+#[tauri::command]
+async fn update_backlinks(
+    app: tauri::AppHandle,
+    old_path: String,
+    new_path: String,
+    root: String,
+    dry_run: Option<bool>,
+) -> AppResult<links::BacklinkUpdate> {
+    let max_bytes = settings::current(&app).max_open_bytes;
+    tokio::task::spawn_blocking(move || {
+        let root_path = Path::new(&root);
+        if !root_path.is_dir() {
+            return Err(AppError::InvalidInput(format!("workspace path is not a directory: {}", root)));
+        }
+        let locks = app.state::<files::FileLocks>();
+        let (old_path, new_path) = (Path::new(&old_path), Path::new(&new_path));
+        links::update_backlinks(&locks, old_path, new_path, root_path, max_bytes, dry_run.unwrap_or(false))
+    })
+    .await?
+}
+
+/// Rename a note inside its folder and rewrite references to it across
+/// `root` in one go. With `dry_run` nothing moves; the files that would be
+/// touched and their changed lines come back for review.
+#[tauri::command]
+async fn rename_with_refs(
+    app: tauri::AppHandle,
+    old_path: String,
+    new_name: String,
+    root: String,
+    dry_run: Option<bool>,
+) -> AppResult<links::RenameWithRefs> {
+    let max_bytes = settings::current(&app).max_open_bytes;
+    tokio::task::spawn_blocking(move || {
+        let root_path = Path::new(&root);
+        if !root_path.is_dir() {
+            return Err(AppError::InvalidInput(format!("workspace path is not a directory: {}", root)));
+        }
+        let dry_run = dry_run.unwrap_or(false);
+        let locks = app.state::<files::FileLocks>();
+        let result = links::rename_with_refs(&locks, Path::new(&old_path), &new_name, root_path, max_bytes, dry_run)?;
+        if !dry_run {
+            if let Err(e) = db::renamed(&app, Path::new(&old_path), Path::new(&result.new_path)) {
+                tracing::warn!("notes index not updated after rename: {}", e);
+            }
+        }
+        Ok(result)
+    })
+    .await?
+}
+
+// ============== Notes index ==============
+
+/// Re-read every note under `root` into the notes index and keep watching
+/// it. Replaces the previously indexed workspace; known files keep their IDs.
+#[tauri::command]
+async fn rebuild_index(app: tauri::AppHandle, root: String) -> AppResult<db::IndexSummary> {
+    tokio::task::spawn_blocking(move || db::rebuild(&app, Path::new(&root))).await?
+}
+
+/// Index (or re-index) one note, e.g. right after saving it.
+#[tauri::command]
+async fn index_file(app: tauri::AppHandle, path: String) -> AppResult<db::NoteSummary> {
+    tokio::task::spawn_blocking(move || db::index_file(&app, Path::new(&path))).await?
+}
+
+/// Drop a note, or every note under a folder, from the index.
+#[tauri::command]
+fn remove_indexed_file(app: tauri::AppHandle, path: String) -> AppResult<usize> {
+    db::remove_file(&app, Path::new(&path))
+}
+
+/// Stable ID of the note at `path`. It does not change when the note is
+/// edited, and moves with it on a rename.
+#[tauri::command]
+async fn get_document_id(app: tauri::AppHandle, path: String) -> AppResult<String> {
+    tokio::task::spawn_blocking(move || db::document_id(&app, Path::new(&path))).await?
+}
+
+#[tauri::command]
+fn list_note_tags(app: tauri::AppHandle) -> AppResult<Vec<db::TagCount>> {
+    db::tags(&app)
+}
+
+/// Notes tagged `tag` or one of its nested tags (`project` finds `project/alpha`).
+#[tauri::command]
+fn notes_with_tag(app: tauri::AppHandle, tag: String) -> AppResult<Vec<db::NoteSummary>> {
+    db::with_tag(&app, &tag)
+}
+
+/// Indexed notes that `path` links to.
+#[tauri::command]
+fn note_links(app: tauri::AppHandle, path: String) -> AppResult<Vec<db::NoteSummary>> {
+    db::links_from(&app, Path::new(&path))
 }
 
-fn read_file_capped(path: &Path, max_bytes: usize) -> std::io::Result<Vec<u8>> {
-    use std::io::Read;
-    let mut f = std::fs::File::open(path)?;
-    let mut buf = Vec::with_capacity(max_bytes.min(64 * 1024));
-    let mut chunk = [0u8; 8192];
-    while buf.len() < max_bytes {
-        let n = f.read(&mut chunk)?;
-        if n == 0 {
-            break;
-        }
-        let take = n.min(max_bytes - buf.len());
-        buf.extend_from_slice(&chunk[..take]);
-        if take < n {
-            break;
+/// Indexed notes that link to `path`. Unlike `find_backlinks` this reads
+/// nothing from disk and has no line positions.
+#[tauri::command]
+fn note_backlinks(app: tauri::AppHandle, path: String) -> AppResult<Vec<db::NoteSummary>> {
+    db::backlinks(&app, Path::new(&path))
+}
+
+/// Notes whose title contains `query`, best matches first.
+#[tauri::command]
+fn find_notes_by_title(app: tauri::AppHandle, query: String, limit: Option<usize>) -> AppResult<Vec<db::NoteSummary>> {
+    db::find_by_title(&app, &query, limit.unwrap_or(50))
+}
+
+/// Notes under `root` and the links between them, for the graph view. With
+/// `focus` (a note path) only its neighbourhood up to `hops` links away
+/// (default 2) comes back, which keeps large vaults drawable.
+#[tauri::command]
+async fn workspace_graph(
+    app: tauri::AppHandle,
+    root: String,
+    focus: Option<String>,
+    hops: Option<usize>,
+) -> AppResult<db::NoteGraph> {
+    tokio::task::spawn_blocking(move || {
+        let root_path = Path::new(&root);
+        if !root_path.is_dir() {
+            return Err(AppError::InvalidInput(format!("workspace path is not a directory: {}", root)));
         }
+        db::graph(&app, root_path, focus.as_deref().map(Path::new), hops.unwrap_or(2))
+    })
+    .await?
+}
+
+/// Find-and-replace across the notes under `root`. `replace_id` lets
+/// `cancel_replace` stop the run between files; what was written by then is
+/// what comes back. With `options.dryRun` nothing is written and every
+/// entry has a per-line preview. Files saved from an editor in the meantime
+/// are listed in `failed` instead of being overwritten.
+#[tauri::command]
+async fn replace_in_files(
+    app: tauri::AppHandle,
+    replaces: tauri::State<'_, replace::ReplaceRegistry>,
+    root: String,
+    query: String,
+    replacement: String,
+    options: Option<replace::ReplaceOptions>,
+    replace_id: String,
+) -> AppResult<replace::ReplaceInFiles> {
+    let root_path = PathBuf::from(&root);
+    if !root_path.is_dir() {
+        return Err(AppError::InvalidInput(format!("workspace path is not a directory: {}", root)));
     }
-    Ok(buf)
+    let options = options.unwrap_or_default();
+    let re = replace::build_pattern(&query, options)?;
+    let max_bytes = settings::current(&app).max_open_bytes;
+    let cancel = replaces.0.register(&replace_id);
+    let result = tokio::task::spawn_blocking(move || {
+        let locks = app.state::<files::FileLocks>();
+        replace::replace_in_files(&locks, &root_path, &re, &replacement, options, max_bytes, &cancel)
+    })
+    .await;
+    replaces.0.finish(&replace_id);
+    result?
+}
+
+#[tauri::command]
+fn cancel_replace(replaces: tauri::State<'_, replace::ReplaceRegistry>, replace_id: String) -> bool {
+    replaces.0.cancel(&replace_id)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RelativizeResult {
+    content: String,
+    changed: u32,
+    changes: Vec<links::PlannedChange>,
+}
+
+/// Turn absolute local link and image paths in a document into paths
+/// relative to it, e.g. before committing notes to a shared repository.
+/// With `dry_run` the original content comes back alongside the changes.
+#[tauri::command]
+fn relativize_paths(content: String, doc_path: String, dry_run: Option<bool>) -> RelativizeResult {
+    let doc = Path::new(&doc_path);
+    let (rewritten, changed) = links::relativize(&content, doc);
+    let changes = links::planned_changes(doc, &content, &rewritten);
+    let content = if dry_run.unwrap_or(false) { content } else { rewritten };
+    RelativizeResult { content, changed: changed as u32, changes }
 }
 
 /// Reveal a file or folder in the host OS file manager.
 /// On Windows uses `explorer /select,<path>`; on macOS uses `open -R <path>`;
 /// on Linux falls back to opening the parent folder via xdg-open.
 #[tauri::command]
-fn reveal_in_os(path: String) -> Result<(), String> {
+fn reveal_in_os(path: String) -> AppResult<()> {
     let target = Path::new(&path);
     if !target.exists() {
-        return Err(format!("path does not exist: {}", path));
+        return Err(AppError::NotFound { path });
     }
 
     #[cfg(target_os = "windows")]
@@ -716,7 +2261,7 @@ fn reveal_in_os(path: String) -> Result<(), String> {
         std::process::Command::new("explorer.exe")
             .arg(format!("/select,{}", path))
             .spawn()
-            .map_err(|e| format!("explorer: {}", e))?;
+            .map_err(|e| AppError::Internal(format!("explorer: {}", e)))?;
         return Ok(());
     }
 
@@ -725,7 +2270,7 @@ fn reveal_in_os(path: String) -> Result<(), String> {
         std::process::Command::new("open")
             .args(["-R", &path])
             .spawn()
-            .map_err(|e| format!("open: {}", e))?;
+            .map_err(|e| AppError::Internal(format!("open: {}", e)))?;
         return Ok(());
     }
 
@@ -738,12 +2283,12 @@ fn reveal_in_os(path: String) -> Result<(), String> {
         std::process::Command::new("xdg-open")
             .arg(&parent)
             .spawn()
-            .map_err(|e| format!("xdg-open: {}", e))?;
+            .map_err(|e| AppError::Internal(format!("xdg-open: {}", e)))?;
         return Ok(());
     }
 
     #[allow(unreachable_code)]
-    Err("reveal_in_os: unsupported platform".into())
+    Err(AppError::Internal("reveal_in_os: unsupported platform".into()))
 }
 
 /// List all font family names installed on the system.
@@ -765,11 +2310,68 @@ fn list_system_fonts() -> Vec<String> {
     families.into_iter().collect()
 }
 
+/// Build an editor window, restoring its stored always-on-top and zen state.
+/// The size is `size` when given (and then stored for the label), else the
+/// size the label last closed with, else the default; it never exceeds the
+/// monitor's work area.
+fn build_editor_window(
+    app: &tauri::AppHandle,
+    label: &str,
+    url: String,
+    size: Option<window_state::WindowSize>,
+) -> AppResult<tauri::WebviewWindow> {
+    if let Some(size) = size {
+        window_state::update(app, label, |s| s.size = Some(size))?;
+    }
+    let restored = window_state::get(app, label).unwrap_or_default();
+    let (default_w, default_h) = WINDOW_PRESETS[0].1;
+    let size = restored.size.unwrap_or(window_state::WindowSize { width: default_w, height: default_h });
+
+    let window = portable::with_data_dir(WebviewWindowBuilder::new(
+        app,
+        label,
+        WebviewUrl::App(url.into())
+    ))
+    .title("MerMark Editor")
+    .inner_size(size.width.max(MIN_WINDOW_SIZE.0), size.height.max(MIN_WINDOW_SIZE.1))
+    .resizable(true)
+    .center()
+    .always_on_top(restored.always_on_top)
+    .theme(settings::current(app).theme.window_theme())
+    .build()?;
+    clamp_to_work_area(&window)?;
+
+    // A restored always-on-top window already sits above everything; don't
+    // also yank focus to it.
+    if !restored.always_on_top {
+        window.set_focus()?;
+    }
+    window_state::sync_zoom_with_monitor(&window);
+    if restored.zen {
+        window_state::set_zen(&window, true)?;
+    }
+    Ok(window)
+}
+
 #[tauri::command]
-async fn create_new_window(app: tauri::AppHandle, file_path: Option<String>) -> Result<String, String> {
+async fn create_new_window(
+    app: tauri::AppHandle,
+    file_path: Option<String>,
+    carry_layout_from: Option<String>,
+    width: Option<f64>,
+    height: Option<f64>,
+    preset: Option<String>,
+) -> AppResult<String> {
+    ensure_multi_window(&app)?;
+    let size = requested_window_size(preset.as_deref(), width, height)?;
     let window_id = WINDOW_COUNTER.fetch_add(1, Ordering::SeqCst);
     let window_label = format!("window-{}", window_id);
 
+    // Popping out a window's last tab: the new window inherits its layout.
+    if let Some(source) = &carry_layout_from {
+        window_state::hand_off_layout(&app, source, &window_label)?;
+    }
+
     let url = match &file_path {
         Some(path) => {
             let encoded_path = urlencoding::encode(path);
@@ -778,21 +2380,468 @@ async fn create_new_window(app: tauri::AppHandle, file_path: Option<String>) ->
         None => "index.html".to_string()
     };
 
-    let window = WebviewWindowBuilder::new(
-        &app,
-        &window_label,
-        WebviewUrl::App(url.into())
-    )
-    .title("MerMark Editor")
-    .inner_size(1200.0, 800.0)
-    .resizable(true)
-    .center()
-    .build()
-    .map_err(|e| e.to_string())?;
+    build_editor_window(&app, &window_label, url, size)?;
+    Ok(window_label)
+}
 
-    window.set_focus().map_err(|e| e.to_string())?;
+// ============== Present mode ==============
 
-    Ok(window_label)
+/// Open (or bring back) the read-only mirror of `source_window`. It loads
+/// with `?present=<source>` so the frontend renders the document view only;
+/// content arrives through `push_present_update`.
+#[tauri::command]
+async fn open_present_window(
+    app: tauri::AppHandle,
+    pairs: tauri::State<'_, present::PresentPairs>,
+    source_window: String,
+    file_path: Option<String>,
+) -> AppResult<String> {
+    ensure_multi_window(&app)?;
+    window_by_label(&app, &source_window)?;
+    if let Some(existing) = pairs.present_for(&source_window).and_then(|l| app.get_webview_window(&l)) {
+        bring_to_front(&existing)?;
+        return Ok(existing.label().to_string());
+    }
+    let label = present::label_for(&source_window);
+    let mut url = format!("index.html?present={}", urlencoding::encode(&source_window));
+    if let Some(path) = &file_path {
+        url.push_str(&format!("&file={}", urlencoding::encode(path)));
+    }
+    let title = match file_path.as_deref().and_then(|p| Path::new(p).file_name()) {
+        Some(name) => format!("{} — Present", name.to_string_lossy()),
+        None => "MerMark — Present".to_string(),
+    };
+    pairs.pair(&source_window, &label);
+    let built = portable::with_data_dir(WebviewWindowBuilder::new(&app, &label, WebviewUrl::App(url.into())))
+        .title(title)
+        .inner_size(1280.0, 800.0)
+        .center()
+        .theme(settings::current(&app).theme.window_theme())
+        .build();
+    if let Err(e) = built {
+        pairs.forget(&source_window);
+        return Err(e.into());
+    }
+    Ok(label)
+}
+
+/// Close the mirror of `source_window`; false when it had none.
+#[tauri::command]
+fn close_present_window(
+    app: tauri::AppHandle,
+    pairs: tauri::State<'_, present::PresentPairs>,
+    source_window: String,
+) -> AppResult<bool> {
+    let Some(label) = pairs.forget(&source_window) else { return Ok(false) };
+    if let Some(window) = app.get_webview_window(&label) {
+        window.close()?;
+    }
+    Ok(true)
+}
+
+/// Called by a source window as its document changes. Returns false once
+/// it has no mirror, so it can stop sending.
+#[tauri::command]
+fn push_present_update(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    pairs: tauri::State<'_, present::PresentPairs>,
+    file_path: Option<String>,
+    content: String,
+) -> AppResult<bool> {
+    let update = present::PresentUpdate { file_path, content };
+    let Some(label) = pairs.push(window.label(), update.clone()) else { return Ok(false) };
+    app.emit_to(tauri::EventTarget::webview_window(&label), "present-update", update)?;
+    Ok(true)
+}
+
+/// Latest content for a mirror that just loaded.
+#[tauri::command]
+fn get_present_content(
+    window: tauri::WebviewWindow,
+    pairs: tauri::State<'_, present::PresentPairs>,
+) -> Option<present::PresentUpdate> {
+    pairs.last_for(window.label())
+}
+
+// ============== Diagram Inventory ==============
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceDiagram {
+    file: String,
+    /// 1-based line of the opening fence.
+    line: usize,
+    /// `None` when the first line is not a known Mermaid keyword.
+    diagram_type: Option<&'static str>,
+    /// `None` unless validation was requested.
+    valid: Option<bool>,
+    problem: Option<String>,
+}
+
+/// Every Mermaid block under `root`, for the diagrams overview. With
+/// `validate` each one also gets the cheap structural check; the webview's
+/// Mermaid parser remains the final word.
+#[tauri::command]
+async fn list_workspace_diagrams(
+    app: tauri::AppHandle,
+    root: String,
+    validate: Option<bool>,
+) -> AppResult<Vec<WorkspaceDiagram>> {
+    let settings = settings::current(&app);
+    let (max_bytes, mermaid_version) = (settings.max_open_bytes, settings.mermaid_version);
+    let validate = validate.unwrap_or(false);
+    tokio::task::spawn_blocking(move || {
+        let root_path = Path::new(&root);
+        if !root_path.is_dir() {
+            return Err(AppError::InvalidInput(format!("workspace path is not a directory: {}", root)));
+        }
+        let mut out = Vec::new();
+        for file in links::collect_markdown_files(root_path, max_bytes) {
+            let Ok(text) = files::read_text(&file, Some(max_bytes)) else { continue };
+            // A pin to a version we don't ship fails every block of the file.
+            let version = markdown::mermaid::version_for(&text, &mermaid_version);
+            for block in markdown::mermaid::extract(&text) {
+                let checked = validate.then(|| version.clone().and_then(|_| markdown::mermaid::check(&block)));
+                out.push(WorkspaceDiagram {
+                    file: file.to_string_lossy().into_owned(),
+                    line: block.line,
+                    diagram_type: markdown::mermaid::detect_type(&block.source),
+                    valid: checked.as_ref().map(Result::is_ok),
+                    problem: checked.and_then(Result::err),
+                });
+            }
+        }
+        Ok(out)
+    })
+    .await?
+}
+
+/// Mermaid major versions bundled in the webview, oldest first.
+#[tauri::command]
+fn list_bundled_mermaid_versions() -> Vec<&'static str> {
+    markdown::mermaid::BUNDLED_VERSIONS.to_vec()
+}
+
+/// Pick the bundled Mermaid version used unless a document pins its own.
+/// Returns the version as stored (`v11.2` becomes `11`).
+#[tauri::command]
+fn set_mermaid_version(app: tauri::AppHandle, version: String) -> AppResult<String> {
+    let bundled = markdown::mermaid::bundled_version(&version).ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "Mermaid {} is not bundled; available: {}",
+            version,
+            markdown::mermaid::BUNDLED_VERSIONS.join(", ")
+        ))
+    })?;
+    let state = app.state::<settings::SettingsState>();
+    let mut current = state.0.lock().unwrap();
+    let updated = settings::AppSettings { mermaid_version: bundled.to_string(), ..current.clone() };
+    settings::save(&app, &updated)?;
+    *current = updated;
+    Ok(bundled.to_string())
+}
+
+/// Mermaid version to render and export `content` with: its front-matter
+/// `mermaid_version:` pin, else the setting. A version that isn't bundled is
+/// `InvalidInput` rather than a quiet fallback to the default.
+#[tauri::command]
+fn mermaid_version_for(app: tauri::AppHandle, content: String) -> AppResult<String> {
+    let setting = settings::current(&app).mermaid_version;
+    markdown::mermaid::version_for(&content, &setting)
+        .map(str::to_string)
+        .map_err(AppError::InvalidInput)
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum DiagramFormat {
+    Svg,
+    Png,
+}
+
+impl DiagramFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            DiagramFormat::Svg => "svg",
+            DiagramFormat::Png => "png",
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentDiagram {
+    line: usize,
+    diagram_type: Option<&'static str>,
+    source: String,
+}
+
+/// The Mermaid blocks of one document, in order. The webview renders these
+/// and hands the results to `extract_diagrams_to_images` in the same order.
+#[tauri::command]
+fn document_diagrams(content: String) -> Vec<DocumentDiagram> {
+    markdown::mermaid::extract(&content)
+        .into_iter()
+        .map(|block| DocumentDiagram {
+            line: block.line,
+            diagram_type: markdown::mermaid::detect_type(&block.source),
+            source: block.source,
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtractedDiagrams {
+    /// Files written, one per rendered block.
+    images: Vec<String>,
+    /// The document with rendered blocks swapped for image references when
+    /// `replace` was set, otherwise the input unchanged.
+    content: String,
+}
+
+/// Write each rendered diagram to `assets/<stem>-diagram-N.<format>` next to
+/// the document, `<stem>` being the document's file stem. `rendered` follows
+/// `document_diagrams`; a `None` entry (the diagram failed to render) keeps
+/// its code fence. Existing images are never overwritten: N skips names
+/// already taken, so re-running continues where earlier runs stopped.
+#[tauri::command]
+async fn extract_diagrams_to_images(
+    locks: tauri::State<'_, files::FileLocks>,
+    content: String,
+    doc_path: String,
+    format: DiagramFormat,
+    replace: bool,
+    rendered: Vec<Option<Vec<u8>>>,
+) -> AppResult<ExtractedDiagrams> {
+    let doc = Path::new(&doc_path);
+    let doc_dir = doc
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .ok_or_else(|| AppError::InvalidInput(format!("document has no folder: {}", doc_path)))?;
+    let stem = doc.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let blocks = markdown::mermaid::extract(&content);
+    if rendered.len() != blocks.len() {
+        return Err(AppError::InvalidInput(format!(
+            "{} rendered diagrams for {} Mermaid blocks",
+            rendered.len(),
+            blocks.len()
+        )));
+    }
+    let mut images = Vec::new();
+    let mut replaced = Vec::new();
+    let mut n = 1;
+    for (block, bytes) in blocks.iter().zip(&rendered) {
+        let Some(bytes) = bytes else { continue };
+        loop {
+            let target = format!("assets/{}-diagram-{}.{}", stem, n, format.extension());
+            n += 1;
+            let path = doc_dir.join(&target);
+            if let files::BinaryWrite::Written { path } = files::write_binary_atomic(&locks, &path, bytes, false).await? {
+                images.push(path);
+                replaced.push((block, target.replace(' ', "%20")));
+                break;
+            }
+        }
+    }
+    let content = if replace {
+        markdown::mermaid::replace_with_images(&content, &replaced)
+    } else {
+        content
+    };
+    Ok(ExtractedDiagrams { images, content })
+}
+
+// ============== HTML Bundle ==============
+
+fn read_bundle_docs(paths: &[String], max_bytes: u64) -> AppResult<Vec<(PathBuf, String)>> {
+    paths
+        .iter()
+        .map(|p| Ok((PathBuf::from(p), files::read_text(Path::new(p), Some(max_bytes))?)))
+        .collect()
+}
+
+/// First step of `export_bundle`: output names, titles and each note's
+/// Markdown with links rewritten for the bundle. The frontend renders the
+/// planned Markdown and passes the HTML to `export_bundle`.
+#[tauri::command]
+async fn plan_bundle(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    options: Option<bundle::BundleOptions>,
+) -> AppResult<bundle::BundlePlan> {
+    let options = options.unwrap_or_default();
+    let max_bytes = settings::current(&app).max_open_bytes;
+    tokio::task::spawn_blocking(move || Ok(bundle::plan(&read_bundle_docs(&paths, max_bytes)?, options.copy_assets)))
+        .await?
+}
+
+/// Write a read-only mini-site of the selected notes to `output_dir`: one
+/// page each with a shared sidebar, links between them pointing at the
+/// exported siblings and their images copied to `assets/`. `html` holds the
+/// rendered pages in `plan_bundle` order. Progress arrives as
+/// `bundle-progress` events on the calling window.
+#[tauri::command]
+async fn export_bundle(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    paths: Vec<String>,
+    output_dir: String,
+    options: Option<bundle::BundleOptions>,
+    html: Vec<String>,
+) -> AppResult<bundle::BundleResult> {
+    let options = options.unwrap_or_default();
+    let max_bytes = settings::current(&app).max_open_bytes;
+    tokio::task::spawn_blocking(move || {
+        let plan = bundle::plan(&read_bundle_docs(&paths, max_bytes)?, options.copy_assets);
+        let output = PathBuf::from(&output_dir);
+        let title = options.title.clone().unwrap_or_else(|| {
+            output.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| "Notes".to_string())
+        });
+        bundle::write(&plan, &html, &output, &title, |progress| {
+            let _ = window.emit("bundle-progress", progress);
+        })
+    })
+    .await?
+}
+
+// ============== Gist Sharing ==============
+
+#[tauri::command]
+fn build_gist_payload(files: Vec<gist::GistFile>, description: Option<String>, public: bool) -> AppResult<String> {
+    gist::build_payload(&files, description.as_deref().unwrap_or(""), public)
+}
+
+/// Post a `build_gist_payload` body and return the gist URL. A `token`
+/// replaces the one kept in memory from an earlier call; without one the
+/// remembered token is used.
+#[tauri::command]
+async fn create_gist(
+    state: tauri::State<'_, gist::GistToken>,
+    payload: String,
+    token: Option<String>,
+) -> AppResult<String> {
+    let token = match token.filter(|t| !t.trim().is_empty()) {
+        Some(t) => t.trim().to_string(),
+        None => state
+            .0
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| AppError::InvalidInput("a GitHub token is required to create a gist".to_string()))?,
+    };
+    let url = gist::create(&payload, &token).await?;
+    *state.0.lock().unwrap() = Some(token);
+    Ok(url)
+}
+
+// ============== Named Sessions ==============
+
+/// Save every editor window and its open files as `sessions/<name>.json`.
+#[tauri::command]
+fn save_session_as(
+    app: tauri::AppHandle,
+    registry: tauri::State<'_, OpenFilesRegistry>,
+    tabs: tauri::State<'_, sessions::WindowTabs>,
+    name: String,
+) -> AppResult<sessions::SessionSummary> {
+    let open_files = registry.0.lock().unwrap().clone();
+    let tabs = tabs.0.lock().unwrap().clone();
+    sessions::save_as(&app, &name, &open_files, &tabs)
+}
+
+/// The calling window's file tabs in tab-bar order and the active one, for
+/// `save_session_as`. Sent again whenever they change.
+#[tauri::command]
+fn report_window_tabs(
+    window: tauri::WebviewWindow,
+    tabs: tauri::State<'_, sessions::WindowTabs>,
+    files: Vec<String>,
+    active: Option<usize>,
+) {
+    tabs.0.lock().unwrap().insert(window.label().to_string(), sessions::TabOrder { files, active });
+}
+
+#[tauri::command]
+fn list_sessions(app: tauri::AppHandle) -> AppResult<Vec<sessions::SessionSummary>> {
+    sessions::list(&app)
+}
+
+#[tauri::command]
+fn delete_session(app: tauri::AppHandle, name: String) -> AppResult<()> {
+    sessions::delete(&app, &name)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LoadedSession {
+    /// Labels of the windows that were opened.
+    windows: Vec<String>,
+    /// Files in the snapshot that no longer exist.
+    skipped: Vec<String>,
+}
+
+/// Open the windows of a saved session, each restoring its window state
+/// and then its tabs in their saved order. With `replace` the current
+/// editor windows are asked to close (unsaved changes still prompt, and a
+/// window kept open keeps its files); otherwise the session is added
+/// alongside them.
+#[tauri::command]
+async fn load_session(
+    app: tauri::AppHandle,
+    pending: tauri::State<'_, sessions::PendingTabs>,
+    name: String,
+    replace: bool,
+) -> AppResult<LoadedSession> {
+    ensure_multi_window(&app)?;
+    let session = sessions::read(&app, &name)?;
+    let current: Vec<String> = app.webview_windows().into_keys().filter(|l| !is_helper_window(l)).collect();
+
+    let mut loaded = LoadedSession { windows: Vec::new(), skipped: Vec::new() };
+    for window in &session.windows {
+        let (tabs, missing) = sessions::existing_files(window);
+        loaded.skipped.extend(missing);
+        if tabs.files.is_empty() {
+            continue;
+        }
+        let label = format!("window-{}", WINDOW_COUNTER.fetch_add(1, Ordering::SeqCst));
+        let state = window.state.clone();
+        window_state::update(&app, &label, |s| *s = window_state::WindowState { layout: state.layout.sanitized(), ..state })?;
+        pending.0.lock().unwrap().insert(label.clone(), tabs);
+        build_editor_window(&app, &label, "index.html".to_string(), None)?;
+        loaded.windows.push(label);
+    }
+
+    if replace && !loaded.windows.is_empty() {
+        for label in &current {
+            if let Some(window) = app.get_webview_window(label) {
+                window.close()?;
+            }
+        }
+    }
+    Ok(loaded)
+}
+
+/// Tabs `load_session` queued for the calling window; empty otherwise.
+#[tauri::command]
+fn take_pending_tabs(window: tauri::WebviewWindow, pending: tauri::State<'_, sessions::PendingTabs>) -> sessions::TabOrder {
+    pending.0.lock().unwrap().remove(window.label()).unwrap_or_default()
+}
+
+// ============== Pending Opens ==============
+
+/// Remember a file to reopen after the next start. The updater calls this
+/// for every open document before it relaunches the app.
+#[tauri::command]
+fn queue_pending_open(app: tauri::AppHandle, path: String) -> AppResult<()> {
+    pending_opens::queue(&app, &path)
+}
+
+/// Files queued before the last restart, cleared as they are returned.
+#[tauri::command]
+fn take_pending_opens(app: tauri::AppHandle) -> AppResult<Vec<String>> {
+    pending_opens::take(&app)
 }
 
 #[cfg(any(test, target_os = "linux"))]
@@ -836,13 +2885,30 @@ fn apply_linux_webkit_overrides() {
     }
 }
 
+/// Log each command name at debug level before it runs (arguments are left
+/// out; they can hold document content).
+fn logged<R: tauri::Runtime>(
+    handler: impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        tracing::debug!(command = invoke.message.command(), "invoke");
+        handler(invoke)
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     #[cfg(target_os = "linux")]
     apply_linux_webkit_overrides();
 
     tauri::Builder::default()
-        .register_uri_scheme_protocol(PRINT_SCHEME, |ctx, _request| {
+        .register_uri_scheme_protocol(PRINT_SCHEME, |ctx, request| {
+            if request.uri().path() == "/selftest" {
+                return tauri::http::Response::builder()
+                    .header("Content-Type", "text/html; charset=utf-8")
+                    .body(selftest::SAMPLE_HTML.as_bytes().to_vec())
+                    .unwrap();
+            }
             let html = ctx
                 .app_handle()
                 .state::<PrintHtmlState>()
@@ -875,38 +2941,174 @@ pub fn run() {
                 }
                 let _ = window.set_focus();
 
-                if args.len() > 1 {
-                    let file_path = &args[1];
-                    if is_supported_markdown_path(file_path) {
-                        let _ = window.emit("open-file", file_path.clone());
-                    }
+                let (files, folders) = classify_launch_args(args.iter().skip(1));
+                for folder in folders {
+                    let _ = window.emit("open-workspace", folder);
+                }
+                for file_path in files {
+                    let _ = window.emit("open-file", file_path);
                 }
             }
         }))
         .manage(OpenFileState(Mutex::new(None)))
+        .manage(config_recovery::Recoveries::default())
         .manage(OpenFilesRegistry(Mutex::new(HashMap::new())))
         .manage(PrintHtmlState(Mutex::new(None)))
+        .manage(SystemThemeState(Mutex::new("light".to_string())))
+        .manage(files::FileLocks::default())
+        .manage(gist::GistToken::default())
+        .manage(dialogs::LastDirectory::default())
+        .manage(scan::ScanRegistry::default())
+        .manage(sessions::PendingTabs::default())
+        .manage(sessions::WindowTabs::default())
+        .manage(watch::WatchSuspensions::default())
+        .manage(background::Background::default())
+        .manage(metrics::Metrics::default())
+        .manage(replace::ReplaceRegistry::default())
+        .manage(present::PresentPairs::default())
+        .manage(user_config::UserConfig::default())
+        .manage(db::NotesDb::default())
+        .manage(on_save::OnSaveRuns::default())
+        .manage(window_state::ZenWindows::default())
+        .manage(window_state::LayoutHandoffs::default())
+        .manage(window_state::ZoomMonitors::default())
         .manage(ai::process::ChildRegistry::new())
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(logged(tauri::generate_handler![
             get_open_file_path,
             create_new_window,
+            list_window_presets,
+            open_present_window,
+            close_present_window,
+            push_present_update,
+            get_present_content,
+            save_session_as,
+            report_window_tabs,
+            list_sessions,
+            load_session,
+            delete_session,
+            take_pending_tabs,
+            queue_pending_open,
+            take_pending_opens,
             get_all_windows,
             get_current_window_label,
+            get_runtime_mode,
+            get_window_state,
+            set_always_on_top,
+            set_window_zoom,
+            get_window_zoom,
+            set_fullscreen,
+            list_monitors,
+            move_window_to_monitor,
+            toggle_zen,
+            get_layout,
+            save_layout,
+            reset_layout,
+            get_settings,
+            validate_config_files,
+            is_portable,
+            register_file_associations,
+            is_default_handler,
+            is_background_paused,
+            record_command_timings,
+            get_command_metrics,
+            clear_metrics,
+            update_settings,
+            get_system_theme,
+            set_log_level,
+            get_log_path,
+            open_log_file,
             print_document,
+            run_self_test,
+            export_range_markdown,
+            split_frontmatter,
+            generate_toc,
+            check_anchor_links,
+            export_outline,
+            export_patch,
+            plan_bundle,
+            list_workspace_diagrams,
+            document_diagrams,
+            list_bundled_mermaid_versions,
+            set_mermaid_version,
+            mermaid_version_for,
+            extract_diagrams_to_images,
+            export_bundle,
+            build_gist_payload,
+            create_gist,
+            document_stats,
+            detect_language,
+            section_stats,
+            format_markdown,
+            format_tables,
+            convert_links,
+            find_invisible_chars,
+            strip_invisible_chars,
+            csv_to_markdown_table,
+            import_csv_file,
+            split_by_heading,
+            join_files,
+            resolve_includes,
             transfer_tab_to_window,
+            complete_tab_transfer,
             register_open_file,
             unregister_open_file,
             unregister_window_files,
             check_file_open,
             focus_window_with_file,
+            read_text_file,
+            open_file_dialog,
+            save_file_dialog,
+            content_hash,
+            save_file_atomic,
+            save_file_with_backup,
+            run_on_save,
+            get_on_save_command,
+            set_on_save_command,
+            write_binary_atomic,
+            import_image,
+            write_draft,
+            read_draft,
+            discard_draft,
+            is_own_write,
+            reload_file,
+            suspend_watch,
+            resume_watch,
+            open_readonly,
+            save_position,
+            get_position,
             list_system_fonts,
             read_workspace_tree,
+            scan_workspace,
+            cancel_scan,
             create_md_file,
             create_folder,
             rename_path,
             delete_path,
             reveal_in_os,
+            open_path_external,
+            add_recent_folder,
+            get_recent_folders,
+            clear_recent_folders,
+            get_user_config,
+            list_templates,
             search_workspace_content,
+            open_at_match,
+            find_backlinks,
+            update_backlinks,
+            rename_with_refs,
+            rebuild_index,
+            index_file,
+            remove_indexed_file,
+            get_document_id,
+            list_note_tags,
+            notes_with_tag,
+            note_links,
+            note_backlinks,
+            find_notes_by_title,
+            workspace_graph,
+            replace_in_files,
+            cancel_replace,
+            relativize_paths,
             ai_health_check,
             ai_ollama_models,
             ai_openai_models,
@@ -932,7 +3134,7 @@ pub fn run() {
             ai_send,
             ai_cancel,
             ai_image_save
-        ])
+        ]))
         .setup(|app| {
             // Check for CLI arguments (file association on first launch)
             let args: Vec<String> = std::env::args().collect();
@@ -945,6 +3147,52 @@ pub fn run() {
                 }
             }
 
+            // The main window is declared with `create: false` so it can
+            // pick up the portable webview profile before it exists.
+            if let Some(config) = app.config().app.windows.iter().find(|w| w.label == "main") {
+                portable::with_data_dir(WebviewWindowBuilder::from_config(app.handle(), config)?).build()?;
+            }
+
+            let app_settings = settings::load(app.handle());
+            if let Some(window) = app.get_webview_window("main") {
+                // Read the OS appearance before any theme override is applied.
+                // Platforms without live `ThemeChanged` keep this startup value.
+                if let Ok(theme) = window.theme() {
+                    *app.state::<SystemThemeState>().0.lock().unwrap() = settings::theme_name(theme).to_string();
+                }
+                let _ = window.set_theme(app_settings.theme.window_theme());
+                window_state::restore(&window);
+                let _ = clamp_to_work_area(&window);
+            }
+            match logging::init(app.handle(), &app_settings) {
+                Ok(log) => {
+                    app.manage(log);
+                }
+                Err(e) => eprintln!("[logging] disabled: {}", e),
+            }
+            app.manage(settings::SettingsState(Mutex::new(app_settings)));
+            tracing::info!(version = %app.package_info().version, "started");
+
+            if let Err(e) = user_config::start(app.handle()) {
+                tracing::warn!("user config watcher not started: {}", e);
+            }
+
+            // Stat every remembered document off the main thread.
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Err(e) = positions::prune(&handle) {
+                    tracing::warn!("positions prune failed: {}", e);
+                }
+            });
+
+            // Catch the notes index up with edits made while closed.
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Err(e) = db::resume(&handle) {
+                    tracing::warn!("notes index not resumed: {}", e);
+                }
+            });
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
@@ -997,15 +3245,73 @@ pub fn run() {
                         }
                     }
                 }
+                RunEvent::WindowEvent { event: WindowEvent::ThemeChanged(theme), .. } => {
+                    // Only trust the event while windows follow the OS; with a
+                    // forced light/dark theme it just echoes our own override.
+                    if settings::current(app).theme != settings::ThemePreference::Auto {
+                        return;
+                    }
+                    let name = settings::theme_name(theme);
+                    let changed = {
+                        let state = app.state::<SystemThemeState>();
+                        let mut current = state.0.lock().unwrap();
+                        let changed = *current != name;
+                        *current = name.to_string();
+                        changed
+                    };
+                    // Every window reports the change; broadcast it once.
+                    if changed {
+                        let _ = app.emit("system-theme-changed", name);
+                    }
+                }
+                RunEvent::WindowEvent { label, event: WindowEvent::Focused(focused), .. } => {
+                    on_focus_changed(app, &label, focused);
+                }
+                RunEvent::WindowEvent { label, event: WindowEvent::Resized(_), .. } => {
+                    if let Some(window) = app.get_webview_window(&label) {
+                        window_state::sync_zen_with_fullscreen(&window);
+                    }
+                }
+                RunEvent::WindowEvent { label, event: WindowEvent::Moved(_), .. } => {
+                    if let Some(window) = app.get_webview_window(&label) {
+                        window_state::sync_zoom_with_monitor(&window);
+                    }
+                }
+                RunEvent::WindowEvent {
+                    label,
+                    event: WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, position }),
+                    ..
+                } => {
+                    if is_helper_window(&label) {
+                        return;
+                    }
+                    let dropped = classify_drop(&paths, position.x, position.y);
+                    let _ = app.emit_to(tauri::EventTarget::webview_window(&label), "files-dropped", dropped);
+                }
                 RunEvent::WindowEvent { label, event: WindowEvent::CloseRequested { api, .. }, .. } => {
-                    // The print helper window is auxiliary — never let it gate app lifecycle.
-                    if label == PRINT_WINDOW_LABEL {
+                    if let Some(mirror) = app.state::<present::PresentPairs>().forget(&label) {
+                        if let Some(window) = app.get_webview_window(&mirror) {
+                            let _ = window.close();
+                        }
+                    }
+                    // Helper windows are auxiliary — never let them gate app lifecycle.
+                    if is_helper_window(&label) {
+                        return;
+                    }
+                    if let Some(window) = app.get_webview_window(&label) {
+                        window_state::remember_size(&window);
+                    }
+                    window_state::on_close(app, &label);
+                    app.state::<watch::WatchSuspensions>().forget(&label);
+                    app.state::<background::Background>().forget(&label);
+                    // Single-window mode always closes as the last window.
+                    if settings::current(app).single_window_mode {
                         return;
                     }
                     let editor_windows = app
                         .webview_windows()
                         .keys()
-                        .filter(|l| *l != PRINT_WINDOW_LABEL)
+                        .filter(|l| !is_helper_window(l))
                         .count();
                     if editor_windows <= 1 {
                         return;
@@ -1025,6 +3331,23 @@ mod tests {
     use super::*;
     use std::ffi::OsStr;
 
+    #[test]
+    fn windows_are_centered_and_shrunk_to_the_work_area() {
+        assert_eq!(fit_in_area((1920, 0, 1920, 1040), (1200, 800)), ((2280, 120), (1200, 800)));
+        assert_eq!(fit_in_area((-1280, 0, 1280, 984), (1600, 900)), ((-1280, 42), (1280, 900)));
+    }
+
+    #[test]
+    fn window_size_comes_from_preset_or_explicit_dimensions() {
+        let size = |p, w, h| requested_window_size(p, w, h).unwrap().map(|s| (s.width, s.height));
+        assert_eq!(size(None, None, None), None);
+        assert_eq!(size(Some("slides-16-9"), None, None), Some((1280.0, 720.0)));
+        assert_eq!(size(Some("slides-16-9"), None, Some(900.0)), Some((1280.0, 900.0)));
+        assert_eq!(size(None, Some(10.0), None), Some((320.0, 800.0)));
+        assert!(requested_window_size(Some("poster"), None, None).is_err());
+        assert!(requested_window_size(None, Some(f64::NAN), None).is_err());
+    }
+
     #[test]
     fn webkit_override_applies_when_unset_or_blank() {
         assert!(should_apply_webkit_override(None));
@@ -1049,4 +3372,59 @@ mod tests {
         // Symbol must build on every platform so the Linux applier is type-checked in CI.
         let _f: fn() = apply_linux_webkit_overrides;
     }
+
+    #[test]
+    fn launch_args_split_into_files_and_folders() {
+        let dir = std::env::temp_dir().join(format!("mermark-args-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let note = dir.join("note.md");
+        let image = dir.join("pic.png");
+        std::fs::write(&note, "# hi").unwrap();
+        std::fs::write(&image, "png").unwrap();
+        let args: Vec<String> = [
+            dir.to_string_lossy().into_owned(),
+            note.to_string_lossy().into_owned(),
+            image.to_string_lossy().into_owned(),
+            dir.join("missing.md").to_string_lossy().into_owned(),
+            "--flag".to_string(),
+        ]
+        .into();
+        let (files, folders) = classify_launch_args(&args);
+        assert_eq!(files, [note.to_string_lossy().into_owned()]);
+        assert_eq!(folders, [dir.to_string_lossy().into_owned()]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reveal_keeps_the_range_only_while_the_match_is_there() {
+        let hit = ContentSearchHit {
+            path: "/n/a.md".into(),
+            line: 2,
+            column: 3,
+            start: 9,
+            end: 14,
+            matched: "Mango".into(),
+            snippet: "a Mango".into(),
+        };
+        let reveal = RevealMatch::for_hit(&hit, "intro\r\na mango\r\n");
+        assert_eq!(reveal, RevealMatch { line: 2, column: 3, start: Some(9), end: Some(14) });
+        let reveal = RevealMatch::for_hit(&hit, "edited\r\na mango\r\n");
+        assert_eq!(reveal, RevealMatch { line: 2, column: 1, start: None, end: None });
+        assert_eq!(RevealMatch::for_hit(&hit, "short").start, None);
+    }
+
+    #[test]
+    fn drops_are_classified_by_kind() {
+        let dir = std::env::temp_dir().join(format!("mermark-drop-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = [dir.clone(), dir.join("a.MD"), dir.join("shot.PNG"), dir.join("data.csv")];
+        let dropped = classify_drop(&paths, 10.0, 20.0);
+        let name = |i: usize| paths[i].to_string_lossy().into_owned();
+        assert_eq!(dropped.folders, [name(0)]);
+        assert_eq!(dropped.markdown, [name(1)]);
+        assert_eq!(dropped.images, [name(2)]);
+        assert_eq!(dropped.other, [name(3)]);
+        assert_eq!((dropped.x, dropped.y), (10.0, 20.0));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,920 @@
+//! Note-to-note link extraction (`[[wikilinks]]` and relative Markdown links),
+//! backlink lookup across a workspace and reference rewriting after a rename.
+
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::files::FileLocks;
+use crate::{is_workspace_hidden, is_workspace_markdown};
+
+/// Same file ceiling as the content search so a backlink lookup on a huge
+/// vault stays bounded.
+const MAX_FILES: usize = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Wikilink,
+    Markdown,
+}
+
+/// A single link found on a line. `start..end` is the byte range of the
+/// target portion only (no alias, heading or title) so it can be rewritten
+/// in place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkRef {
+    pub kind: LinkKind,
+    pub target: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklinkHit {
+    /// File that contains the reference.
+    pub path: String,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column (in chars) where the link target starts.
+    pub column: usize,
+    pub kind: LinkKind,
+    /// The referring line, trimmed and capped to 240 chars for display.
+    pub context: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklinkUpdate {
+    pub files_changed: usize,
+    pub links_updated: usize,
+    /// Every rewritten line; on a dry run nothing was written yet.
+    pub changes: Vec<PlannedChange>,
+}
+
+/// One line a batch edit rewrites, for review before it is applied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedChange {
+    pub file: String,
+    /// 1-based line number.
+    pub line: usize,
+    pub old: String,
+    pub new: String,
+}
+
+/// Lines that differ between `old` and `new`. The rewrites here never add or
+/// remove lines, so lines are compared pairwise.
+pub fn planned_changes(file: &Path, old: &str, new: &str) -> Vec<PlannedChange> {
+    old.lines()
+        .zip(new.lines())
+        .enumerate()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(i, (a, b))| PlannedChange {
+            file: file.to_string_lossy().into_owned(),
+            line: i + 1,
+            old: a.to_string(),
+            new: b.to_string(),
+        })
+        .collect()
+}
+
+/// Blank out the contents of inline code spans so links inside them are not
+/// picked up. Byte offsets are preserved.
+pub fn mask_code_spans(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_code = false;
+    for ch in line.chars() {
+        if ch == '`' {
+            in_code = !in_code;
+            out.push(ch);
+        } else if in_code {
+            for _ in 0..ch.len_utf8() {
+                out.push(' ');
+            }
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+fn has_scheme(target: &str) -> bool {
+    if target.starts_with("mailto:") || target.contains("://") {
+        return true;
+    }
+    // `C:\foo.md` / `C:/foo.md` is a Windows path, not a scheme.
+    match target.find(':') {
+        Some(idx) if idx > 1 => target[..idx].chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.'),
+        _ => false,
+    }
+}
+
+/// Extract wikilinks and relative Markdown links from a single line. Remote
+/// URLs and pure `#anchor` links are skipped.
+pub fn extract_links(line: &str) -> Vec<LinkRef> {
+    let masked = mask_code_spans(line);
+    let bytes = masked.as_bytes();
+    let mut out = Vec::new();
+
+    let mut i = 0;
+    while let Some(rel) = masked[i..].find("[[") {
+        let open = i + rel + 2;
+        let Some(close_rel) = masked[open..].find("]]") else { break };
+        let close = open + close_rel;
+        let inner = &masked[open..close];
+        let name_len = inner.find(['|', '#']).unwrap_or(inner.len());
+        let raw = &inner[..name_len];
+        let lead = raw.len() - raw.trim_start().len();
+        let name = raw.trim();
+        if !name.is_empty() {
+            out.push(LinkRef {
+                kind: LinkKind::Wikilink,
+                target: line[open + lead..open + lead + name.len()].to_string(),
+                start: open + lead,
+                end: open + lead + name.len(),
+            });
+        }
+        i = close + 2;
+    }
+
+    let mut i = 0;
+    while let Some(rel) = masked[i..].find("](") {
+        let open = i + rel + 2;
+        // Require a `[` earlier on the line that is not part of a wikilink.
+        if !masked[..i + rel].contains('[') {
+            i = open;
+            continue;
+        }
+        let mut depth = 0usize;
+        let mut close = None;
+        for (off, b) in bytes[open..].iter().enumerate() {
+            match b {
+                b'(' => depth += 1,
+                b')' if depth == 0 => {
+                    close = Some(open + off);
+                    break;
+                }
+                b')' => depth -= 1,
+                _ => {}
+            }
+        }
+        let Some(close) = close else { break };
+        let dest = &masked[open..close];
+        let (start, dest) = if let Some(stripped) = dest.trim_start().strip_prefix('<') {
+            let lead = dest.len() - dest.trim_start().len() + 1;
+            let end = stripped.find('>').unwrap_or(stripped.len());
+            (open + lead, &stripped[..end])
+        } else {
+            let lead = dest.len() - dest.trim_start().len();
+            let trimmed = dest.trim_start();
+            let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+            (open + lead, &trimmed[..end])
+        };
+        let path_len = dest.find('#').unwrap_or(dest.len());
+        let path = &dest[..path_len];
+        if !path.is_empty() && !has_scheme(path) {
+            out.push(LinkRef {
+                kind: LinkKind::Markdown,
+                target: line[start..start + path.len()].to_string(),
+                start,
+                end: start + path.len(),
+            });
+        }
+        i = close + 1;
+    }
+
+    out.sort_by_key(|l| l.start);
+    out
+}
+
+/// Lexically normalize `.` and `..` components without touching the disk,
+/// so links to files that were just renamed away still resolve.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Relative path from directory `from_dir` to `to`, always with `/`
+/// separators so it can be written straight into Markdown.
+pub fn relative_path(from_dir: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to_comps: Vec<Component> = to.components().collect();
+    let common = from
+        .iter()
+        .zip(to_comps.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut parts: Vec<String> = Vec::new();
+    for _ in common..from.len() {
+        parts.push("..".to_string());
+    }
+    for comp in &to_comps[common..] {
+        parts.push(comp.as_os_str().to_string_lossy().into_owned());
+    }
+    parts.join("/")
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    if cfg!(windows) {
+        a.to_string_lossy().eq_ignore_ascii_case(&b.to_string_lossy())
+    } else {
+        a == b
+    }
+}
+
+fn strip_markdown_ext(name: &str) -> &str {
+    let lower = name.to_ascii_lowercase();
+    for ext in [".markdown", ".mdx", ".md"] {
+        if lower.ends_with(ext) {
+            return &name[..name.len() - ext.len()];
+        }
+    }
+    name
+}
+
+/// Resolve a Markdown link destination relative to the referring file.
+pub fn resolve_markdown_target(from_file: &Path, target: &str) -> Option<PathBuf> {
+    if target.is_empty() || has_scheme(target) {
+        return None;
+    }
+    let decoded = urlencoding::decode(target)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| target.to_string());
+    let candidate = Path::new(&decoded);
+    let joined = if candidate.is_absolute() {
+        candidate.to_path_buf()
+    } else {
+        from_file.parent()?.join(candidate)
+    };
+    Some(normalize_path(&joined))
+}
+
+/// The lowercased file stem a wikilink name refers to: the part
+/// `wikilink_matches` compares with a target's stem.
+pub fn wikilink_stem(name: &str) -> Option<String> {
+    strip_markdown_ext(name.trim())
+        .split(['/', '\\'])
+        .rfind(|s| !s.is_empty())
+        .map(|s| s.to_lowercase())
+}
+
+/// Whether a wikilink name (`Note`, `folder/Note`, `Note.md`) refers to
+/// `target`. Matching is by file stem, case-insensitive; a folder prefix in
+/// the link must also match the trailing folders of the target.
+pub fn wikilink_matches(name: &str, target: &Path) -> bool {
+    let wanted: Vec<String> = strip_markdown_ext(name.trim())
+        .split(['/', '\\'])
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_lowercase())
+        .collect();
+    if wanted.is_empty() {
+        return false;
+    }
+    let stem = target
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mut actual: Vec<String> = target
+        .parent()
+        .map(|p| {
+            p.components()
+                .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    actual.push(stem);
+    actual.len() >= wanted.len() && actual[actual.len() - wanted.len()..] == wanted[..]
+}
+
+fn link_points_at(link: &LinkRef, from_file: &Path, target: &Path) -> bool {
+    match link.kind {
+        LinkKind::Wikilink => wikilink_matches(&link.target, target),
+        LinkKind::Markdown => resolve_markdown_target(from_file, &link.target)
+            .map(|resolved| same_path(&resolved, target))
+            .unwrap_or(false),
+    }
+}
+
+/// Iterate the lines of a document that are outside fenced code blocks,
+/// yielding `(line_index, line)`.
+pub fn prose_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut fence: Option<&str> = None;
+    text.lines().enumerate().filter(move |(_, line)| {
+        let trimmed = line.trim_start();
+        let marker = if trimmed.starts_with("```") {
+            Some("```")
+        } else if trimmed.starts_with("~~~") {
+            Some("~~~")
+        } else {
+            None
+        };
+        match (fence, marker) {
+            (None, Some(m)) => {
+                fence = Some(m);
+                false
+            }
+            (Some(open), Some(m)) if open == m => {
+                fence = None;
+                false
+            }
+            (Some(_), _) => false,
+            (None, None) => true,
+        }
+    })
+}
+
+/// Every Markdown file under `root`, skipping hidden folders,
+/// `node_modules` and files over `max_bytes`, capped at `MAX_FILES`.
+pub fn collect_markdown_files(root: &Path, max_bytes: u64) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(rd) => rd,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_workspace_hidden(&name) {
+                continue;
+            }
+            let file_type = match entry.file_type() {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+            if file_type.is_dir() {
+                stack.push(entry.path());
+            } else if file_type.is_file() && is_workspace_markdown(&name) {
+                let too_big = entry.metadata().map(|m| m.len() > max_bytes).unwrap_or(true);
+                if too_big {
+                    continue;
+                }
+                files.push(entry.path());
+                if files.len() >= MAX_FILES {
+                    return files;
+                }
+            }
+        }
+    }
+    files
+}
+
+/// The whole note, or `None` when it is too big, binary or unreadable.
+/// Never a prefix: rewrites save what was read.
+fn read_markdown(path: &Path, max_bytes: u64) -> Option<String> {
+    crate::files::read_text(path, Some(max_bytes)).ok()
+}
+
+/// Save `rewritten`, made from `original`, over `file` under the file's
+/// write lock so it can't interleave with an editor save. A file that no
+/// longer holds `original` is left alone with a `Conflict`.
+pub(crate) fn write_rewrite(locks: &FileLocks, file: &Path, original: &str, rewritten: &str) -> AppResult<()> {
+    let _guard = locks.acquire_blocking(file)?;
+    let current = crate::files::read_text(file, None)?;
+    if current != original {
+        return Err(AppError::Conflict {
+            path: Some(file.to_string_lossy().into_owned()),
+            message: format!("{} changed on disk while it was being rewritten", file.display()),
+        });
+    }
+    crate::files::write_atomic(file, rewritten.as_bytes())?;
+    locks.record_self_write(file);
+    Ok(())
+}
+
+fn context_of(line: &str) -> String {
+    let trimmed = line.trim();
+    if trimmed.chars().count() > 240 {
+        format!("{}…", trimmed.chars().take(240).collect::<String>())
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Backlinks to `target` found in a single document.
+pub fn backlinks_in(text: &str, from_file: &Path, target: &Path) -> Vec<BacklinkHit> {
+    let mut hits = Vec::new();
+    for (idx, line) in prose_lines(text) {
+        for link in extract_links(line) {
+            if link_points_at(&link, from_file, target) {
+                hits.push(BacklinkHit {
+                    path: from_file.to_string_lossy().into_owned(),
+                    line: idx + 1,
+                    column: line[..link.start].chars().count() + 1,
+                    kind: link.kind,
+                    context: context_of(line),
+                });
+            }
+        }
+    }
+    hits
+}
+
+pub fn find_backlinks(target: &Path, root: &Path, max_bytes: u64) -> Vec<BacklinkHit> {
+    let target = normalize_path(target);
+    let mut hits = Vec::new();
+    for file in collect_markdown_files(root, max_bytes) {
+        if same_path(&normalize_path(&file), &target) {
+            continue;
+        }
+        if let Some(text) = read_markdown(&file, max_bytes) {
+            hits.extend(backlinks_in(&text, &file, &target));
+        }
+    }
+    hits
+}
+
+fn wikilink_replacement(original: &str, new_path: &Path, root: &Path) -> String {
+    let stem = new_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !original.contains('/') && !original.contains('\\') {
+        return stem;
+    }
+    // Keep the folder-qualified style when the original link used it.
+    let rel = match new_path.strip_prefix(root) {
+        Ok(rel) => rel.to_path_buf(),
+        Err(_) => return stem,
+    };
+    let rel = relative_path(Path::new(""), &rel.with_file_name(&stem));
+    rel.trim_start_matches("./").to_string()
+}
+
+/// Rewrite every link in `text` that points at `old` so it points at `new`.
+/// Returns the new text and the number of links rewritten.
+pub fn rewrite_links(text: &str, from_file: &Path, old: &Path, new: &Path, root: &Path) -> (String, usize) {
+    let mut fence: Option<&str> = None;
+    let mut count = 0usize;
+    let mut out = String::with_capacity(text.len());
+    for segment in text.split_inclusive('\n') {
+        let line = segment.trim_end_matches(['\n', '\r']);
+        let eol = &segment[line.len()..];
+        let trimmed = line.trim_start();
+        let marker = if trimmed.starts_with("```") {
+            Some("```")
+        } else if trimmed.starts_with("~~~") {
+            Some("~~~")
+        } else {
+            None
+        };
+        let in_prose = match (fence, marker) {
+            (None, Some(m)) => {
+                fence = Some(m);
+                false
+            }
+            (Some(open), Some(m)) if open == m => {
+                fence = None;
+                false
+            }
+            (Some(_), _) => false,
+            (None, None) => true,
+        };
+        if !in_prose {
+            out.push_str(segment);
+            continue;
+        }
+        let mut rewritten = line.to_string();
+        for link in extract_links(line).into_iter().rev() {
+            if !link_points_at(&link, from_file, old) {
+                continue;
+            }
+            let replacement = match link.kind {
+                LinkKind::Wikilink => wikilink_replacement(&link.target, new, root),
+                LinkKind::Markdown => {
+                    let dir = from_file.parent().unwrap_or(Path::new(""));
+                    let rel = relative_path(dir, new);
+                    if link.target.contains("%20") || (rel.contains(' ') && !line[..link.start].ends_with('<')) {
+                        rel.replace(' ', "%20")
+                    } else {
+                        rel
+                    }
+                }
+            };
+            rewritten.replace_range(link.start..link.end, &replacement);
+            count += 1;
+        }
+        out.push_str(&rewritten);
+        out.push_str(eol);
+    }
+    (out, count)
+}
+
+/// Byte range of the destination in a reference definition
+/// (`[label]: /path/to/file "title"`).
+fn definition_target(line: &str) -> Option<(usize, usize)> {
+    let trimmed = line.trim_start();
+    if !trimmed.starts_with('[') || trimmed.starts_with("[[") {
+        return None;
+    }
+    let colon = trimmed.find("]:")? + 2;
+    if trimmed[1..colon - 2].contains(']') {
+        return None;
+    }
+    let lead = line.len() - trimmed.len() + colon;
+    let rest = &line[lead..];
+    let start = lead + rest.len() - rest.trim_start().len();
+    let dest = &line[start..];
+    let (start, dest) = match dest.strip_prefix('<') {
+        Some(inner) => (start + 1, &inner[..inner.find('>')?]),
+        None => (start, &dest[..dest.find(char::is_whitespace).unwrap_or(dest.len())]),
+    };
+    let len = dest.find('#').unwrap_or(dest.len());
+    (len > 0).then_some((start, start + len))
+}
+
+/// Whether `a` and `b` share a folder below the filesystem root; a path that
+/// only meets the document at `/` or a drive is left absolute.
+fn share_ancestor(a: &Path, b: &Path) -> bool {
+    a.components()
+        .zip(b.components())
+        .take_while(|(x, y)| x == y)
+        .any(|(c, _)| matches!(c, Component::Normal(_)))
+}
+
+/// Apply `map` to every local Markdown link, image and reference-definition
+/// target on prose lines. `map` gets the percent-decoded, normalized path and
+/// returns the replacement (with `/` separators) or `None` to keep it.
+fn map_local_targets(text: &str, mut map: impl FnMut(&Path) -> Option<String>) -> (String, usize) {
+    let prose: std::collections::HashSet<usize> = prose_lines(text).map(|(i, _)| i).collect();
+    let mut count = 0usize;
+    let mut out = String::with_capacity(text.len());
+    for (i, segment) in text.split_inclusive('\n').enumerate() {
+        let line = segment.trim_end_matches(['\n', '\r']);
+        let eol = &segment[line.len()..];
+        if !prose.contains(&i) {
+            out.push_str(segment);
+            continue;
+        }
+        let mut targets: Vec<(usize, usize)> = extract_links(line)
+            .into_iter()
+            .filter(|l| l.kind == LinkKind::Markdown)
+            .map(|l| (l.start, l.end))
+            .collect();
+        targets.extend(definition_target(line));
+        targets.sort();
+        targets.dedup();
+        let mut rewritten = line.to_string();
+        for (start, end) in targets.into_iter().rev() {
+            let target = &line[start..end];
+            if has_scheme(target) {
+                continue;
+            }
+            let decoded = urlencoding::decode(target)
+                .map(|s| s.into_owned())
+                .unwrap_or_else(|_| target.to_string());
+            let Some(rel) = map(&normalize_path(Path::new(&decoded))) else { continue };
+            let rel = if target.contains("%20") || (rel.contains(' ') && !line[..start].ends_with('<')) {
+                rel.replace(' ', "%20")
+            } else {
+                rel
+            };
+            rewritten.replace_range(start..end, &rel);
+            count += 1;
+        }
+        out.push_str(&rewritten);
+        out.push_str(eol);
+    }
+    (out, count)
+}
+
+/// Rewrite absolute local link, image and reference-definition targets in
+/// `text` relative to the folder of `doc_path`. Remote URLs, relative paths
+/// and targets with no common ancestor are left alone. Returns the new text
+/// and how many targets changed.
+pub fn relativize(text: &str, doc_path: &Path) -> (String, usize) {
+    let dir = normalize_path(doc_path.parent().unwrap_or(Path::new("")));
+    map_local_targets(text, |path| {
+        (path.is_absolute() && share_ancestor(&dir, path)).then(|| relative_path(&dir, path))
+    })
+}
+
+/// Rewrite relative targets written for a document at `from_doc` so they
+/// still point at the same files from `to_doc`, e.g. when its text moves
+/// into another document. Absolute paths and remote URLs are left alone.
+pub fn rebase(text: &str, from_doc: &Path, to_doc: &Path) -> (String, usize) {
+    let from = normalize_path(from_doc.parent().unwrap_or(Path::new("")));
+    let to = normalize_path(to_doc.parent().unwrap_or(Path::new("")));
+    if from == to {
+        return (text.to_string(), 0);
+    }
+    map_local_targets(text, |path| {
+        (!path.is_absolute()).then(|| relative_path(&to, &normalize_path(&from.join(path))))
+    })
+}
+
+/// Rewrite links to `old` across `root`. With `dry_run` the files are left
+/// untouched and only the planned changes are reported.
+pub fn update_backlinks(
+    locks: &FileLocks,
+    old: &Path,
+    new: &Path,
+    root: &Path,
+    max_bytes: u64,
+    dry_run: bool,
+) -> AppResult<BacklinkUpdate> {
+    let old = normalize_path(old);
+    let new = normalize_path(new);
+    let mut result = BacklinkUpdate { files_changed: 0, links_updated: 0, changes: Vec::new() };
+    for file in collect_markdown_files(root, max_bytes) {
+        let Some(text) = read_markdown(&file, max_bytes) else { continue };
+        let (rewritten, count) = rewrite_links(&text, &file, &old, &new, root);
+        if count == 0 {
+            continue;
+        }
+        result.changes.extend(planned_changes(&file, &text, &rewritten));
+        if !dry_run {
+            write_rewrite(locks, &file, &text, &rewritten)?;
+        }
+        result.files_changed += 1;
+        result.links_updated += count;
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameWithRefs {
+    pub new_path: String,
+    /// Files whose links were rewritten (on a dry run: would be).
+    pub updated_files: Vec<String>,
+    /// Rewrites that could not be saved after the rename went through; their
+    /// links still point at the old name.
+    pub failed: Vec<FailedRewrite>,
+    pub changes: Vec<PlannedChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedRewrite {
+    pub path: String,
+    pub message: String,
+}
+
+/// Rename `old` to `new_name` in its folder (keeping its extension when the
+/// name has none) and point every reference under `root` at the new name.
+/// All rewrites are worked out before the rename; once it has happened, a
+/// file that can't be saved is reported in `failed` and the rest are still
+/// written, so the result says exactly which files changed.
+pub fn rename_with_refs(
+    locks: &FileLocks,
+    old: &Path,
+    new_name: &str,
+    root: &Path,
+    max_bytes: u64,
+    dry_run: bool,
+) -> AppResult<RenameWithRefs> {
+    let name = new_name.trim();
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Err(AppError::InvalidInput(format!("invalid file name: {:?}", new_name)));
+    }
+    if !old.is_file() {
+        return Err(AppError::NotFound { path: old.to_string_lossy().into_owned() });
+    }
+    let old = normalize_path(old);
+    let mut new = old.with_file_name(name);
+    if new.extension().is_none() {
+        if let Some(ext) = old.extension() {
+            new.set_extension(ext);
+        }
+    }
+    if new.exists() && !same_path(&new, &old) {
+        return Err(AppError::AlreadyExists { path: new.to_string_lossy().into_owned() });
+    }
+
+    let mut changes = Vec::new();
+    let mut rewrites = Vec::new();
+    for file in collect_markdown_files(root, max_bytes) {
+        let Some(text) = read_markdown(&file, max_bytes) else { continue };
+        let (rewritten, count) = rewrite_links(&text, &file, &old, &new, root);
+        if count == 0 {
+            continue;
+        }
+        // The note's links to itself move with it.
+        let file = if same_path(&normalize_path(&file), &old) { new.clone() } else { file };
+        changes.extend(planned_changes(&file, &text, &rewritten));
+        rewrites.push((file, text, rewritten));
+    }
+    let mut result = RenameWithRefs {
+        new_path: new.to_string_lossy().into_owned(),
+        updated_files: Vec::new(),
+        failed: Vec::new(),
+        changes,
+    };
+    if dry_run {
+        result.updated_files = rewrites.into_iter().map(|(file, _, _)| file.to_string_lossy().into_owned()).collect();
+        return Ok(result);
+    }
+
+    std::fs::rename(&old, &new).map_err(|e| AppError::io(&old, e))?;
+    // Past the rename there is no bailing out: a note that can't be re-read
+    // or written is reported so its links can be fixed by hand.
+    for (file, original, rewritten) in rewrites {
+        let path = file.to_string_lossy().into_owned();
+        match write_rewrite(locks, &file, &original, &rewritten) {
+            Ok(()) => result.updated_files.push(path),
+            Err(e) => result.failed.push(FailedRewrite { path, message: e.to_string() }),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_wikilink_name_without_alias_or_heading() {
+        let links = extract_links("see [[My Note#Intro|the note]] and [[Other]]");
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].kind, LinkKind::Wikilink);
+        assert_eq!(links[0].target, "My Note");
+        assert_eq!(links[1].target, "Other");
+    }
+
+    #[test]
+    fn extracts_relative_markdown_links_and_skips_remote() {
+        let links = extract_links("[a](../b.md#x \"t\") [web](https://x.io/a.md) [top](#anchor)");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target, "../b.md");
+    }
+
+    #[test]
+    fn ignores_links_inside_code_spans() {
+        assert!(extract_links("`[[Nope]]` and `[x](y.md)`").is_empty());
+    }
+
+    #[test]
+    fn resolves_percent_encoded_relative_targets() {
+        let from = Path::new("/vault/a/start.md");
+        let resolved = resolve_markdown_target(from, "../b/My%20Note.md").unwrap();
+        assert_eq!(resolved, PathBuf::from("/vault/b/My Note.md"));
+    }
+
+    #[test]
+    fn wikilink_matching_honours_folder_prefix() {
+        let target = Path::new("/vault/projects/Plan.md");
+        assert!(wikilink_matches("plan", target));
+        assert!(wikilink_matches("projects/Plan", target));
+        assert!(!wikilink_matches("archive/Plan", target));
+    }
+
+    #[test]
+    fn relative_path_walks_up_and_down() {
+        assert_eq!(relative_path(Path::new("/v/a/b"), Path::new("/v/c/d.md")), "../../c/d.md");
+        assert_eq!(relative_path(Path::new("/v"), Path::new("/v/d.md")), "d.md");
+    }
+
+    #[test]
+    fn backlinks_skip_fenced_code() {
+        let text = "[[Plan]]\n```\n[[Plan]]\n```\n[p](projects/Plan.md)\n";
+        let hits = backlinks_in(text, Path::new("/vault/index.md"), Path::new("/vault/projects/Plan.md"));
+        assert_eq!(hits.iter().map(|h| h.line).collect::<Vec<_>>(), vec![1, 5]);
+    }
+
+    #[test]
+    fn rewrite_updates_both_link_styles() {
+        let text = "[[Plan|the plan]] and [p](projects/Plan.md#goals)\r\n";
+        let (out, n) = rewrite_links(
+            text,
+            Path::new("/vault/index.md"),
+            Path::new("/vault/projects/Plan.md"),
+            Path::new("/vault/projects/Roadmap.md"),
+            Path::new("/vault"),
+        );
+        assert_eq!(n, 2);
+        assert_eq!(out, "[[Roadmap|the plan]] and [p](projects/Roadmap.md#goals)\r\n");
+    }
+
+    #[test]
+    fn dry_run_reports_changes_without_writing() {
+        let root = std::env::temp_dir().join(format!("mermark-backlinks-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let index = root.join("index.md");
+        let original = "# Index\nSee [[Plan]].\n";
+        std::fs::write(&index, original).unwrap();
+        std::fs::write(root.join("Plan.md"), "plan").unwrap();
+
+        let update =
+            update_backlinks(&FileLocks::default(), &root.join("Plan.md"), &root.join("Roadmap.md"), &root, u64::MAX, true)
+                .unwrap();
+        assert_eq!((update.files_changed, update.links_updated), (1, 1));
+        assert_eq!(
+            update.changes,
+            [PlannedChange {
+                file: index.to_string_lossy().into_owned(),
+                line: 2,
+                old: "See [[Plan]].".to_string(),
+                new: "See [[Roadmap]].".to_string(),
+            }]
+        );
+        assert_eq!(std::fs::read_to_string(&index).unwrap(), original);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rewriting_a_large_note_keeps_all_of_it() {
+        let root = std::env::temp_dir().join(format!("mermark-backlinks-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let big = root.join("big.md");
+        let filler = "Lorem ipsum dolor sit amet.\n".repeat(30_000);
+        std::fs::write(&big, format!("See [[Plan]].\n{}The end, [[Plan]].\n", filler)).unwrap();
+        assert!(std::fs::metadata(&big).unwrap().len() > 512 * 1024);
+
+        let update = update_backlinks(
+            &FileLocks::default(),
+            &root.join("Plan.md"),
+            &root.join("Roadmap.md"),
+            &root,
+            crate::files::DEFAULT_MAX_OPEN_BYTES,
+            false,
+        )
+        .unwrap();
+        assert_eq!(update.links_updated, 2);
+        let saved = std::fs::read_to_string(&big).unwrap();
+        assert_eq!(saved, format!("See [[Roadmap]].\n{}The end, [[Roadmap]].\n", filler));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn relativize_rewrites_absolute_local_targets_only() {
+        let text = "![a](/notes/img/a%20b.png) [b](/notes/sub/b.md#x) [c](c.md) [d](https://x.io/d)\n\
+                    [e]: </notes/e.pdf> \"E\"\n[f](/etc/f.conf)\n```\n![z](/notes/z.png)\n```\n";
+        let (out, n) = relativize(text, Path::new("/notes/day/today.md"));
+        assert_eq!(n, 3);
+        assert_eq!(
+            out,
+            "![a](../img/a%20b.png) [b](../sub/b.md#x) [c](c.md) [d](https://x.io/d)\n\
+             [e]: <../e.pdf> \"E\"\n[f](/etc/f.conf)\n```\n![z](/notes/z.png)\n```\n"
+        );
+    }
+
+    #[test]
+    fn rebase_keeps_relative_targets_pointing_at_the_same_files() {
+        let text = "![a](img/a%20b.png) [b](../other/b.md#x) [c](/abs/c.md) [d](https://x.io/d)\n`[e](e.md)`\n";
+        let (out, n) = rebase(text, Path::new("/notes/day/today.md"), Path::new("/notes/merged.md"));
+        assert_eq!(
+            out,
+            "![a](day/img/a%20b.png) [b](other/b.md#x) [c](/abs/c.md) [d](https://x.io/d)\n`[e](e.md)`\n"
+        );
+        assert_eq!(n, 2);
+        assert_eq!(rebase(text, Path::new("/n/a.md"), Path::new("/n/b.md")).1, 0);
+    }
+
+    #[test]
+    fn rename_with_refs_moves_the_file_and_its_references() {
+        let root = std::env::temp_dir().join(format!("mermark-rename-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        let index = root.join("sub/index.md");
+        std::fs::write(&index, "[[Plan]] and [p](../Plan.md#goals)\n").unwrap();
+        std::fs::write(root.join("Plan.md"), "# Plan\n[top](Plan.md)\n").unwrap();
+        std::fs::write(root.join("Taken.md"), "").unwrap();
+        let locks = FileLocks::default();
+
+        assert!(matches!(
+            rename_with_refs(&locks, &root.join("Plan.md"), "Taken", &root, u64::MAX, false),
+            Err(AppError::AlreadyExists { .. })
+        ));
+        assert!(rename_with_refs(&locks, &root.join("Plan.md"), "a/b", &root, u64::MAX, false).is_err());
+
+        let preview = rename_with_refs(&locks, &root.join("Plan.md"), "Roadmap", &root, u64::MAX, true).unwrap();
+        assert_eq!(preview.updated_files.len(), 2);
+        assert!(root.join("Plan.md").is_file());
+
+        let done = rename_with_refs(&locks, &root.join("Plan.md"), "Roadmap", &root, u64::MAX, false).unwrap();
+        let roadmap = root.join("Roadmap.md");
+        assert_eq!(done.new_path, roadmap.to_string_lossy());
+        assert!(done.failed.is_empty());
+        assert!(!root.join("Plan.md").exists());
+        assert_eq!(std::fs::read_to_string(&index).unwrap(), "[[Roadmap]] and [p](../Roadmap.md#goals)\n");
+        assert_eq!(std::fs::read_to_string(&roadmap).unwrap(), "# Plan\n[top](Roadmap.md)\n");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn a_rewrite_leaves_a_file_changed_since_it_was_read() {
+        let file = std::env::temp_dir().join(format!("mermark-rewrite-{}.md", uuid::Uuid::new_v4()));
+        std::fs::write(&file, "saved by the editor\n").unwrap();
+        let locks = FileLocks::default();
+        assert!(matches!(
+            write_rewrite(&locks, &file, "read before\n", "rewritten\n"),
+            Err(AppError::Conflict { .. })
+        ));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "saved by the editor\n");
+        write_rewrite(&locks, &file, "saved by the editor\n", "rewritten\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "rewritten\n");
+        std::fs::remove_file(&file).unwrap();
+    }
+}
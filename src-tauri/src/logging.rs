@@ -0,0 +1,180 @@
+//! Troubleshooting log: `tracing` events written to `<app_log_dir>/mermark.log`
+//! (`data/logs` in portable mode), rotated by size so the total stays
+//! bounded. Events carry command names and paths, never document content.
+//! File output can be switched off with the `fileLogging` setting.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{reload, Registry};
+
+use crate::error::{AppError, AppResult};
+use crate::settings::AppSettings;
+
+pub const LOG_FILE: &str = "mermark.log";
+/// One file's size before it is rotated to `mermark.1.log`.
+const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+/// Files kept including the active one, so at most ~6 MB on disk.
+const KEEP_FILES: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    /// Also logs every command invocation.
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// `mermark.log` rotating to `mermark.1.log`, `mermark.2.log`, ...
+pub struct RotatingFile {
+    dir: PathBuf,
+    file: Option<File>,
+    written: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+fn rotated_name(index: usize) -> String {
+    if index == 0 {
+        LOG_FILE.to_string()
+    } else {
+        format!("mermark.{}.log", index)
+    }
+}
+
+impl RotatingFile {
+    pub fn open(dir: &Path, max_bytes: u64, keep: usize) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(LOG_FILE);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self { dir: dir.to_path_buf(), file: Some(file), written, max_bytes, keep: keep.max(1) })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        let _ = std::fs::remove_file(self.dir.join(rotated_name(self.keep - 1)));
+        for i in (0..self.keep - 1).rev() {
+            let from = self.dir.join(rotated_name(i));
+            if from.exists() {
+                std::fs::rename(&from, self.dir.join(rotated_name(i + 1)))?;
+            }
+        }
+        self.file = Some(File::create(self.dir.join(LOG_FILE))?);
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let file = self.file.as_mut().ok_or_else(|| std::io::Error::other("log file closed"))?;
+        let n = file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), |f| f.flush())
+    }
+}
+
+/// Handle the fmt layer writes through; drops output while disabled.
+#[derive(Clone)]
+struct SharedWriter {
+    file: Arc<Mutex<RotatingFile>>,
+    enabled: Arc<AtomicBool>,
+}
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return Ok(buf.len());
+        }
+        self.file.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.lock().unwrap().flush()
+    }
+}
+
+pub struct LogState {
+    level: reload::Handle<LevelFilter, Registry>,
+    enabled: Arc<AtomicBool>,
+    pub path: PathBuf,
+}
+
+impl LogState {
+    pub fn set_level(&self, level: LogLevel) -> AppResult<()> {
+        self.level
+            .reload(level.filter())
+            .map_err(|e| AppError::Internal(format!("log level: {}", e)))
+    }
+
+    /// Follow `fileLogging` and `logLevel` after a settings change.
+    pub fn apply(&self, settings: &AppSettings) -> AppResult<()> {
+        self.enabled.store(settings.file_logging, Ordering::Relaxed);
+        self.set_level(settings.log_level)
+    }
+}
+
+/// Install the global subscriber. Called once from setup.
+pub fn init(app: &tauri::AppHandle, settings: &AppSettings) -> AppResult<LogState> {
+    let dir = crate::portable::log_dir(app)?;
+    let file = RotatingFile::open(&dir, MAX_FILE_BYTES, KEEP_FILES).map_err(|e| AppError::io(&dir, e))?;
+    let enabled = Arc::new(AtomicBool::new(settings.file_logging));
+    let writer = SharedWriter { file: Arc::new(Mutex::new(file)), enabled: enabled.clone() };
+    let (filter, level) = reload::Layer::new(settings.log_level.filter());
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(move || writer.clone()))
+        .try_init()
+        .map_err(|e| AppError::Internal(format!("logging: {}", e)))?;
+    Ok(LogState { level, enabled, path: dir.join(LOG_FILE) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_by_size_and_keeps_a_bounded_number_of_files() {
+        let dir = std::env::temp_dir().join(format!("mermark-log-{}", uuid::Uuid::new_v4()));
+        let mut log = RotatingFile::open(&dir, 10, 3).unwrap();
+        for line in ["aaaaaaaa\n", "bbbbbbbb\n", "cccccccc\n", "dddddddd\n"] {
+            log.write_all(line.as_bytes()).unwrap();
+        }
+        log.flush().unwrap();
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("mermark.log"), "dddddddd\n");
+        assert_eq!(read("mermark.1.log"), "cccccccc\n");
+        assert_eq!(read("mermark.2.log"), "bbbbbbbb\n");
+        assert!(!dir.join("mermark.3.log").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+//! CSV/TSV to GFM table, for pasting spreadsheet data and importing files.
+
+use super::tables::{self, Align};
+
+/// Parse delimited text into records. Quoted fields may contain the
+/// delimiter, newlines and `""` escapes; a trailing newline ends the last
+/// record rather than adding an empty one.
+pub fn parse(data: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    // Blank lines are not rows.
+    records.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+    records
+}
+
+/// Make a field safe inside a table cell: pipes escaped, line breaks as `<br>`.
+fn cell(field: &str) -> String {
+    field
+        .trim()
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// Render delimited `data` as a padded GFM table. Without a header row the
+/// columns are named `Column 1`, `Column 2`, ...
+pub fn to_table(data: &str, delimiter: char, has_header: bool) -> String {
+    let records = parse(data, delimiter);
+    if records.is_empty() {
+        return String::new();
+    }
+    let mut rows: Vec<Vec<String>> = records.iter().map(|r| r.iter().map(|f| cell(f)).collect()).collect();
+    if !has_header {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        rows.insert(0, (1..=columns).map(|i| format!("Column {}", i)).collect());
+    }
+    let mut out = tables::render(&rows, &vec![Align::None; rows[0].len()]).join("\n");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_keep_delimiters_quotes_and_newlines() {
+        let records = parse("a,\"b, c\",\"say \"\"hi\"\"\"\r\n1,\"two\nlines\",3\n", ',');
+        assert_eq!(records, vec![vec!["a", "b, c", "say \"hi\""], vec!["1", "two\nlines", "3"]]);
+    }
+
+    #[test]
+    fn renders_table_with_header_and_escaped_pipes() {
+        assert_eq!(
+            to_table("name\tvalue\nx|y\t\"1\n2\"\n", '\t', true),
+            "| name | value  |\n| ---- | ------ |\n| x\\|y | 1<br>2 |\n"
+        );
+    }
+
+    #[test]
+    fn generates_header_when_missing() {
+        assert_eq!(
+            to_table("1;2\n3\n", ';', false),
+            "| Column 1 | Column 2 |\n| -------- | -------- |\n| 1        | 2        |\n| 3        |          |\n"
+        );
+    }
+}
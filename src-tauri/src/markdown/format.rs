@@ -0,0 +1,161 @@
+//! "Format Document": whitespace and marker normalization that leaves the
+//! rendered result alone. Fenced code and front matter are never touched.
+
+use serde::Deserialize;
+
+use super::{fenced_blocks, frontmatter, heading_level, tables};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormatOptions {
+    /// Bullet list marker: `-`, `*` or `+`.
+    pub bullet: char,
+    pub tables: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self { bullet: '-', tables: true }
+    }
+}
+
+/// Horizontal rules such as `* * *` look like list items; leave them be.
+fn is_thematic_break(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_'].iter().any(|&m| compact.chars().all(|c| c == m))
+}
+
+fn normalize_bullet(line: &str, bullet: char) -> String {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+    let mut chars = rest.chars();
+    match (chars.next(), chars.next()) {
+        (Some(m @ ('-' | '*' | '+')), Some(' ' | '\t')) if m != bullet && !is_thematic_break(line) => {
+            format!("{}{}{}", &line[..indent], bullet, &rest[1..])
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// `#   Title` -> `# Title`.
+fn normalize_heading(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let hashes = trimmed.len() - trimmed.trim_start_matches('#').len();
+    let text = trimmed[hashes..].trim();
+    if text.is_empty() {
+        trimmed[..hashes].to_string()
+    } else {
+        format!("{} {}", &trimmed[..hashes], text)
+    }
+}
+
+/// Strip trailing whitespace, but keep a two-space hard line break when the
+/// paragraph continues on the next line.
+fn trim_trailing(line: &str, next_is_text: bool) -> String {
+    let trimmed = line.trim_end_matches([' ', '\t']);
+    if next_is_text && !trimmed.is_empty() && line[trimmed.len()..].starts_with("  ") {
+        format!("{}  ", trimmed)
+    } else {
+        trimmed.to_string()
+    }
+}
+
+pub fn format(text: &str, options: &FormatOptions) -> String {
+    let (head, body) = match frontmatter::split(text) {
+        Some((raw, body)) => (Some(raw), body),
+        None => (None, text),
+    };
+    let body = if options.tables { tables::format_all(body) } else { body.to_string() };
+
+    let lines: Vec<&str> = body.lines().collect();
+    let fences = fenced_blocks(&body);
+    let in_fence = |i: usize| fences.iter().any(|&(open, close)| open <= i && i <= close);
+
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        if in_fence(i) {
+            out.push(line.to_string());
+            continue;
+        }
+        let next_is_text = lines
+            .get(i + 1)
+            .map(|n| !n.trim().is_empty() && !in_fence(i + 1))
+            .unwrap_or(false);
+        let mut line = trim_trailing(line, next_is_text);
+        let blank = line.is_empty();
+        // Collapse runs of blank lines (and drop leading ones).
+        if blank && out.last().is_none_or(|prev| prev.is_empty()) {
+            continue;
+        }
+        if heading_level(&line).is_some() {
+            line = normalize_heading(&line);
+            if out.last().is_some_and(|prev| !prev.is_empty()) {
+                out.push(String::new());
+            }
+            out.push(line);
+            if lines.get(i + 1).is_some_and(|n| !n.trim().is_empty()) {
+                out.push(String::new());
+            }
+            continue;
+        }
+        out.push(normalize_bullet(&line, options.bullet));
+    }
+    while out.last().is_some_and(|l| l.is_empty()) {
+        out.pop();
+    }
+
+    let mut result = String::new();
+    if let Some(raw) = head {
+        result.push_str("---\n");
+        result.push_str(raw);
+        result.push_str("\n---\n");
+        if !out.is_empty() {
+            result.push('\n');
+        }
+    }
+    result.push_str(&out.join("\n"));
+    if !result.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(text: &str) -> String {
+        format(text, &FormatOptions::default())
+    }
+
+    #[test]
+    fn normalizes_headings_lists_and_blank_lines() {
+        let doc = "#   Title  \nintro   \n\n\n\n* one\n+ two\n## Next\ntext";
+        assert_eq!(fmt(doc), "# Title\n\nintro\n\n- one\n- two\n\n## Next\n\ntext\n");
+    }
+
+    #[test]
+    fn leaves_code_front_matter_and_rules_alone() {
+        let doc = "---\ntitle:  x  \n---\n\n* * *\n```\n*  keep   \n\n\n```\n";
+        assert_eq!(fmt(doc), "---\ntitle:  x  \n---\n\n* * *\n```\n*  keep   \n\n\n```\n");
+    }
+
+    #[test]
+    fn keeps_hard_line_breaks() {
+        assert_eq!(fmt("line one   \nline two  \n"), "line one  \nline two\n");
+    }
+
+    #[test]
+    fn is_idempotent() {
+        let doc = "intro\n# A\n* x\n|a|b|\n|-|-|\n|1|2|\n\n\n## B   ##\n";
+        let once = fmt(doc);
+        assert_eq!(fmt(&once), once);
+    }
+
+    #[test]
+    fn uses_the_configured_bullet() {
+        let options = FormatOptions { bullet: '*', ..Default::default() };
+        assert_eq!(format("- a\n  - b\n", &options), "* a\n  * b\n");
+    }
+}
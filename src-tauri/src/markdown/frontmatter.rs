@@ -0,0 +1,85 @@
+//! YAML front matter: splitting it off the body and reading flat scalar keys.
+//! Line-based on purpose, like the frontend's `utils/frontmatter.ts` — the
+//! keys we care about (`title`, ...) are plain `key: value` scalars.
+
+use std::path::{Path, PathBuf};
+
+/// `(inner yaml, body)` when `text` opens with a `---` fenced block closed by
+/// `---` or `...`. The inner text excludes the fences.
+pub fn split(text: &str) -> Option<(&str, &str)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let first_end = text.find('\n')?;
+    if text[..first_end].trim_end() != "---" {
+        return None;
+    }
+    let inner_start = first_end + 1;
+    let mut pos = inner_start;
+    while pos <= text.len() {
+        let line_end = text[pos..].find('\n').map(|i| pos + i).unwrap_or(text.len());
+        let line = text[pos..line_end].trim_end();
+        if line == "---" || line == "..." {
+            let inner = text[inner_start..pos].trim_end_matches(['\r', '\n']);
+            let body = text.get(line_end + 1..).unwrap_or("");
+            return Some((inner, body));
+        }
+        if line_end == text.len() {
+            break;
+        }
+        pos = line_end + 1;
+    }
+    None
+}
+
+/// Value of a top-level scalar `key`, with surrounding quotes removed.
+pub fn get_key(raw: &str, key: &str) -> Option<String> {
+    raw.lines().find_map(|line| {
+        let rest = line.strip_prefix(key)?.trim_start();
+        let value = rest.strip_prefix(':')?.trim();
+        let unquoted = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+        Some(unquoted.to_string())
+    })
+}
+
+/// `notes/post.md` -> `notes/post.meta.yaml`.
+pub fn meta_path(doc: &Path) -> PathBuf {
+    let stem = doc.file_stem().unwrap_or_default().to_string_lossy();
+    doc.with_file_name(format!("{}.meta.yaml", stem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_front_matter_from_body() {
+        let doc = "---\ntitle: \"Hello\"\ntags: [a]\n---\n# Body\n";
+        let (raw, body) = split(doc).unwrap();
+        assert_eq!(raw, "title: \"Hello\"\ntags: [a]");
+        assert_eq!(body, "# Body\n");
+        assert_eq!(get_key(raw, "title").as_deref(), Some("Hello"));
+        assert_eq!(get_key(raw, "missing"), None);
+    }
+
+    #[test]
+    fn handles_crlf_and_dot_terminator() {
+        let (raw, body) = split("---\r\ntitle: x\r\n...\r\nbody").unwrap();
+        assert_eq!(raw, "title: x");
+        assert_eq!(body, "body");
+    }
+
+    #[test]
+    fn documents_without_front_matter_are_left_alone() {
+        assert!(split("# Title\n---\n").is_none());
+        assert!(split("---\nnever closed\n").is_none());
+        assert!(split("").is_none());
+    }
+
+    #[test]
+    fn meta_path_is_a_sibling() {
+        assert_eq!(meta_path(Path::new("/n/post.md")), PathBuf::from("/n/post.meta.yaml"));
+    }
+}
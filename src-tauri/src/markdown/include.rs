@@ -0,0 +1,219 @@
+//! `{{include: other.md}}` transclusion flattened into one document for
+//! export or sharing. Targets resolve relative to the file that names them,
+//! and the inlined text's relative links and images are rebased onto the
+//! top document so they still point at the same files. Only files inside the
+//! document's workspace or its own folder are read. Directives in code are
+//! left alone; ones that can't be inlined become a visible comment.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+
+use super::frontmatter;
+use crate::links;
+
+pub const DEFAULT_MAX_DEPTH: usize = 8;
+
+const OPEN: &str = "{{include:";
+const CLOSE: &str = "}}";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedIncludes {
+    pub content: String,
+    /// Inlined files, once each, in the order first included.
+    pub included: Vec<String>,
+}
+
+struct Resolver<'a> {
+    /// The document everything is inlined into; links are rebased onto it.
+    root_doc: &'a Path,
+    max_depth: usize,
+    max_bytes: u64,
+    /// Canonical folders an include may read from.
+    allowed: Vec<PathBuf>,
+    /// Files being inlined on the current path from the top, for cycles.
+    active: Vec<PathBuf>,
+    seen: HashSet<PathBuf>,
+    included: Vec<String>,
+}
+
+impl Resolver<'_> {
+    /// The text to put in place of an include of `target` from `doc`.
+    fn inline(&mut self, target: &str, doc: &Path, depth: usize) -> String {
+        let placeholder = |why: &str| format!("<!-- include {}: {} -->", why, target);
+        let path = links::normalize_path(&doc.parent().unwrap_or(Path::new("")).join(target));
+        if self.active.contains(&path) {
+            return placeholder("skipped, it includes itself");
+        }
+        if depth >= self.max_depth {
+            return placeholder("skipped, nested too deep");
+        }
+        let Ok(real) = std::fs::canonicalize(&path) else {
+            return placeholder("not found");
+        };
+        if !self.allowed.iter().any(|base| real.starts_with(base)) {
+            return placeholder("refused, outside the workspace");
+        }
+        let Ok(text) = crate::files::read_text(&path, Some(self.max_bytes)) else {
+            return placeholder("not found");
+        };
+        if self.seen.insert(path.clone()) {
+            self.included.push(path.to_string_lossy().into_owned());
+        }
+        // The included file's front matter would read as a rule and text.
+        let body = frontmatter::split(&text).map_or(text.as_str(), |(_, body)| body);
+        let (body, _) = links::rebase(body, &path, self.root_doc);
+        self.active.push(path.clone());
+        let expanded = self.expand(&body, &path, depth + 1);
+        self.active.pop();
+        expanded.trim_matches(['\n', '\r']).to_string()
+    }
+
+    /// Replace the directives in the prose of `text`, which belongs to `doc`.
+    fn expand(&mut self, text: &str, doc: &Path, depth: usize) -> String {
+        let prose: HashSet<usize> = links::prose_lines(text).map(|(i, _)| i).collect();
+        let mut out = String::with_capacity(text.len());
+        for (i, segment) in text.split_inclusive('\n').enumerate() {
+            if !prose.contains(&i) || !segment.contains(OPEN) {
+                out.push_str(segment);
+                continue;
+            }
+            let masked = links::mask_code_spans(segment);
+            let mut copied = 0;
+            let mut from = 0;
+            while let Some(start) = masked[from..].find(OPEN).map(|p| from + p) {
+                let Some(end) = masked[start..].find(CLOSE).map(|p| start + p) else { break };
+                let target = segment[start + OPEN.len()..end].trim();
+                out.push_str(&segment[copied..start]);
+                if target.is_empty() {
+                    out.push_str(&segment[start..end + CLOSE.len()]);
+                } else {
+                    let inlined = self.inline(target, doc, depth);
+                    out.push_str(&inlined);
+                }
+                copied = end + CLOSE.len();
+                from = copied;
+            }
+            out.push_str(&segment[copied..]);
+        }
+        out
+    }
+}
+
+/// Inline every include of `content` (the text of `doc_path`), following
+/// includes inside included files down to `max_depth` levels. Targets must
+/// lie under `workspace` (when the document is in it) or the document's
+/// folder; symlinks are followed before that check.
+pub fn resolve(
+    content: &str,
+    doc_path: &Path,
+    workspace: Option<&Path>,
+    max_depth: usize,
+    max_bytes: u64,
+) -> ResolvedIncludes {
+    let root_doc = links::normalize_path(doc_path);
+    let folder = root_doc.parent().and_then(|dir| std::fs::canonicalize(dir).ok());
+    let mut allowed: Vec<PathBuf> = folder.iter().cloned().collect();
+    if let Some(workspace) = workspace.and_then(|w| std::fs::canonicalize(w).ok()) {
+        if folder.as_ref().is_some_and(|dir| dir.starts_with(&workspace)) {
+            allowed.push(workspace);
+        }
+    }
+    let mut resolver = Resolver {
+        root_doc: &root_doc,
+        max_depth,
+        max_bytes,
+        allowed,
+        active: vec![root_doc.clone()],
+        seen: HashSet::new(),
+        included: Vec::new(),
+    };
+    let content = resolver.expand(content, &root_doc, 0);
+    ResolvedIncludes { content, included: resolver.included }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("mermark-include-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("parts/img")).unwrap();
+        std::fs::write(
+            root.join("parts/intro.md"),
+            "---\ntitle: Intro\n---\n\n## Intro\n\n![logo](img/logo.png) {{include: note.md}}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("parts/note.md"), "a note\n").unwrap();
+        std::fs::write(root.join("parts/loop.md"), "loop {{include: ../main.md}}\n").unwrap();
+        root
+    }
+
+    #[test]
+    fn inlines_nested_files_with_rebased_assets() {
+        let root = workspace();
+        let doc = root.join("main.md");
+        let text = "# Main\r\n{{include: parts/intro.md}}\r\n```\n{{include: parts/note.md}}\n```\n`{{include: x.md}}`\n";
+        let resolved = resolve(text, &doc, None, DEFAULT_MAX_DEPTH, u64::MAX);
+        assert_eq!(
+            resolved.content,
+            "# Main\r\n## Intro\n\n![logo](parts/img/logo.png) a note\r\n```\n{{include: parts/note.md}}\n```\n`{{include: x.md}}`\n"
+        );
+        let names: Vec<String> = resolved
+            .included
+            .iter()
+            .map(|p| Path::new(p).file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["intro.md", "note.md"]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn missing_cyclic_and_deep_includes_leave_a_comment() {
+        let root = workspace();
+        let doc = root.join("main.md");
+        let text = "{{include: gone.md}}\n{{include: parts/loop.md}}\n";
+        assert_eq!(
+            resolve(text, &doc, None, DEFAULT_MAX_DEPTH, u64::MAX).content,
+            "<!-- include not found: gone.md -->\nloop <!-- include skipped, it includes itself: ../main.md -->\n"
+        );
+        assert_eq!(
+            resolve("{{include: parts/intro.md}}", &doc, None, 1, u64::MAX).content,
+            "## Intro\n\n![logo](parts/img/logo.png) <!-- include skipped, nested too deep: note.md -->"
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn includes_outside_the_workspace_are_refused() {
+        let root = workspace();
+        let outside = std::env::temp_dir().join(format!("mermark-include-out-{}.md", uuid::Uuid::new_v4()));
+        std::fs::write(&outside, "secret\n").unwrap();
+        let doc = root.join("parts/intro.md");
+        let text = format!("{{{{include: ../../../../../../etc/passwd}}}}\n{{{{include: {}}}}}\n", outside.display());
+        assert_eq!(
+            resolve(&text, &doc, None, DEFAULT_MAX_DEPTH, u64::MAX).content,
+            format!(
+                "<!-- include refused, outside the workspace: ../../../../../../etc/passwd -->\n\
+                 <!-- include refused, outside the workspace: {} -->\n",
+                outside.display()
+            )
+        );
+
+        // The document's own folder is always allowed; its workspace only when given.
+        let up = "{{include: ../main.md}}";
+        std::fs::write(root.join("main.md"), "main\n").unwrap();
+        assert_eq!(
+            resolve(up, &doc, None, DEFAULT_MAX_DEPTH, u64::MAX).content,
+            "<!-- include refused, outside the workspace: ../main.md -->"
+        );
+        assert_eq!(resolve(up, &doc, Some(&root), DEFAULT_MAX_DEPTH, u64::MAX).content, "main");
+        // A workspace that doesn't contain the document grants nothing.
+        assert_eq!(
+            resolve(up, &doc, Some(&root.join("parts/img")), DEFAULT_MAX_DEPTH, u64::MAX).content,
+            "<!-- include refused, outside the workspace: ../main.md -->"
+        );
+        std::fs::remove_file(&outside).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
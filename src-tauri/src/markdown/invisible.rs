@@ -0,0 +1,165 @@
+//! Invisible characters and leftover editor markers: the corruption a normal
+//! text view hides. Everything is scanned, code blocks included.
+
+use serde::{Deserialize, Serialize};
+
+/// Placeholders the editor substitutes while converting content; none of
+/// them should ever reach a saved document.
+pub const SENTINELS: &[&str] = &["__CURSOR__", "__BR__", "__PAGE_BREAK__"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Category {
+    /// U+200B/200C/200D/2060 and friends.
+    ZeroWidth,
+    /// U+FEFF anywhere but the very start of the file.
+    Bom,
+    /// Directional embeddings, overrides, isolates and marks.
+    Bidi,
+    /// Internal markers from [`SENTINELS`].
+    Sentinel,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvisibleChar {
+    /// 1-based line and column (in chars).
+    pub line: usize,
+    pub col: usize,
+    /// `U+200B`; `None` for sentinels, whose `name` is the marker itself.
+    pub codepoint: Option<String>,
+    pub name: String,
+    pub category: Category,
+}
+
+fn describe(c: char) -> Option<(Category, &'static str)> {
+    Some(match c {
+        '\u{200B}' => (Category::ZeroWidth, "ZERO WIDTH SPACE"),
+        '\u{200C}' => (Category::ZeroWidth, "ZERO WIDTH NON-JOINER"),
+        '\u{200D}' => (Category::ZeroWidth, "ZERO WIDTH JOINER"),
+        '\u{2060}' => (Category::ZeroWidth, "WORD JOINER"),
+        '\u{180E}' => (Category::ZeroWidth, "MONGOLIAN VOWEL SEPARATOR"),
+        '\u{FEFF}' => (Category::Bom, "BYTE ORDER MARK"),
+        '\u{200E}' => (Category::Bidi, "LEFT-TO-RIGHT MARK"),
+        '\u{200F}' => (Category::Bidi, "RIGHT-TO-LEFT MARK"),
+        '\u{061C}' => (Category::Bidi, "ARABIC LETTER MARK"),
+        '\u{202A}' => (Category::Bidi, "LEFT-TO-RIGHT EMBEDDING"),
+        '\u{202B}' => (Category::Bidi, "RIGHT-TO-LEFT EMBEDDING"),
+        '\u{202C}' => (Category::Bidi, "POP DIRECTIONAL FORMATTING"),
+        '\u{202D}' => (Category::Bidi, "LEFT-TO-RIGHT OVERRIDE"),
+        '\u{202E}' => (Category::Bidi, "RIGHT-TO-LEFT OVERRIDE"),
+        '\u{2066}' => (Category::Bidi, "LEFT-TO-RIGHT ISOLATE"),
+        '\u{2067}' => (Category::Bidi, "RIGHT-TO-LEFT ISOLATE"),
+        '\u{2068}' => (Category::Bidi, "FIRST STRONG ISOLATE"),
+        '\u{2069}' => (Category::Bidi, "POP DIRECTIONAL ISOLATE"),
+        _ => return None,
+    })
+}
+
+/// A joiner between two pictographs builds an emoji sequence (👩‍💻); that
+/// one is content, not corruption.
+fn is_emoji_joiner(prev: Option<char>, c: char, next: Option<char>) -> bool {
+    let pictograph = |ch: Option<char>| ch.is_some_and(|ch| ch as u32 >= 0x2000 && !ch.is_alphanumeric());
+    c == '\u{200D}' && pictograph(prev) && pictograph(next)
+}
+
+/// Visit every flagged char as `(byte offset, char, category, name)`.
+/// `file_start` says whether `text` begins the document, where a BOM is fine.
+fn scan_chars(text: &str, file_start: bool, mut visit: impl FnMut(usize, char, Category, &'static str)) {
+    let mut prev = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, n)| n);
+        if let Some((category, name)) = describe(c) {
+            let leading_bom = category == Category::Bom && i == 0 && file_start;
+            if !leading_bom && !is_emoji_joiner(prev, c, next) {
+                visit(i, c, category, name);
+            }
+        }
+        prev = Some(c);
+    }
+}
+
+pub fn find(text: &str) -> Vec<InvisibleChar> {
+    let mut hits = Vec::new();
+    for (index, line) in text.split('\n').enumerate() {
+        let offset = line.as_ptr() as usize - text.as_ptr() as usize;
+        let col_of = |byte: usize| line[..byte].chars().count() + 1;
+        scan_chars(line, offset == 0, |i, c, category, name| {
+            hits.push(InvisibleChar {
+                line: index + 1,
+                col: col_of(i),
+                codepoint: Some(format!("U+{:04X}", c as u32)),
+                name: name.to_string(),
+                category,
+            });
+        });
+        for marker in SENTINELS {
+            for (i, _) in line.match_indices(marker) {
+                hits.push(InvisibleChar {
+                    line: index + 1,
+                    col: col_of(i),
+                    codepoint: None,
+                    name: marker.to_string(),
+                    category: Category::Sentinel,
+                });
+            }
+        }
+    }
+    hits.sort_by_key(|h| (h.line, h.col));
+    hits
+}
+
+/// Remove every occurrence in the selected categories.
+pub fn strip(text: &str, which: &[Category]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    scan_chars(text, true, |i, c, category, _| {
+        if which.contains(&category) {
+            out.push_str(&text[last..i]);
+            last = i + c.len_utf8();
+        }
+    });
+    out.push_str(&text[last..]);
+    if which.contains(&Category::Sentinel) {
+        for marker in SENTINELS {
+            out = out.replace(marker, "");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_each_category_with_positions() {
+        let text = "\u{FEFF}# Title\nab\u{200B}c\n\u{FEFF}x \u{202E}y\nfoo__CURSOR__bar\n";
+        let hits = find(text);
+        let summary: Vec<(usize, usize, Category)> = hits.iter().map(|h| (h.line, h.col, h.category)).collect();
+        assert_eq!(
+            summary,
+            [
+                (2, 3, Category::ZeroWidth),
+                (3, 1, Category::Bom),
+                (3, 4, Category::Bidi),
+                (4, 4, Category::Sentinel),
+            ]
+        );
+        assert_eq!(hits[0].codepoint.as_deref(), Some("U+200B"));
+        assert_eq!(hits[3].name, "__CURSOR__");
+    }
+
+    #[test]
+    fn emoji_sequences_are_not_flagged() {
+        assert!(find("dev: 👩\u{200D}💻\n").is_empty());
+    }
+
+    #[test]
+    fn strips_only_selected_categories() {
+        let text = "\u{FEFF}a\u{200B}b\u{200F}c__BR__d";
+        assert_eq!(strip(text, &[Category::ZeroWidth, Category::Sentinel]), "\u{FEFF}ab\u{200F}cd");
+        assert_eq!(strip(text, &[Category::Bidi]), "\u{FEFF}a\u{200B}bc__BR__d");
+    }
+}
@@ -0,0 +1,111 @@
+//! The inverse of `split`: several notes concatenated into one document,
+//! with relative links rebased onto the merged file's folder.
+
+use std::path::Path;
+use serde::Deserialize;
+
+use super::{frontmatter, heading_level};
+use crate::links;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JoinSeparator {
+    /// `# <file name>` above each document.
+    Heading,
+    /// A `---` rule between documents.
+    #[default]
+    Rule,
+    /// Just a blank line.
+    None,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JoinOptions {
+    pub separator: JoinSeparator,
+    /// Levels added to every heading of the joined documents, so they nest
+    /// under the separator headings. Capped at `######`.
+    pub demote: usize,
+    /// Where the merged document will be saved; relative links are rebased
+    /// onto its folder. Defaults to the first file.
+    pub target_path: Option<String>,
+}
+
+/// Push ATX headings outside code `by` levels deeper.
+fn demote_headings(text: &str, by: usize) -> String {
+    if by == 0 {
+        return text.to_string();
+    }
+    let prose: std::collections::HashSet<usize> = links::prose_lines(text).map(|(i, _)| i).collect();
+    let mut out = String::with_capacity(text.len() + by * 8);
+    for (i, segment) in text.split_inclusive('\n').enumerate() {
+        match heading_level(segment.trim_end_matches(['\n', '\r'])).filter(|_| prose.contains(&i)) {
+            Some(level) => {
+                let indent = segment.len() - segment.trim_start_matches(' ').len();
+                let added = (level + by).min(6) - level;
+                out.push_str(&segment[..indent]);
+                out.push_str(&"#".repeat(added));
+                out.push_str(&segment[indent..]);
+            }
+            None => out.push_str(segment),
+        }
+    }
+    out
+}
+
+/// `docs` in order, as `(path, content)`. Only the first document's front
+/// matter is kept, at the very top; the others' would read as rules and
+/// text in the middle of the merged note.
+pub fn join(docs: &[(&Path, &str)], options: &JoinOptions, target: &Path) -> String {
+    let mut out = String::new();
+    for (i, &(path, text)) in docs.iter().enumerate() {
+        let body = match frontmatter::split(text) {
+            Some((_, body)) => {
+                if i == 0 {
+                    out.push_str(&text[..text.len() - body.len()]);
+                }
+                body
+            }
+            None => text,
+        };
+        let (body, _) = links::rebase(body, path, target);
+        let body = demote_headings(&body, options.demote);
+        let body = body.trim_matches(['\n', '\r']);
+        if i > 0 {
+            out.push_str(match options.separator {
+                JoinSeparator::Rule => "\n\n---\n\n",
+                _ => "\n\n",
+            });
+        }
+        if options.separator == JoinSeparator::Heading {
+            let name = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+            out.push_str(&format!("# {}\n\n", name));
+        }
+        out.push_str(body);
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_with_headings_demoted_and_links_rebased() {
+        let a = "---\ntitle: A\n---\n# Intro\n![x](img/x.png)\n";
+        let b = "---\ntags: [b]\n---\n# Two\n```\n# code\n```\n###### Deep\n[a](../notes/a.md)\n";
+        let docs = [(Path::new("/n/notes/a.md"), a), (Path::new("/n/other/b.md"), b)];
+        let options = JoinOptions { separator: JoinSeparator::Heading, demote: 1, target_path: None };
+        assert_eq!(
+            join(&docs, &options, Path::new("/n/notes/all.md")),
+            "---\ntitle: A\n---\n# a\n\n## Intro\n![x](img/x.png)\n\n# b\n\n## Two\n```\n# code\n```\n###### Deep\n[a](a.md)\n"
+        );
+
+        let options = JoinOptions::default();
+        assert_eq!(
+            join(&[(Path::new("/n/a.md"), "one\n\n"), (Path::new("/n/b.md"), "two")], &options, Path::new("/n/a.md")),
+            "one\n\n---\n\ntwo\n"
+        );
+    }
+}
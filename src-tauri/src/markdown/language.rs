@@ -0,0 +1,194 @@
+//! Guess the language a note is written in, so the editor can point the
+//! spell checker at the right dictionary. Scripts with their own alphabet
+//! decide directly; Latin-script text is scored against small trigram
+//! profiles of the common European languages. Only a bounded prefix of the
+//! prose is sampled, so cost doesn't grow with the document.
+
+use std::collections::HashMap;
+use serde::Serialize;
+
+use super::{fenced_blocks, frontmatter};
+
+/// Characters of prose sampled, front matter and code excluded.
+const SAMPLE_CHARS: usize = 4000;
+/// Fewer letters than this is too little to tell languages apart.
+const MIN_LETTERS: usize = 20;
+/// Letters at which a clear margin counts with full confidence.
+const FULL_CONFIDENCE_LETTERS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DetectedLanguage {
+    /// ISO 639-1 code, or `und` when the sample is too short or unknown.
+    pub lang: String,
+    /// 0..1; low for short samples and close calls.
+    pub confidence: f32,
+}
+
+/// Most frequent trigrams per language, most common first. Word boundaries
+/// are spaces.
+const PROFILES: &[(&str, &[&str])] = &[
+    ("en", &[
+        " th", "the", "he ", "and", " an", "nd ", " of", "of ", " to", "to ", "ing", "ng ", " in", "in ", "is ",
+        "ion", " is", "tio", "on ", "at ", "ed ", "er ", "es ", "re ", "ent", "hat", " ha", "tha", " fo", "for",
+        "or ", " wi", "wit", "ith", "th ", "as ", "her", " be", "you", "ly ",
+    ]),
+    ("de", &[
+        "en ", "er ", " de", "der", "ie ", "ich", "sch", "ein", "che", "die", " di", "den", "und", " un", "nd ",
+        "ch ", " ei", "in ", "te ", "cht", "gen", "ung", "ine", " zu", "ten", "es ", "end", "ist", " is", "st ",
+        "das", " da", "nde", "ver", " ve", "eit", "auf", " au", "mit", " mi",
+    ]),
+    ("fr", &[
+        "es ", " de", "de ", "le ", " le", "ent", "nt ", "la ", " la", "ion", "re ", " pa", "e d", "ne ", "les",
+        " co", "our", "on ", "tio", "que", " qu", "ue ", " et", "et ", "men", "ait", " po", "est", " es", "ans",
+        " un", "une", "des", " da", "dan", "par", "pou", "ur ", "e l", "s d",
+    ]),
+    ("es", &[
+        "de ", " de", "os ", "la ", " la", "el ", " el", "es ", " qu", "que", "ue ", "en ", " en", "ent", "as ",
+        "ión", "ció", "con", " co", "ado", "los", " lo", "nte", "par", " pa", "ra ", "est", "ar ", "del", "aci",
+        "ien", " se", "do ", "una", " un", "por", " po", "er ", "men", "on ",
+    ]),
+    ("it", &[
+        "di ", " di", "che", " ch", "la ", " la", "to ", "re ", "el ", "one", " co", "del", "ell", "lla", "ent",
+        "per", " pe", "on ", "le ", "ato", "no ", "zio", "ion", "are", "e d", "ne ", "nte", " in", "tà ", "con",
+        "i d", " de", "o d", "ri ", "ti ", "men", "ta ", " un", "gli", " il",
+    ]),
+    ("pt", &[
+        "de ", " de", "os ", "que", " qu", "ue ", "do ", " do", "ão ", "ção", "da ", " da", "ent", " co", "com",
+        "es ", " pa", "ara", "par", "ra ", "nte", " se", "em ", "men", "as ", "um ", " um", "uma", "est", "o d",
+        "a d", "con", " e ", "não", "ado", "ica", "por", " po", "dos", "ser",
+    ]),
+    ("nl", &[
+        "en ", "de ", " de", "an ", "van", " va", "et ", "het", " he", "een", " ee", "n d", "er ", "aar", "ij ",
+        "ver", " ve", "oor", "ten", " in", "in ", "ing", "ng ", "te ", "nde", "and", "sch", "den", "ie ", "die",
+        " di", "gen", " ge", "eer", "dat", " da", "at ", "is ", " is", "ede",
+    ]),
+    ("pl", &[
+        "ie ", "nie", " ni", "ch ", " pr", "prz", "rze", "ych", "ego", "go ", "ani", " na", "na ", " po", "wie",
+        "owa", "ia ", "że ", "ów ", "est", "jes", " je", "się", " si", "ię ", "dzi", " w ", "cze", "ost", "ski",
+        "kie", "nia", "ać ", "ej ", "em ", " za", "ny ", "aby", "sta", " do",
+    ]),
+];
+
+/// Prose from the start of the document, code and front matter left out,
+/// lowercased with everything but letters turned into single spaces.
+fn sample(text: &str) -> String {
+    let body_start = match frontmatter::split(text) {
+        Some((_, body)) => text[..text.len() - body.len()].matches('\n').count(),
+        None => 0,
+    };
+    let fences = fenced_blocks(text);
+    let mut out = String::from(" ");
+    let mut taken = 0;
+    for (i, line) in text.lines().enumerate() {
+        if i < body_start || fences.iter().any(|&(open, close)| open <= i && i <= close) {
+            continue;
+        }
+        for word in line.split_whitespace() {
+            if word.contains("://") || word.starts_with('`') {
+                continue;
+            }
+            for ch in word.chars() {
+                if ch.is_alphabetic() {
+                    out.extend(ch.to_lowercase());
+                } else if !out.ends_with(' ') {
+                    out.push(' ');
+                }
+            }
+            if !out.ends_with(' ') {
+                out.push(' ');
+            }
+            taken += word.len() + 1;
+        }
+        if taken >= SAMPLE_CHARS {
+            break;
+        }
+    }
+    out
+}
+
+/// Language of a script that only one supported language (or family) uses.
+fn by_script(letters: &[char]) -> Option<&'static str> {
+    let count = |f: fn(&char) -> bool| letters.iter().filter(|c| f(c)).count();
+    let kana = count(|c| matches!(*c, '\u{3040}'..='\u{30ff}'));
+    let han = count(|c| matches!(*c, '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}'));
+    let hangul = count(|c| matches!(*c, '\u{ac00}'..='\u{d7af}' | '\u{1100}'..='\u{11ff}'));
+    let cyrillic = count(|c| matches!(*c, '\u{0400}'..='\u{04ff}'));
+    let greek = count(|c| matches!(*c, '\u{0370}'..='\u{03ff}'));
+    let half = letters.len() / 2;
+    if kana > 0 && kana + han > half {
+        Some("ja")
+    } else if han > half {
+        Some("zh")
+    } else if hangul > half {
+        Some("ko")
+    } else if cyrillic > half {
+        Some("ru")
+    } else if greek > half {
+        Some("el")
+    } else {
+        None
+    }
+}
+
+pub fn detect(text: &str) -> DetectedLanguage {
+    let sample = sample(text);
+    let letters: Vec<char> = sample.chars().filter(|c| *c != ' ').collect();
+    if letters.len() < MIN_LETTERS {
+        return DetectedLanguage { lang: "und".to_string(), confidence: 0.0 };
+    }
+    let size = (letters.len() as f32 / FULL_CONFIDENCE_LETTERS as f32).min(1.0);
+    if let Some(lang) = by_script(&letters) {
+        return DetectedLanguage { lang: lang.to_string(), confidence: 0.5 + 0.5 * size };
+    }
+
+    let chars: Vec<char> = sample.chars().collect();
+    let mut trigrams: HashMap<String, usize> = HashMap::new();
+    for window in chars.windows(3) {
+        *trigrams.entry(window.iter().collect()).or_default() += 1;
+    }
+    let mut scores: Vec<(&str, f32)> = PROFILES
+        .iter()
+        .map(|(lang, profile)| {
+            let score = profile
+                .iter()
+                .enumerate()
+                .map(|(rank, t)| trigrams.get(*t).copied().unwrap_or(0) as f32 * (profile.len() - rank) as f32)
+                .sum();
+            (*lang, score)
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let (lang, best) = scores[0];
+    if best == 0.0 {
+        return DetectedLanguage { lang: "und".to_string(), confidence: 0.0 };
+    }
+    let margin = (best - scores[1].1) / best;
+    DetectedLanguage { lang: lang.to_string(), confidence: (margin * 2.0).min(1.0) * size }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_common_languages_apart() {
+        let en = "The meeting notes for this week are in the shared folder, and the plan is to finish the \
+                  review of the design with the whole team before Friday.";
+        let pl = "Notatki ze spotkania w tym tygodniu są w folderze, a plan jest taki, żeby przejrzeć projekt \
+                  z całym zespołem przed piątkiem i nie zostawiać tego na później.";
+        let de = "Die Notizen der Besprechung liegen im gemeinsamen Ordner, und wir wollen die Überprüfung \
+                  des Entwurfs mit dem ganzen Team noch vor Freitag abschließen.";
+        assert_eq!(detect(en).lang, "en");
+        assert_eq!(detect(pl).lang, "pl");
+        assert_eq!(detect(de).lang, "de");
+        assert!(detect(en).confidence > 0.2);
+        assert_eq!(detect("会议记录在共享文件夹中，我们计划在星期五之前完成设计评审。").lang, "zh");
+    }
+
+    #[test]
+    fn short_text_and_code_give_no_guess() {
+        assert_eq!(detect("Hi there"), DetectedLanguage { lang: "und".to_string(), confidence: 0.0 });
+        let code = "---\ntitle: Il piano\n---\n```\nlet the_value = compute(the_input);\n```\nOk\n";
+        assert_eq!(detect(code).lang, "und");
+    }
+}
@@ -0,0 +1,390 @@
+//! Converting links between inline `[text](url "title")` and reference
+//! `[text][label]` style. Links in code spans and fenced blocks are skipped;
+//! images are left as they are.
+
+use std::collections::{HashMap, HashSet};
+
+use super::fenced_blocks;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStyle {
+    Inline,
+    Reference,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Definition {
+    label: String,
+    url: String,
+    title: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Link<'a> {
+    Inline { text: &'a str, url: &'a str, title: Option<&'a str> },
+    /// `[text][label]`, `[text][]` (label = text) and `[text]`.
+    Reference { text: &'a str, label: &'a str },
+}
+
+/// Reference labels match case-insensitively with whitespace collapsed.
+fn normalize_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// `[label]: url "title"` (up to three spaces of indent).
+fn parse_definition(line: &str) -> Option<Definition> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = line[indent..].strip_prefix('[')?;
+    let close = rest.find("]:")?;
+    let label = &rest[..close];
+    if label.trim().is_empty() || label.contains('[') {
+        return None;
+    }
+    let after = rest[close + 2..].trim();
+    let (url, title) = match after.find(char::is_whitespace) {
+        Some(i) => (&after[..i], Some(after[i..].trim())),
+        None => (after, None),
+    };
+    if url.is_empty() {
+        return None;
+    }
+    let title = match title {
+        Some(t) if t.len() >= 2 && (t.starts_with('"') || t.starts_with('\'') || t.starts_with('(')) => {
+            Some(t[1..t.len() - 1].to_string())
+        }
+        Some(_) => return None,
+        None => None,
+    };
+    Some(Definition {
+        label: label.to_string(),
+        url: url.trim_start_matches('<').trim_end_matches('>').to_string(),
+        title,
+    })
+}
+
+/// Index just past the `]` matching the `[` at `open`.
+fn matching_bracket(s: &str, open: usize) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Parse `(url "title")` starting at `open` (the `(`); returns the end index.
+fn parse_destination(s: &str, open: usize) -> Option<(usize, &str, Option<&str>)> {
+    let bytes = s.as_bytes();
+    let mut i = open + 1;
+    while i < bytes.len() && bytes[i] == b' ' {
+        i += 1;
+    }
+    let url_start = i;
+    let url_end;
+    if bytes.get(i) == Some(&b'<') {
+        let close = s[i..].find('>')? + i;
+        url_end = close + 1;
+        i = close + 1;
+    } else {
+        let mut depth = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 1,
+                b'(' => depth += 1,
+                b')' if depth == 0 => break,
+                b')' => depth -= 1,
+                b' ' | b'\t' => break,
+                _ => {}
+            }
+            i += 1;
+        }
+        url_end = i.min(bytes.len());
+    }
+    let url = &s[url_start..url_end];
+    while i < bytes.len() && bytes[i] == b' ' {
+        i += 1;
+    }
+    let mut title = None;
+    if let Some(&q) = bytes.get(i) {
+        let close_q = match q {
+            b'"' => Some(b'"'),
+            b'\'' => Some(b'\''),
+            b'(' => Some(b')'),
+            _ => None,
+        };
+        if let Some(cq) = close_q {
+            let rel = s[i + 1..].bytes().position(|b| b == cq)?;
+            title = Some(&s[i + 1..i + 1 + rel]);
+            i += rel + 2;
+            while i < bytes.len() && bytes[i] == b' ' {
+                i += 1;
+            }
+        }
+    }
+    if bytes.get(i) != Some(&b')') {
+        return None;
+    }
+    Some((i + 1, url, title))
+}
+
+/// Call `f` for every link on `line` outside code spans; a `Some` return
+/// replaces the link's source text.
+fn rewrite_line(line: &str, f: &mut impl FnMut(&Link) -> Option<String>) -> String {
+    let bytes = line.as_bytes();
+    let mut out = String::with_capacity(line.len());
+    let mut last = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => {
+                let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+                let fence = &line[i..i + run];
+                i = match line[i + run..].find(fence) {
+                    Some(rel) => i + run + rel + run,
+                    None => i + run,
+                };
+            }
+            b'[' => {
+                let image = i > 0 && bytes[i - 1] == b'!';
+                let Some(text_end) = matching_bracket(line, i) else {
+                    i += 1;
+                    continue;
+                };
+                let text = &line[i + 1..text_end - 1];
+                let (end, link) = match bytes.get(text_end) {
+                    Some(b'(') => match parse_destination(line, text_end) {
+                        Some((end, url, title)) => (end, Link::Inline { text, url, title }),
+                        None => {
+                            i = text_end;
+                            continue;
+                        }
+                    },
+                    Some(b'[') => match matching_bracket(line, text_end) {
+                        Some(label_end) => {
+                            let label = &line[text_end + 1..label_end - 1];
+                            (label_end, Link::Reference { text, label: if label.is_empty() { text } else { label } })
+                        }
+                        None => (text_end, Link::Reference { text, label: text }),
+                    },
+                    _ => (text_end, Link::Reference { text, label: text }),
+                };
+                if let Some(replacement) = (!image).then(|| f(&link)).flatten() {
+                    out.push_str(&line[last..i]);
+                    out.push_str(&replacement);
+                    last = end;
+                }
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    out.push_str(&line[last.min(line.len())..]);
+    out
+}
+
+fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { "link".to_string() } else { slug.chars().take(40).collect() }
+}
+
+fn format_definition(def: &Definition) -> String {
+    match &def.title {
+        Some(t) => format!("[{}]: {} \"{}\"", def.label, def.url, t),
+        None => format!("[{}]: {}", def.label, def.url),
+    }
+}
+
+fn inline_link(text: &str, url: &str, title: Option<&str>) -> String {
+    match title {
+        Some(t) => format!("[{}]({} \"{}\")", text, url, t),
+        None => format!("[{}]({})", text, url),
+    }
+}
+
+pub fn convert(text: &str, to: LinkStyle) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let fences = fenced_blocks(text);
+    let in_fence = |i: usize| fences.iter().any(|&(open, close)| open <= i && i <= close);
+    let def_lines: HashSet<usize> = (0..lines.len())
+        .filter(|&i| !in_fence(i) && parse_definition(lines[i]).is_some())
+        .collect();
+    let mut defs: Vec<Definition> = def_lines.iter().filter_map(|&i| parse_definition(lines[i])).collect();
+    defs.sort_by_key(|d| normalize_label(&d.label));
+    let by_label: HashMap<String, Definition> = defs.iter().map(|d| (normalize_label(&d.label), d.clone())).collect();
+
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    match to {
+        LinkStyle::Reference => {
+            let mut used: HashSet<String> = by_label.keys().cloned().collect();
+            let mut reuse: HashMap<(String, Option<String>), String> = defs
+                .iter()
+                .map(|d| ((d.url.clone(), d.title.clone()), d.label.clone()))
+                .collect();
+            let mut added: Vec<Definition> = Vec::new();
+            for (i, line) in lines.iter().enumerate() {
+                if in_fence(i) || def_lines.contains(&i) {
+                    out.push(line.to_string());
+                    continue;
+                }
+                out.push(rewrite_line(line, &mut |link| {
+                    let Link::Inline { text, url, title } = link else { return None };
+                    let url = url.trim_start_matches('<').trim_end_matches('>');
+                    let key = (url.to_string(), title.map(str::to_string));
+                    let label = reuse.entry(key).or_insert_with(|| {
+                        let base = slug(text);
+                        let mut label = base.clone();
+                        let mut n = 2;
+                        while used.contains(&normalize_label(&label)) {
+                            label = format!("{}-{}", base, n);
+                            n += 1;
+                        }
+                        used.insert(normalize_label(&label));
+                        added.push(Definition { label: label.clone(), url: url.to_string(), title: title.map(str::to_string) });
+                        label
+                    });
+                    Some(if normalize_label(label) == normalize_label(text) {
+                        format!("[{}][]", text)
+                    } else {
+                        format!("[{}][{}]", text, label)
+                    })
+                }));
+            }
+            if !added.is_empty() {
+                while out.last().is_some_and(|l| l.trim().is_empty()) {
+                    out.pop();
+                }
+                let ends_with_defs = out.last().is_some_and(|l| parse_definition(l).is_some());
+                if !out.is_empty() && !ends_with_defs {
+                    out.push(String::new());
+                }
+                out.extend(added.iter().map(format_definition));
+            }
+        }
+        LinkStyle::Inline => {
+            let mut converted: HashSet<String> = HashSet::new();
+            for (i, line) in lines.iter().enumerate() {
+                if in_fence(i) || def_lines.contains(&i) {
+                    out.push(line.to_string());
+                    continue;
+                }
+                out.push(rewrite_line(line, &mut |link| {
+                    let Link::Reference { text, label } = link else { return None };
+                    let key = normalize_label(label);
+                    let def = by_label.get(&key)?;
+                    converted.insert(key);
+                    Some(inline_link(text, &def.url, def.title.as_deref()))
+                }));
+            }
+            // Drop definitions nothing refers to any more (images may still).
+            let still_used: HashSet<String> = out
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !in_fence(*i) && !def_lines.contains(i))
+                .flat_map(|(_, l)| {
+                    let mut labels = Vec::new();
+                    let _ = rewrite_line(&l.replace("![", "["), &mut |link| {
+                        if let Link::Reference { label, .. } = link {
+                            labels.push(normalize_label(label));
+                        }
+                        None
+                    });
+                    labels
+                })
+                .collect();
+            let mut kept = Vec::with_capacity(out.len());
+            for (i, line) in out.into_iter().enumerate() {
+                let drop = def_lines.contains(&i)
+                    && parse_definition(&line)
+                        .map(|d| {
+                            let key = normalize_label(&d.label);
+                            converted.contains(&key) && !still_used.contains(&key)
+                        })
+                        .unwrap_or(false);
+                if !drop {
+                    kept.push(line);
+                }
+            }
+            while kept.last().is_some_and(|l| l.trim().is_empty()) {
+                kept.pop();
+            }
+            out = kept;
+        }
+    }
+
+    let mut result = out.join("\n");
+    if text.ends_with('\n') && !result.is_empty() {
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_to_reference_dedupes_and_reuses_labels() {
+        let doc = "See [Docs](https://d.io \"Home\") and [docs](https://d.io \"Home\").\nAlso [Docs](https://other.io).\n";
+        assert_eq!(
+            convert(doc, LinkStyle::Reference),
+            "See [Docs][] and [docs][].\nAlso [Docs][docs-2].\n\n[docs]: https://d.io \"Home\"\n[docs-2]: https://other.io\n"
+        );
+    }
+
+    #[test]
+    fn reuses_existing_definitions_and_skips_code() {
+        let doc = "[a](https://x.io) `[b](https://y.io)` ![i](i.png)\n\n```\n[c](https://z.io)\n```\n\n[site]: https://x.io\n";
+        assert_eq!(
+            convert(doc, LinkStyle::Reference),
+            "[a][site] `[b](https://y.io)` ![i](i.png)\n\n```\n[c](https://z.io)\n```\n\n[site]: https://x.io\n"
+        );
+    }
+
+    #[test]
+    fn reference_to_inline_keeps_titles_and_drops_spent_definitions() {
+        let doc = "Go [here][Site] or [site][] or [Site].\n![logo][img]\n\n[site]: https://x.io 'The site'\n[img]: logo.png\n";
+        assert_eq!(
+            convert(doc, LinkStyle::Inline),
+            "Go [here](https://x.io \"The site\") or [site](https://x.io \"The site\") or [Site](https://x.io \"The site\").\n![logo][img]\n\n[img]: logo.png\n"
+        );
+    }
+
+    #[test]
+    fn round_trip_is_stable() {
+        let doc = "A [one](https://1.io) and [two](https://2.io \"T\").\n";
+        let reference = convert(doc, LinkStyle::Reference);
+        assert_eq!(convert(&reference, LinkStyle::Inline), doc);
+    }
+
+    #[test]
+    fn unknown_references_and_plain_brackets_are_untouched() {
+        let doc = "A [note] and [x][missing] and [ ] box.\n";
+        assert_eq!(convert(doc, LinkStyle::Inline), doc);
+        assert_eq!(convert(doc, LinkStyle::Reference), doc);
+    }
+}
@@ -0,0 +1,331 @@
+//! Mermaid blocks in a document: where they are, which diagram type they
+//! declare and a cheap structural check. Rendering (and the real parser)
+//! stays in the webview; this only catches what can be seen from the text,
+//! such as an unknown type, an unclosed fence or unbalanced blocks.
+
+use super::{fenced_blocks, frontmatter};
+
+/// Diagram keywords Mermaid accepts on the first line.
+pub const DIAGRAM_TYPES: &[&str] = &[
+    "flowchart",
+    "graph",
+    "sequenceDiagram",
+    "classDiagram",
+    "classDiagram-v2",
+    "stateDiagram",
+    "stateDiagram-v2",
+    "erDiagram",
+    "journey",
+    "gantt",
+    "pie",
+    "quadrantChart",
+    "requirementDiagram",
+    "gitGraph",
+    "C4Context",
+    "C4Container",
+    "C4Component",
+    "C4Dynamic",
+    "C4Deployment",
+    "mindmap",
+    "timeline",
+    "zenuml",
+    "sankey-beta",
+    "xychart-beta",
+    "block-beta",
+    "packet-beta",
+    "kanban",
+    "architecture-beta",
+    "radar-beta",
+];
+
+/// Mermaid major versions the webview bundle ships, oldest first. Keep in
+/// step with `src/utils/mermaid-versions.ts`.
+pub const BUNDLED_VERSIONS: &[&str] = &["11"];
+pub const DEFAULT_VERSION: &str = "11";
+
+/// Front-matter key a document pins its Mermaid version with.
+pub const VERSION_KEY: &str = "mermaid_version";
+
+/// Sequence diagram statements that open a block closed by `end`.
+const SEQUENCE_BLOCKS: &[&str] = &["loop", "alt", "opt", "par", "critical", "break", "rect", "box"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MermaidBlock {
+    /// 1-based line of the opening fence.
+    pub line: usize,
+    /// 1-based last line of the block, the closing fence when there is one.
+    pub end_line: usize,
+    pub closed: bool,
+    pub source: String,
+}
+
+fn opens_mermaid(line: &str, marker: &str) -> bool {
+    let Some(info) = line.trim_start().strip_prefix(marker) else { return false };
+    let info = info.trim_start_matches(marker.chars().next().unwrap_or('`')).trim_start();
+    info.strip_prefix("mermaid")
+        .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '-'))
+}
+
+/// Every ```` ```mermaid ````, `~~~mermaid` and `:::mermaid` block, in
+/// document order. Mermaid-looking text inside other code blocks is ignored.
+pub fn extract(text: &str) -> Vec<MermaidBlock> {
+    let lines: Vec<&str> = text.lines().collect();
+    let fences = fenced_blocks(text);
+    let mut blocks = Vec::new();
+    for &(open, close) in &fences {
+        let marker = if lines[open].trim_start().starts_with('~') { "~~~" } else { "```" };
+        if !opens_mermaid(lines[open], marker) {
+            continue;
+        }
+        let closed = close > open && lines[close].trim_start().starts_with(marker);
+        let end = if closed { close } else { close + 1 };
+        blocks.push(MermaidBlock {
+            line: open + 1,
+            end_line: close + 1,
+            closed,
+            source: lines[open + 1..end].join("\n"),
+        });
+    }
+    let in_fence = |i: usize| fences.iter().any(|&(open, close)| open <= i && i <= close);
+    let mut i = 0;
+    while i < lines.len() {
+        if in_fence(i) || !opens_mermaid(lines[i], ":::") {
+            i += 1;
+            continue;
+        }
+        let close = (i + 1..lines.len()).find(|&j| lines[j].trim() == ":::");
+        let end = close.unwrap_or(lines.len());
+        blocks.push(MermaidBlock {
+            line: i + 1,
+            end_line: close.map_or(lines.len(), |c| c + 1),
+            closed: close.is_some(),
+            source: lines[i + 1..end].join("\n"),
+        });
+        i = end + 1;
+    }
+    blocks.sort_by_key(|b| b.line);
+    blocks
+}
+
+/// Replace each block in `images` (`(block, markdown target)`) with an
+/// image reference, keeping everything else byte for byte.
+pub fn replace_with_images(text: &str, images: &[(&MermaidBlock, String)]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut skip_until = 0;
+    for (i, segment) in text.split_inclusive('\n').enumerate() {
+        let line_no = i + 1;
+        if line_no <= skip_until {
+            continue;
+        }
+        if let Some((block, target)) = images.iter().find(|(b, _)| b.line == line_no) {
+            let alt = detect_type(&block.source).unwrap_or("");
+            let eol = if segment.ends_with("\r\n") { "\r\n" } else { "\n" };
+            out.push_str(&format!("![{}]({})", alt, target));
+            let last = text.split_inclusive('\n').nth(block.end_line - 1).unwrap_or("");
+            if last.ends_with('\n') {
+                out.push_str(eol);
+            }
+            skip_until = block.end_line;
+            continue;
+        }
+        out.push_str(segment);
+    }
+    out
+}
+
+/// Lines that carry diagram statements: no blank lines, `%%` comments or
+/// directives, and no leading `---` config block.
+fn statements(source: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = source.lines().map(str::trim).collect();
+    if lines.first() == Some(&"---") {
+        if let Some(end) = lines[1..].iter().position(|l| *l == "---") {
+            lines.drain(..end + 2);
+        }
+    }
+    lines.into_iter().filter(|l| !l.is_empty() && !l.starts_with("%%")).collect()
+}
+
+/// The declared diagram type, from the first statement's keyword.
+pub fn detect_type(source: &str) -> Option<&'static str> {
+    let first = statements(source).into_iter().next()?;
+    let keyword = first.split(|c: char| c.is_whitespace() || c == ';').next()?;
+    DIAGRAM_TYPES.iter().copied().find(|t| *t == keyword)
+}
+
+/// `()` and `[]` must close on the same line in flowcharts. A stray closer
+/// is allowed since `id>text]` is a node shape.
+fn check_line_brackets(line: &str) -> Result<(), char> {
+    let mut stack = Vec::new();
+    let mut in_quote = false;
+    for c in line.chars() {
+        match c {
+            '"' => in_quote = !in_quote,
+            _ if in_quote => {}
+            '(' | '[' | '{' => stack.push(c),
+            ')' | ']' | '}' => {
+                let want = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                match stack.last() {
+                    Some(&top) if top == want => {
+                        stack.pop();
+                    }
+                    Some(&top) => return Err(top),
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    stack.first().map_or(Ok(()), |&c| Err(c))
+}
+
+fn first_word(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or("")
+}
+
+/// Cheap structural validation. `Err` carries a short description.
+pub fn check(block: &MermaidBlock) -> Result<(), String> {
+    if !block.closed {
+        return Err("the block is never closed".to_string());
+    }
+    let Some(kind) = detect_type(&block.source) else {
+        return Err("unknown or missing diagram type".to_string());
+    };
+    let body: Vec<&str> = statements(&block.source).into_iter().skip(1).collect();
+    if body.is_empty() && !matches!(kind, "gitGraph" | "mindmap") {
+        return Err("the diagram has no content".to_string());
+    }
+    match kind {
+        "flowchart" | "graph" => {
+            let mut depth = 0i32;
+            for line in &body {
+                if let Err(c) = check_line_brackets(line) {
+                    return Err(format!("unclosed `{}` in: {}", c, line));
+                }
+                match first_word(line) {
+                    "subgraph" => depth += 1,
+                    "end" => depth -= 1,
+                    _ => {}
+                }
+                if depth < 0 {
+                    return Err("`end` without a matching `subgraph`".to_string());
+                }
+            }
+            if depth > 0 {
+                return Err("`subgraph` without a matching `end`".to_string());
+            }
+        }
+        "sequenceDiagram" => {
+            let mut depth = 0i32;
+            for line in &body {
+                let word = first_word(line);
+                if SEQUENCE_BLOCKS.contains(&word) {
+                    depth += 1;
+                } else if word == "end" {
+                    depth -= 1;
+                    if depth < 0 {
+                        return Err("`end` without an open block".to_string());
+                    }
+                }
+            }
+            if depth > 0 {
+                return Err("a loop/alt/opt block is missing its `end`".to_string());
+            }
+        }
+        "classDiagram" | "classDiagram-v2" | "stateDiagram" | "stateDiagram-v2" => {
+            let open = body.iter().map(|l| l.matches('{').count()).sum::<usize>();
+            let close = body.iter().map(|l| l.matches('}').count()).sum::<usize>();
+            if open != close {
+                return Err(format!("{} `{{` but {} `}}`", open, close));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The bundled version `requested` names: `11`, `v11` and `11.4.1` all
+/// select major version 11.
+pub fn bundled_version(requested: &str) -> Option<&'static str> {
+    let requested = requested.trim().trim_start_matches(['v', 'V']);
+    let major = requested.split('.').next().unwrap_or(requested);
+    BUNDLED_VERSIONS.iter().copied().find(|v| *v == major)
+}
+
+/// `mermaid_version` from the document's front matter, if it pins one.
+pub fn pinned_version(text: &str) -> Option<String> {
+    let (raw, _) = frontmatter::split(text)?;
+    frontmatter::get_key(raw, VERSION_KEY).filter(|v| !v.is_empty())
+}
+
+/// Version to render and check `text` with: its pin, else `setting`. A
+/// version that isn't bundled is an error, never a silent fallback.
+pub fn version_for(text: &str, setting: &str) -> Result<&'static str, String> {
+    let (requested, source) = match pinned_version(text) {
+        Some(pin) => (pin, "pinned in front matter"),
+        None => (setting.to_string(), "selected in settings"),
+    };
+    bundled_version(&requested).ok_or_else(|| {
+        format!("Mermaid {} ({}) is not bundled; available: {}", requested, source, BUNDLED_VERSIONS.join(", "))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_all_formats_and_skips_other_code() {
+        let doc = "```mermaid\ngraph TD\nA-->B\n```\n~~~\n```mermaid\nnope\n```\n~~~\n:::mermaid\npie\n\"a\" : 1\n:::\n~~~mermaid\nsequenceDiagram\n";
+        let blocks = extract(doc);
+        let summary: Vec<(usize, bool, Option<&str>)> =
+            blocks.iter().map(|b| (b.line, b.closed, detect_type(&b.source))).collect();
+        assert_eq!(summary, [(1, true, Some("graph")), (10, true, Some("pie")), (14, false, Some("sequenceDiagram"))]);
+    }
+
+    #[test]
+    fn type_detection_skips_config_and_comments() {
+        assert_eq!(detect_type("---\ntitle: x\n---\n%% note\nflowchart LR\nA-->B"), Some("flowchart"));
+        assert_eq!(detect_type("%%{init: {}}%%\nstateDiagram-v2\n[*] --> A"), Some("stateDiagram-v2"));
+        assert_eq!(detect_type("flowchartt LR\nA-->B"), None);
+    }
+
+    #[test]
+    fn structural_checks_flag_common_breakage() {
+        let block = |source: &str| MermaidBlock { line: 1, end_line: 3, closed: true, source: source.to_string() };
+        assert!(check(&block("flowchart LR\nA[Start] --> B>Flag] --> C(\"(quoted\")")).is_ok());
+        assert!(check(&block("flowchart LR\nA[Start --> B")).is_err());
+        assert!(check(&block("flowchart LR\nsubgraph one\nA-->B\n")).is_err());
+        assert!(check(&block("sequenceDiagram\nloop Every minute\nA->>B: ping (x\nend")).is_ok());
+        assert!(check(&block("sequenceDiagram\nalt ok\nA->>B: hi")).is_err());
+        assert!(check(&block("classDiagram\nclass A {\n+int x\n")).is_err());
+        assert!(check(&block("erDiagram\nA ||--o{ B : has")).is_ok());
+        assert!(check(&block("nonsense")).is_err());
+        assert!(check(&MermaidBlock { line: 1, end_line: 2, closed: false, source: "graph TD\nA".into() }).is_err());
+    }
+
+    #[test]
+    fn the_front_matter_pin_beats_the_setting() {
+        assert_eq!(bundled_version("v11.4.1"), Some("11"));
+        assert_eq!(version_for("# No pin", "11"), Ok("11"));
+        assert_eq!(version_for("---\nmermaid_version: \"11.2\"\n---\n", "9"), Ok("11"));
+        let err = version_for("---\nmermaid_version: 8\n---\n", "11").unwrap_err();
+        assert!(err.contains("Mermaid 8 (pinned in front matter) is not bundled"), "{}", err);
+        assert!(version_for("", "12").is_err());
+    }
+
+    #[test]
+    fn blocks_are_swapped_for_image_references() {
+        let doc = "# Doc\r\n```mermaid\r\ngraph TD\r\nA-->B\r\n```\r\ntext\r\n:::mermaid\r\npie\r\n:::";
+        let blocks = extract(doc);
+        assert_eq!(blocks.iter().map(|b| (b.line, b.end_line)).collect::<Vec<_>>(), [(2, 5), (7, 9)]);
+        let images = vec![(&blocks[0], "assets/diagram-1.svg".to_string()), (&blocks[1], "assets/diagram-2.svg".to_string())];
+        assert_eq!(
+            replace_with_images(doc, &images),
+            "# Doc\r\n![graph](assets/diagram-1.svg)\r\ntext\r\n![pie](assets/diagram-2.svg)"
+        );
+    }
+}
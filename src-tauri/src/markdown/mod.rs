@@ -0,0 +1,86 @@
+//! Pure Markdown text transforms used by export and editing commands.
+//!
+//! Everything here works on source text line by line; nothing renders HTML.
+
+pub mod csv;
+pub mod format;
+pub mod frontmatter;
+pub mod include;
+pub mod invisible;
+pub mod language;
+pub mod join;
+pub mod link_style;
+pub mod mermaid;
+pub mod outline;
+pub mod range;
+pub mod split;
+pub mod stats;
+pub mod tables;
+
+/// Index spans `(open, close)` of fenced code blocks, inclusive. An
+/// unterminated fence runs to the last line.
+pub fn fenced_blocks(text: &str) -> Vec<(usize, usize)> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(usize, &str)> = None;
+    let mut last = 0;
+    for (i, line) in text.lines().enumerate() {
+        last = i;
+        let trimmed = line.trim_start();
+        let marker = if trimmed.starts_with("```") {
+            Some("```")
+        } else if trimmed.starts_with("~~~") {
+            Some("~~~")
+        } else {
+            None
+        };
+        match (open, marker) {
+            (None, Some(m)) => open = Some((i, m)),
+            (Some((start, m)), Some(end)) if m == end => {
+                blocks.push((start, i));
+                open = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((start, _)) = open {
+        blocks.push((start, last));
+    }
+    blocks
+}
+
+/// Level of an ATX heading line (`# Title` is 1), or `None`.
+pub fn heading_level(line: &str) -> Option<usize> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let level = rest.bytes().take_while(|&b| b == b'#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    match rest[level..].chars().next() {
+        None | Some(' ') | Some('\t') => Some(level),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_closed_and_unterminated_fences() {
+        let text = "a\n```\ncode\n```\nb\n~~~\nrest";
+        assert_eq!(fenced_blocks(text), vec![(1, 3), (5, 6)]);
+    }
+
+    #[test]
+    fn recognizes_atx_headings_only() {
+        assert_eq!(heading_level("## Title"), Some(2));
+        assert_eq!(heading_level("#"), Some(1));
+        assert_eq!(heading_level("#hashtag"), None);
+        assert_eq!(heading_level("    # indented code"), None);
+        assert_eq!(heading_level("####### too deep"), None);
+    }
+}
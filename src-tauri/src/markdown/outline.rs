@@ -0,0 +1,289 @@
+//! Heading outline: the table of contents and its JSON/OPML exports for
+//! outliners and mind-mapping tools.
+
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+use super::{fenced_blocks, frontmatter, heading_level};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TocEntry {
+    pub level: usize,
+    pub text: String,
+    /// Same slug the exporters assign (`slugifyHeading` in the frontend).
+    pub anchor: String,
+    /// 1-based line in the document.
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineNode {
+    pub text: String,
+    pub level: usize,
+    pub anchor: String,
+    pub line: usize,
+    pub children: Vec<OutlineNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Outline {
+    /// Front-matter `title`, the root node when present.
+    pub title: Option<String>,
+    pub children: Vec<OutlineNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorLink {
+    /// 1-based line of the link.
+    pub line: usize,
+    /// The `#slug` target as written.
+    pub link: String,
+    pub target_exists: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutlineFormat {
+    Json,
+    Opml,
+}
+
+/// Lowercase ASCII letters/digits joined by hyphens, accents folded, with
+/// `-2`, `-3` suffixes for repeats.
+pub fn slugify(text: &str, seen: &mut HashSet<String>) -> String {
+    let mut base = String::new();
+    for c in text.nfkd().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() || c == '-' {
+            base.push(c);
+        } else if c.is_whitespace() {
+            base.push('-');
+        }
+    }
+    let mut collapsed = String::with_capacity(base.len());
+    for c in base.chars() {
+        if !(c == '-' && collapsed.ends_with('-')) {
+            collapsed.push(c);
+        }
+    }
+    let base = match collapsed.trim_matches('-') {
+        "" => "section".to_string(),
+        s => s.to_string(),
+    };
+    let mut id = base.clone();
+    let mut n = 2;
+    while seen.contains(&id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    seen.insert(id.clone());
+    id
+}
+
+/// Heading text as rendered: closing `#`s, emphasis and code markers
+/// dropped, links reduced to their label.
+fn plain_heading(line: &str, level: usize) -> String {
+    let text = line.trim_start()[level..].trim();
+    let text = text.trim_end_matches('#').trim_end();
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '_' | '`' => {}
+            '\\' => out.extend(chars.next()),
+            ']' if chars.peek() == Some(&'(') => {
+                for c in chars.by_ref() {
+                    if c == ')' {
+                        break;
+                    }
+                }
+            }
+            '[' | ']' => {}
+            _ => out.push(c),
+        }
+    }
+    out.trim().to_string()
+}
+
+/// ATX headings outside front matter and fenced code, in document order.
+pub fn generate_toc(text: &str) -> Vec<TocEntry> {
+    let mut seen = HashSet::new();
+    prose_lines(text)
+        .filter_map(|(i, line)| {
+            let level = heading_level(line)?;
+            let text = plain_heading(line, level);
+            let anchor = slugify(&text, &mut seen);
+            Some(TocEntry { level, text, anchor, line: i + 1 })
+        })
+        .collect()
+}
+
+/// Lines outside front matter and fenced code, with their 0-based index.
+fn prose_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let skip = match frontmatter::split(text) {
+        Some((_, body)) => text[..text.len() - body.len()].matches('\n').count(),
+        None => 0,
+    };
+    let fences = fenced_blocks(text);
+    text.lines()
+        .enumerate()
+        .skip(skip)
+        .filter(move |(i, _)| !fences.iter().any(|&(open, close)| open <= *i && *i <= close))
+}
+
+/// Every `[...](#slug)` link outside code, flagged by whether a heading
+/// produces that slug. Targets are matched after percent-decoding, the way
+/// the browser resolves them in the exports.
+pub fn check_anchor_links(text: &str) -> Vec<AnchorLink> {
+    let anchors: HashSet<String> = generate_toc(text).into_iter().map(|e| e.anchor).collect();
+    let mut out = Vec::new();
+    for (i, line) in prose_lines(text) {
+        let masked = crate::links::mask_code_spans(line);
+        let mut rest = masked.as_str();
+        while let Some(pos) = rest.find("](#") {
+            let target_start = pos + 3;
+            let end = rest[target_start..]
+                .find(|c: char| c == ')' || c.is_whitespace())
+                .map_or(rest.len(), |e| target_start + e);
+            let slug = &rest[target_start..end];
+            let decoded = urlencoding::decode(slug).map(|s| s.into_owned()).unwrap_or_else(|_| slug.to_string());
+            out.push(AnchorLink {
+                line: i + 1,
+                link: format!("#{}", slug),
+                target_exists: anchors.contains(&decoded),
+            });
+            rest = &rest[end..];
+        }
+    }
+    out
+}
+
+/// Nest flat entries: each heading owns the deeper ones that follow it.
+fn nest(entries: &[TocEntry]) -> Vec<OutlineNode> {
+    let mut nodes = Vec::new();
+    let mut i = 0;
+    while i < entries.len() {
+        let entry = &entries[i];
+        let end = entries[i + 1..]
+            .iter()
+            .position(|e| e.level <= entry.level)
+            .map_or(entries.len(), |p| i + 1 + p);
+        nodes.push(OutlineNode {
+            text: entry.text.clone(),
+            level: entry.level,
+            anchor: entry.anchor.clone(),
+            line: entry.line,
+            children: nest(&entries[i + 1..end]),
+        });
+        i = end;
+    }
+    nodes
+}
+
+pub fn outline(text: &str) -> Outline {
+    Outline {
+        title: frontmatter::split(text).and_then(|(raw, _)| frontmatter::get_key(raw, "title")),
+        children: nest(&generate_toc(text)),
+    }
+}
+
+pub fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn write_opml_nodes(out: &mut String, nodes: &[OutlineNode], depth: usize) {
+    for node in nodes {
+        let indent = "  ".repeat(depth);
+        let attrs = format!(
+            "text=\"{}\" type=\"link\" url=\"#{}\"",
+            xml_escape(&node.text),
+            xml_escape(&node.anchor)
+        );
+        if node.children.is_empty() {
+            out.push_str(&format!("{}<outline {}/>\n", indent, attrs));
+        } else {
+            out.push_str(&format!("{}<outline {}>\n", indent, attrs));
+            write_opml_nodes(out, &node.children, depth + 1);
+            out.push_str(&format!("{}</outline>\n", indent));
+        }
+    }
+}
+
+/// OPML 2.0 document; `fallback_title` names it when there is no
+/// front-matter title.
+pub fn to_opml(outline: &Outline, fallback_title: &str) -> String {
+    let title = outline.title.as_deref().unwrap_or(fallback_title);
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    out.push_str(&format!("  <head>\n    <title>{}</title>\n  </head>\n  <body>\n", xml_escape(title)));
+    match &outline.title {
+        Some(root) => {
+            out.push_str(&format!("    <outline text=\"{}\">\n", xml_escape(root)));
+            write_opml_nodes(&mut out, &outline.children, 3);
+            out.push_str("    </outline>\n");
+        }
+        None => write_opml_nodes(&mut out, &outline.children, 2),
+    }
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toc_skips_front_matter_and_code_and_dedupes_anchors() {
+        let doc = "---\ntitle: Notes\n# not a heading\n---\n# Intro\n```\n# code\n```\n## Café *au* lait ##\n# Intro\n";
+        let toc = generate_toc(doc);
+        let summary: Vec<(usize, &str, &str, usize)> =
+            toc.iter().map(|e| (e.level, e.text.as_str(), e.anchor.as_str(), e.line)).collect();
+        assert_eq!(
+            summary,
+            [(1, "Intro", "intro", 5), (2, "Café au lait", "cafe-au-lait", 9), (1, "Intro", "intro-2", 10)]
+        );
+    }
+
+    #[test]
+    fn nests_by_level_and_tolerates_skipped_levels() {
+        let outline = outline("# A\n### A.1\n## A.2\n# B\n[x](y)\n");
+        let shape: Vec<(&str, Vec<&str>)> = outline
+            .children
+            .iter()
+            .map(|n| (n.text.as_str(), n.children.iter().map(|c| c.text.as_str()).collect()))
+            .collect();
+        assert_eq!(shape, [("A", vec!["A.1", "A.2"]), ("B", vec![])]);
+    }
+
+    #[test]
+    fn anchor_links_are_checked_against_heading_slugs() {
+        let doc = "# Intro\nSee [a](#intro), [b](#intro-2) and [c](#caf%C3%A9).\n`[x](#nope)`\n```\n[y](#nope)\n```\n## Intro\n";
+        let links: Vec<(usize, String, bool)> =
+            check_anchor_links(doc).into_iter().map(|l| (l.line, l.link, l.target_exists)).collect();
+        assert_eq!(
+            links,
+            [
+                (2, "#intro".to_string(), true),
+                (2, "#intro-2".to_string(), true),
+                (2, "#caf%C3%A9".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn opml_uses_front_matter_title_as_root_and_escapes() {
+        let opml = to_opml(&outline("---\ntitle: \"R&D\"\n---\n# A <b>\n## [Link](u)\n"), "doc");
+        assert!(opml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">"));
+        assert!(opml.contains("<title>R&amp;D</title>"));
+        assert!(opml.contains(
+            "    <outline text=\"R&amp;D\">\n      <outline text=\"A &lt;b&gt;\" type=\"link\" url=\"#a-b\">\n        <outline text=\"Link\" type=\"link\" url=\"#link\"/>\n      </outline>\n    </outline>\n"
+        ));
+    }
+}
@@ -0,0 +1,95 @@
+//! Cutting a line range out of a document for partial export.
+
+use serde::Deserialize;
+
+use super::{fenced_blocks, heading_level};
+use crate::error::{AppError, AppResult};
+
+/// 1-based, inclusive line range as sent by the editor selection.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineRange {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// The Markdown for `range`, widened so no fenced block is cut in half, and
+/// prefixed with the nearest heading above it when the slice does not start
+/// with one, so the exported fragment keeps its context.
+pub fn slice(markdown: &str, range: LineRange) -> AppResult<String> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    if range.start_line == 0 || range.start_line > range.end_line || range.start_line > lines.len() {
+        return Err(AppError::InvalidInput(format!(
+            "invalid line range {}-{} for a {}-line document",
+            range.start_line,
+            range.end_line,
+            lines.len()
+        )));
+    }
+    let mut start = range.start_line - 1;
+    let mut end = range.end_line.min(lines.len()) - 1;
+
+    let fences = fenced_blocks(markdown);
+    for &(open, close) in &fences {
+        if open <= end && close >= start {
+            start = start.min(open);
+            end = end.max(close);
+        }
+    }
+
+    let in_fence = |i: usize| fences.iter().any(|&(open, close)| open <= i && i <= close);
+    let mut out = String::new();
+    if heading_level(lines[start]).is_none() {
+        if let Some(heading) = (0..start)
+            .rev()
+            .find(|&i| !in_fence(i) && heading_level(lines[i]).is_some())
+        {
+            out.push_str(lines[heading]);
+            out.push_str("\n\n");
+        }
+    }
+    for line in &lines[start..=end] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start_line: usize, end_line: usize) -> LineRange {
+        LineRange { start_line, end_line }
+    }
+
+    #[test]
+    fn prefixes_the_enclosing_heading() {
+        let doc = "# Intro\ntext\n## Setup\nstep one\nstep two\n";
+        assert_eq!(slice(doc, range(4, 5)).unwrap(), "## Setup\n\nstep one\nstep two\n");
+        assert_eq!(slice(doc, range(3, 4)).unwrap(), "## Setup\nstep one\n");
+    }
+
+    #[test]
+    fn widens_to_whole_fenced_blocks() {
+        let doc = "## Code\n```rust\nfn a() {}\n# not a heading\n```\nafter\n";
+        assert_eq!(
+            slice(doc, range(4, 6)).unwrap(),
+            "## Code\n\n```rust\nfn a() {}\n# not a heading\n```\nafter\n"
+        );
+    }
+
+    #[test]
+    fn ignores_comment_lines_inside_fences_when_looking_for_headings() {
+        let doc = "# Real\n```sh\n# comment\n```\nbody\n";
+        assert!(slice(doc, range(5, 5)).unwrap().starts_with("# Real\n\n"));
+    }
+
+    #[test]
+    fn rejects_empty_or_out_of_bounds_ranges() {
+        assert!(slice("a\nb\n", range(0, 1)).is_err());
+        assert!(slice("a\nb\n", range(2, 1)).is_err());
+        assert!(slice("a\nb\n", range(5, 9)).is_err());
+        assert_eq!(slice("a\nb\n", range(2, 99)).unwrap(), "b\n");
+    }
+}
@@ -0,0 +1,72 @@
+//! Cut one long note into a folder of smaller ones at headings of a chosen
+//! level. Each part keeps its heading line; text before the first cut
+//! (front matter included) becomes `index`.
+
+use super::outline::generate_toc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitPart {
+    /// File name without `.md`: the heading slug, or `index`.
+    pub stem: String,
+    pub content: String,
+}
+
+/// Parts in document order. Headings inside code or front matter never cut.
+/// A blank preamble is dropped rather than written as an empty `index`.
+pub fn split(text: &str, level: usize) -> Vec<SplitPart> {
+    let cuts: Vec<(usize, String)> = generate_toc(text)
+        .into_iter()
+        .filter(|entry| entry.level == level)
+        .map(|entry| (entry.line - 1, entry.anchor))
+        .collect();
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut parts = Vec::new();
+    let first = cuts.first().map_or(lines.len(), |(line, _)| *line);
+    let preamble = lines[..first].concat();
+    if !preamble.trim().is_empty() {
+        parts.push(SplitPart { stem: "index".to_string(), content: preamble });
+    }
+    for (i, (start, anchor)) in cuts.iter().enumerate() {
+        let end = cuts.get(i + 1).map_or(lines.len(), |(line, _)| *line);
+        parts.push(SplitPart { stem: anchor.clone(), content: lines[*start..end].concat() });
+    }
+    parts
+}
+
+/// `<stem>.md`, or `<stem>-2.md`, `<stem>-3.md`, ... while `taken` says the
+/// name is in use.
+pub fn available_name(stem: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut name = format!("{}.md", stem);
+    let mut n = 2;
+    while taken(&name) {
+        name = format!("{}-{}.md", stem, n);
+        n += 1;
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_at_the_chosen_level_only() {
+        let doc = "---\ntitle: Long\n---\nIntro\n## One\na\n### Sub\nb\n```\n## not a heading\n```\n## Two\nc\n";
+        let parts = split(doc, 2);
+        let stems: Vec<&str> = parts.iter().map(|p| p.stem.as_str()).collect();
+        assert_eq!(stems, ["index", "one", "two"]);
+        assert_eq!(parts[0].content, "---\ntitle: Long\n---\nIntro\n");
+        assert_eq!(parts[1].content, "## One\na\n### Sub\nb\n```\n## not a heading\n```\n");
+        assert_eq!(parts.iter().map(|p| p.content.as_str()).collect::<String>(), doc);
+
+        let parts = split("## Same\nx\n## Same\ny", 2);
+        assert_eq!(parts.iter().map(|p| p.stem.as_str()).collect::<Vec<_>>(), ["same", "same-2"]);
+    }
+
+    #[test]
+    fn names_get_a_suffix_on_collision() {
+        let existing = ["one.md", "one-2.md"];
+        assert_eq!(available_name("one", |n| existing.contains(&n)), "one-3.md");
+        assert_eq!(available_name("two", |n| existing.contains(&n)), "two.md");
+    }
+}
@@ -0,0 +1,142 @@
+//! Word counts and reading time, for the whole document and per section.
+//! Front matter and fenced code never count towards either.
+
+use serde::Serialize;
+
+use super::{fenced_blocks, frontmatter, outline};
+
+/// Typical silent reading speed for prose.
+pub const WORDS_PER_MINUTE: f64 = 200.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentStats {
+    pub words: usize,
+    pub reading_minutes: f64,
+    /// Lines inside fenced code blocks, reported but not read.
+    pub code_lines: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionStats {
+    /// `(intro)` for text before the first heading.
+    pub heading: String,
+    /// 0 for the intro section.
+    pub level: usize,
+    /// 1-based line where the section starts.
+    pub line: usize,
+    pub words: usize,
+    pub reading_minutes: f64,
+}
+
+/// Tokens with at least one letter or digit; bare `-`, `|` or `#` markers
+/// are not words.
+fn count_words(line: &str) -> usize {
+    line.split_whitespace().filter(|w| w.chars().any(char::is_alphanumeric)).count()
+}
+
+fn reading_minutes(words: usize) -> f64 {
+    (words as f64 / WORDS_PER_MINUTE * 10.0).round() / 10.0
+}
+
+/// Per-line word counts with front matter and fences zeroed, plus the
+/// index of the first body line.
+fn line_words(text: &str) -> (Vec<usize>, usize, usize) {
+    let skip = match frontmatter::split(text) {
+        Some((_, body)) => text[..text.len() - body.len()].matches('\n').count(),
+        None => 0,
+    };
+    let fences = fenced_blocks(text);
+    let mut code_lines = 0;
+    let words = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i < skip {
+                0
+            } else if fences.iter().any(|&(open, close)| open <= i && i <= close) {
+                code_lines += 1;
+                0
+            } else {
+                count_words(line)
+            }
+        })
+        .collect();
+    (words, skip, code_lines)
+}
+
+pub fn document_stats(text: &str) -> DocumentStats {
+    let (words, _, code_lines) = line_words(text);
+    let words = words.iter().sum();
+    DocumentStats { words, reading_minutes: reading_minutes(words), code_lines }
+}
+
+/// Stats per heading section, segmented like `generate_toc`. A section runs
+/// up to the next heading of any level.
+pub fn section_stats(text: &str) -> Vec<SectionStats> {
+    let (words, skip, _) = line_words(text);
+    let toc = outline::generate_toc(text);
+    let mut sections = Vec::with_capacity(toc.len() + 1);
+    let first = toc.first().map_or(words.len(), |e| e.line - 1);
+    let intro_has_text = text.lines().take(first).skip(skip).any(|l| !l.trim().is_empty());
+    if intro_has_text {
+        let n = words[skip..first].iter().sum();
+        sections.push(SectionStats {
+            heading: "(intro)".to_string(),
+            level: 0,
+            line: skip + 1,
+            words: n,
+            reading_minutes: reading_minutes(n),
+        });
+    }
+    for (i, entry) in toc.iter().enumerate() {
+        let end = toc.get(i + 1).map_or(words.len(), |next| next.line - 1);
+        let n = words[entry.line - 1..end].iter().sum();
+        sections.push(SectionStats {
+            heading: entry.text.clone(),
+            level: entry.level,
+            line: entry.line,
+            words: n,
+            reading_minutes: reading_minutes(n),
+        });
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "---\ntitle: t\n---\nSome intro words here.\n# One\nalpha beta gamma\n- item\n```\nlet code = 1;\n```\n## Two\n| a | b |\n";
+
+    #[test]
+    fn splits_into_intro_and_heading_sections() {
+        let stats: Vec<(String, usize, usize, usize)> = section_stats(DOC)
+            .into_iter()
+            .map(|s| (s.heading, s.level, s.line, s.words))
+            .collect();
+        assert_eq!(
+            stats,
+            [
+                ("(intro)".to_string(), 0, 4, 4),
+                ("One".to_string(), 1, 5, 5),
+                ("Two".to_string(), 2, 11, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn sections_add_up_to_the_document() {
+        let total: usize = section_stats(DOC).iter().map(|s| s.words).sum();
+        let doc = document_stats(DOC);
+        assert_eq!(total, doc.words);
+        assert_eq!(doc.code_lines, 3);
+    }
+
+    #[test]
+    fn reading_time_rounds_to_a_tenth_of_a_minute() {
+        assert_eq!(reading_minutes(0), 0.0);
+        assert_eq!(reading_minutes(330), 1.7);
+    }
+}
@@ -0,0 +1,255 @@
+//! GFM tables: detection, parsing and padded re-rendering.
+
+use super::fenced_blocks;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// Split a table row into trimmed cells, honouring `\|` escapes. `None` when
+/// the line has no unescaped pipe at all.
+pub fn split_row(line: &str) -> Option<Vec<String>> {
+    let trimmed = line.trim();
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut pipes = 0;
+    let mut chars = trimmed.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push_str("\\|");
+                chars.next();
+            }
+            '|' => {
+                pipes += 1;
+                cells.push(std::mem::take(&mut cell));
+            }
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    if pipes == 0 {
+        return None;
+    }
+    // Leading/trailing pipes delimit the row rather than an empty cell.
+    if trimmed.starts_with('|') {
+        cells.remove(0);
+    }
+    if trimmed.ends_with('|') && !trimmed.ends_with("\\|") {
+        cells.pop();
+    }
+    Some(cells.into_iter().map(|c| c.trim().to_string()).collect())
+}
+
+/// Alignments when `cells` form a delimiter row (`---`, `:--`, `:-:`, `--:`).
+pub fn delimiter_alignments(cells: &[String]) -> Option<Vec<Align>> {
+    cells
+        .iter()
+        .map(|c| {
+            let left = c.starts_with(':');
+            let right = c.ends_with(':') && c.len() > 1;
+            let dashes = c.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|ch| ch == '-') {
+                return None;
+            }
+            Some(match (left, right) {
+                (true, true) => Align::Center,
+                (true, false) => Align::Left,
+                (false, true) => Align::Right,
+                (false, false) => Align::None,
+            })
+        })
+        .collect()
+}
+
+fn width(s: &str) -> usize {
+    s.chars().count()
+}
+
+fn pad(cell: &str, w: usize, align: Align) -> String {
+    let gap = w.saturating_sub(width(cell));
+    match align {
+        Align::Right => format!("{}{}", " ".repeat(gap), cell),
+        Align::Center => format!("{}{}{}", " ".repeat(gap / 2), cell, " ".repeat(gap - gap / 2)),
+        Align::None | Align::Left => format!("{}{}", cell, " ".repeat(gap)),
+    }
+}
+
+/// Render parsed rows (header first) with every column padded to one width.
+/// Rows shorter than the header get empty cells; longer ones are kept whole.
+pub fn render(rows: &[Vec<String>], aligns: &[Align]) -> Vec<String> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0).max(aligns.len());
+    let align_of = |i: usize| aligns.get(i).copied().unwrap_or(Align::None);
+    let mut widths = vec![3usize; columns];
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(width(cell));
+        }
+    }
+    let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+    let row_line = |row: &Vec<String>| {
+        line((0..columns)
+            .map(|i| pad(row.get(i).map(String::as_str).unwrap_or(""), widths[i], align_of(i)))
+            .collect())
+    };
+
+    let mut out = Vec::with_capacity(rows.len() + 1);
+    if let Some(header) = rows.first() {
+        out.push(row_line(header));
+    }
+    out.push(line((0..columns)
+        .map(|i| {
+            let w = widths[i];
+            match align_of(i) {
+                Align::None => "-".repeat(w),
+                Align::Left => format!(":{}", "-".repeat(w - 1)),
+                Align::Right => format!("{}:", "-".repeat(w - 1)),
+                Align::Center => format!(":{}:", "-".repeat(w - 2)),
+            }
+        })
+        .collect()));
+    for row in rows.iter().skip(1) {
+        out.push(row_line(row));
+    }
+    out
+}
+
+/// Pad every GFM table in `text` outside fenced code; other lines are kept
+/// byte for byte.
+pub fn format_all(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let fences = fenced_blocks(text);
+    let in_fence = |i: usize| fences.iter().any(|&(open, close)| open <= i && i <= close);
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut i = 0;
+    while i < lines.len() {
+        if let Some((end, rendered)) = table_at(&lines, i, &in_fence) {
+            out.extend(rendered);
+            i = end;
+        } else {
+            out.push(lines[i].to_string());
+            i += 1;
+        }
+    }
+    let mut joined = out.join("\n");
+    if text.ends_with('\n') {
+        joined.push('\n');
+    }
+    joined
+}
+
+fn indent_of(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+fn is_list_item(line: &str) -> bool {
+    let rest = line.trim_start();
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let marker = if digits > 0 {
+        rest[digits..].starts_with(['.', ')'])
+    } else {
+        rest.starts_with(['-', '*', '+'])
+    };
+    let after = if digits > 0 { digits + 1 } else { 1 };
+    marker && rest[after..].starts_with([' ', '\t'])
+}
+
+/// Four or more spaces of indent is code, unless the line continues a list
+/// item that starts further left.
+fn indent_allows_table(lines: &[&str], start: usize, indent: usize) -> bool {
+    if indent < 4 {
+        return true;
+    }
+    lines[..start]
+        .iter()
+        .rev()
+        .filter(|l| !l.trim().is_empty())
+        .find(|l| indent_of(l).len() < indent)
+        .is_some_and(|l| is_list_item(l))
+}
+
+/// A table whose header is line `start`: `(index after the last row, lines)`.
+/// Indentation (tables nested in list items) is kept on every row.
+fn table_at(lines: &[&str], start: usize, in_fence: &impl Fn(usize) -> bool) -> Option<(usize, Vec<String>)> {
+    if start + 1 >= lines.len() || in_fence(start) || in_fence(start + 1) {
+        return None;
+    }
+    let indent = indent_of(lines[start]);
+    if !indent_allows_table(lines, start, indent.len()) || indent_of(lines[start + 1]).len() < indent.len() {
+        return None;
+    }
+    let header = split_row(lines[start])?;
+    let aligns = delimiter_alignments(&split_row(lines[start + 1])?)?;
+    if aligns.len() != header.len() {
+        return None;
+    }
+    let mut rows = vec![header];
+    let mut end = start + 2;
+    while end < lines.len() && !in_fence(end) && !lines[end].trim().is_empty() {
+        if indent_of(lines[end]).len() < indent.len() {
+            break;
+        }
+        match split_row(lines[end]) {
+            Some(row) => rows.push(row),
+            None => break,
+        }
+        end += 1;
+    }
+    let rendered = render(&rows, &aligns).into_iter().map(|l| format!("{}{}", indent, l)).collect();
+    Some((end, rendered))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_columns_and_keeps_alignment() {
+        let doc = "|a|longer|c|\n|:-|:-:|--:|\n|1|2|three|\n";
+        assert_eq!(
+            format_all(doc),
+            "| a   | longer |     c |\n| :-- | :----: | ----: |\n| 1   |   2    | three |\n"
+        );
+    }
+
+    #[test]
+    fn escaped_pipes_stay_inside_cells() {
+        let doc = "| expr | note |\n| --- | --- |\n| a \\| b | x |\n";
+        let out = format_all(doc);
+        assert!(out.contains("| a \\| b | x    |"));
+    }
+
+    #[test]
+    fn formatting_is_idempotent_and_ignores_fences() {
+        let doc = "text\n\n| a | b |\n|---|---|\n| 1 |\n\n```\n|x|y|\n|-|-|\n```\n";
+        let once = format_all(doc);
+        assert_eq!(format_all(&once), once);
+        assert!(once.contains("```\n|x|y|\n|-|-|\n```"));
+        assert!(once.contains("| 1   |     |"));
+    }
+
+    #[test]
+    fn tables_in_list_items_keep_their_indent() {
+        let doc = "- item\n\n    |a|b|\n    |-|-:|\n    |1|22|\n- next\n";
+        assert_eq!(
+            format_all(doc),
+            "- item\n\n    | a   |   b |\n    | --- | --: |\n    | 1   |  22 |\n- next\n"
+        );
+    }
+
+    #[test]
+    fn indented_code_is_not_a_table() {
+        let doc = "para\n\n    |a|b|\n    |-|-|\n";
+        assert_eq!(format_all(doc), doc);
+    }
+
+    #[test]
+    fn header_without_matching_delimiter_is_not_a_table() {
+        let doc = "| a | b |\n| --- |\n";
+        assert_eq!(format_all(doc), doc);
+    }
+}
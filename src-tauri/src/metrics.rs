@@ -0,0 +1,112 @@
+//! Opt-in command timings (`collectMetrics`). Tauri resolves async commands
+//! out of reach of the invoke handler, so the webview times each `invoke`
+//! round trip and reports batches here. Samples live in memory only, in a
+//! bounded ring per command.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// Samples kept per command; older ones fall off.
+const MAX_SAMPLES: usize = 256;
+/// Distinct command names tracked; new names beyond this are ignored.
+const MAX_COMMANDS: usize = 512;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommandTiming {
+    pub command: String,
+    pub ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandMetric {
+    pub command: String,
+    /// Over the retained samples.
+    pub avg_ms: f64,
+    pub p95_ms: f64,
+    /// Every call recorded since the last clear, retained or not.
+    pub count: u64,
+}
+
+#[derive(Default)]
+struct Series {
+    samples: VecDeque<f64>,
+    count: u64,
+}
+
+#[derive(Default)]
+pub struct Metrics(Mutex<HashMap<String, Series>>);
+
+impl Metrics {
+    pub fn record(&self, timings: &[CommandTiming]) {
+        let mut all = self.0.lock().unwrap();
+        for timing in timings {
+            if !timing.ms.is_finite() || timing.ms < 0.0 {
+                continue;
+            }
+            if !all.contains_key(&timing.command) && all.len() >= MAX_COMMANDS {
+                continue;
+            }
+            let series = all.entry(timing.command.clone()).or_default();
+            if series.samples.len() == MAX_SAMPLES {
+                series.samples.pop_front();
+            }
+            series.samples.push_back(timing.ms);
+            series.count += 1;
+        }
+    }
+
+    /// Slowest on average first.
+    pub fn summary(&self) -> Vec<CommandMetric> {
+        let all = self.0.lock().unwrap();
+        let mut out: Vec<CommandMetric> = all
+            .iter()
+            .filter(|(_, s)| !s.samples.is_empty())
+            .map(|(command, series)| {
+                let mut sorted: Vec<f64> = series.samples.iter().copied().collect();
+                sorted.sort_by(f64::total_cmp);
+                let rank = (sorted.len() * 95).div_ceil(100).max(1);
+                CommandMetric {
+                    command: command.clone(),
+                    avg_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+                    p95_ms: sorted[rank - 1],
+                    count: series.count,
+                }
+            })
+            .collect();
+        out.sort_by(|a, b| b.avg_ms.total_cmp(&a.avg_ms).then_with(|| a.command.cmp(&b.command)));
+        out
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timings(command: &str, values: impl IntoIterator<Item = f64>) -> Vec<CommandTiming> {
+        values.into_iter().map(|ms| CommandTiming { command: command.to_string(), ms }).collect()
+    }
+
+    #[test]
+    fn summarizes_per_command_with_a_bounded_ring() {
+        let metrics = Metrics::default();
+        metrics.record(&timings("read_text_file", (1..=20).map(f64::from)));
+        metrics.record(&timings("search", [100.0, f64::NAN, -1.0]));
+        let summary = metrics.summary();
+        assert_eq!(summary[0], CommandMetric { command: "search".into(), avg_ms: 100.0, p95_ms: 100.0, count: 1 });
+        assert_eq!(summary[1].avg_ms, 10.5);
+        assert_eq!(summary[1].p95_ms, 19.0);
+
+        metrics.record(&timings("search", (0..MAX_SAMPLES).map(|_| 1.0)));
+        let search = metrics.summary().into_iter().find(|m| m.command == "search").unwrap();
+        assert_eq!((search.avg_ms, search.count), (1.0, MAX_SAMPLES as u64 + 1));
+
+        metrics.clear();
+        assert!(metrics.summary().is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+//! Docs-as-code hook: after a save, run the command configured for the
+//! file's workspace (a site build, a linter) and stream its output to the
+//! windows as `build-output` events. The command is an argv list kept in
+//! the app settings, never in the workspace, so opening a folder can't make
+//! the editor run anything. It is spawned directly, without a shell.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+/// Saves closer together than this start a single run.
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+#[derive(Default)]
+struct RootRuns {
+    /// Bumped on every save; only the newest waiting save starts a run.
+    generation: u64,
+    /// Generation of the run in `child`.
+    running: u64,
+    child: Option<CommandChild>,
+}
+
+/// Runs per workspace root.
+#[derive(Default)]
+pub struct OnSaveRuns(Mutex<HashMap<String, RootRuns>>);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BuildEvent {
+    Started { argv: Vec<String> },
+    Stdout { line: String },
+    Stderr { line: String },
+    /// `code` is `None` when the process was ended by a signal.
+    Finished { code: Option<i32>, success: bool },
+    /// The command could not be started or its output not read.
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildOutput {
+    pub root: String,
+    pub file: String,
+    #[serde(flatten)]
+    pub event: BuildEvent,
+}
+
+/// The configured workspace that contains `file`, the innermost one when
+/// roots are nested.
+pub fn workspace_for<'a>(commands: &'a BTreeMap<String, Vec<String>>, file: &Path) -> Option<(&'a str, &'a [String])> {
+    commands
+        .iter()
+        .filter(|(root, argv)| !argv.is_empty() && file.starts_with(root))
+        .max_by_key(|(root, _)| root.len())
+        .map(|(root, argv)| (root.as_str(), argv.as_slice()))
+}
+
+/// The argv for one run: `{file}` and `{root}` are replaced inside each
+/// argument, and when the template uses neither both are appended.
+pub fn build_argv(template: &[String], file: &str, root: &str) -> Vec<String> {
+    let uses_placeholders = template.iter().any(|arg| arg.contains("{file}") || arg.contains("{root}"));
+    let mut argv: Vec<String> = template
+        .iter()
+        .map(|arg| arg.replace("{file}", file).replace("{root}", root))
+        .collect();
+    if !uses_placeholders {
+        argv.push(file.to_string());
+        argv.push(root.to_string());
+    }
+    argv
+}
+
+fn emit(app: &tauri::AppHandle, root: &str, file: &str, event: BuildEvent) {
+    let payload = BuildOutput { root: root.to_string(), file: file.to_string(), event };
+    let _ = app.emit("build-output", payload);
+}
+
+/// Queue the on-save command for `file`'s workspace. Returns whether one is
+/// configured; the run itself starts once saves have paused for `DEBOUNCE`.
+pub fn schedule(app: &tauri::AppHandle, file: &Path) -> bool {
+    let settings = crate::settings::current(app);
+    let Some((root, template)) = workspace_for(&settings.on_save_commands, file) else { return false };
+    let file = file.to_string_lossy().into_owned();
+    let argv = build_argv(template, &file, root);
+    let root = root.to_string();
+    let generation = {
+        let state = app.state::<OnSaveRuns>();
+        let mut runs = state.0.lock().unwrap();
+        let entry = runs.entry(root.clone()).or_default();
+        entry.generation += 1;
+        entry.generation
+    };
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(DEBOUNCE).await;
+        start(&handle, root, file, argv, generation);
+    });
+    true
+}
+
+/// Start the run for `generation` unless a later save superseded it. A run
+/// still going from an earlier save is killed first; its output stops being
+/// reported.
+fn start(app: &tauri::AppHandle, root: String, file: String, argv: Vec<String>, generation: u64) {
+    let state = app.state::<OnSaveRuns>();
+    let mut runs = state.0.lock().unwrap();
+    let Some(entry) = runs.get_mut(&root) else { return };
+    if entry.generation != generation {
+        return;
+    }
+    if let Some(previous) = entry.child.take() {
+        let _ = previous.kill();
+    }
+    let spawned = app.shell().command(&argv[0]).args(&argv[1..]).current_dir(&root).spawn();
+    let (mut events, child) = match spawned {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            tracing::warn!(%root, program = %argv[0], "on-save command not started: {}", e);
+            emit(app, &root, &file, BuildEvent::Error { message: format!("{}: {}", argv[0], e) });
+            return;
+        }
+    };
+    entry.running = generation;
+    entry.child = Some(child);
+    drop(runs);
+    emit(app, &root, &file, BuildEvent::Started { argv });
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let line = |bytes: Vec<u8>| String::from_utf8_lossy(&bytes).trim_end_matches(['\n', '\r']).to_string();
+        while let Some(event) = events.recv().await {
+            let current = {
+                let state = handle.state::<OnSaveRuns>();
+                let mut runs = state.0.lock().unwrap();
+                match runs.get_mut(&root).filter(|e| e.running == generation && e.child.is_some()) {
+                    Some(entry) => {
+                        if matches!(event, CommandEvent::Terminated(_)) {
+                            entry.child = None;
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            };
+            if !current {
+                break;
+            }
+            let event = match event {
+                CommandEvent::Stdout(bytes) => BuildEvent::Stdout { line: line(bytes) },
+                CommandEvent::Stderr(bytes) => BuildEvent::Stderr { line: line(bytes) },
+                CommandEvent::Error(message) => BuildEvent::Error { message },
+                CommandEvent::Terminated(status) => {
+                    tracing::info!(%root, code = ?status.code, "on-save command finished");
+                    BuildEvent::Finished { code: status.code, success: status.code == Some(0) }
+                }
+                _ => continue,
+            };
+            emit(&handle, &root, &file, event);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argv_substitutes_or_appends_file_and_root() {
+        let argv = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            build_argv(&argv(&["mkdocs", "build", "--config-file={root}/mkdocs.yml"]), "/w/a b.md", "/w"),
+            ["mkdocs", "build", "--config-file=/w/mkdocs.yml"]
+        );
+        assert_eq!(build_argv(&argv(&["./build.sh"]), "/w/$(rm).md", "/w"), ["./build.sh", "/w/$(rm).md", "/w"]);
+
+        let commands: BTreeMap<String, Vec<String>> = [
+            ("/w".to_string(), argv(&["outer"])),
+            ("/w/site".to_string(), argv(&["inner"])),
+            ("/w/off".to_string(), Vec::new()),
+        ]
+        .into();
+        assert_eq!(workspace_for(&commands, Path::new("/w/site/index.md")).unwrap().0, "/w/site");
+        assert_eq!(workspace_for(&commands, Path::new("/w/off/x.md")).unwrap().0, "/w");
+        assert!(workspace_for(&commands, Path::new("/w2/x.md")).is_none());
+    }
+}
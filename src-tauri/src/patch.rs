@@ -0,0 +1,226 @@
+//! Unified diffs between a file on disk and the editor buffer, for sending
+//! changes to a reviewer without the whole file. Output is in the format
+//! `patch -p1` and `git apply` take, with three lines of context, and a file
+//! that doesn't exist yet is diffed against nothing (`--- /dev/null`).
+
+/// Lines of unchanged text around each change.
+const CONTEXT: usize = 3;
+/// Past this many differing lines the shortest edit is not worth finding;
+/// the changed middle is replaced as one block instead.
+const MAX_EDIT_DISTANCE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// One line of the edit script, with how many old and new lines precede it.
+#[derive(Debug, Clone, Copy)]
+struct Op {
+    kind: Kind,
+    old: usize,
+    new: usize,
+}
+
+/// Shortest edit script turning `a` into `b` (Myers), without the shared
+/// prefix and suffix, which the caller adds back.
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Kind>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max as usize;
+    let mut v = vec![0isize; 2 * offset + 2];
+    // Round d only reads diagonals -d..=d, so each snapshot keeps just those.
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let at = |k: isize| (k + max) as usize;
+    'search: for d in 0..=max {
+        if d as usize > MAX_EDIT_DISTANCE {
+            return None;
+        }
+        trace.push(v[at(-d)..=at(d)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) { v[at(k + 1)] } else { v[at(k - 1)] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut kinds = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let get = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) { k + 1 } else { k - 1 };
+        let prev_x = if d == 0 { 0 } else { get(prev_k) };
+        let prev_y = if d == 0 { 0 } else { prev_x - prev_k };
+        while x > prev_x && y > prev_y {
+            kinds.push(Kind::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            kinds.push(if x == prev_x { Kind::Insert } else { Kind::Delete });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    kinds.reverse();
+    Some(kinds)
+}
+
+fn edit_script(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let middle = myers(mid_a, mid_b).unwrap_or_else(|| {
+        let mut kinds = vec![Kind::Delete; mid_a.len()];
+        kinds.resize(mid_a.len() + mid_b.len(), Kind::Insert);
+        kinds
+    });
+
+    let kinds = std::iter::repeat_n(Kind::Equal, prefix).chain(middle).chain(std::iter::repeat_n(Kind::Equal, suffix));
+    let (mut old, mut new) = (0, 0);
+    kinds
+        .map(|kind| {
+            let op = Op { kind, old, new };
+            if kind != Kind::Insert {
+                old += 1;
+            }
+            if kind != Kind::Delete {
+                new += 1;
+            }
+            op
+        })
+        .collect()
+}
+
+/// Ranges of `ops` to print as hunks: each change with its context, and
+/// changes whose context would touch merged into one hunk.
+fn hunk_ranges(ops: &[Op]) -> Vec<std::ops::Range<usize>> {
+    let len = ops.len();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < len {
+        if ops[i].kind == Kind::Equal {
+            i += 1;
+            continue;
+        }
+        let start = i.saturating_sub(CONTEXT);
+        let mut end = i;
+        loop {
+            while end < len && ops[end].kind != Kind::Equal {
+                end += 1;
+            }
+            let run = ops[end..].iter().take_while(|op| op.kind == Kind::Equal).count();
+            if end + run >= len || run > 2 * CONTEXT {
+                end += run.min(CONTEXT);
+                break;
+            }
+            end += run;
+        }
+        ranges.push(start..end);
+        i = end;
+    }
+    ranges
+}
+
+/// `start,count` of a hunk side; an empty side names the line before it.
+fn hunk_side(before: usize, count: usize) -> String {
+    let start = if count == 0 { before } else { before + 1 };
+    format!("{},{}", start, count)
+}
+
+fn push_line(out: &mut String, prefix: char, line: &str) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Unified diff from `old` (`None` for a file that doesn't exist yet) to
+/// `new`, both labeled `name`. Identical texts give an empty string.
+pub fn unified_diff(name: &str, old: Option<&str>, new: &str) -> String {
+    let a: Vec<&str> = old.unwrap_or("").split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = edit_script(&a, &b);
+    let ranges = hunk_ranges(&ops);
+    if ranges.is_empty() {
+        return String::new();
+    }
+
+    let mut out = match old {
+        Some(_) => format!("--- a/{}\n", name),
+        None => "--- /dev/null\n".to_string(),
+    };
+    out.push_str(&format!("+++ b/{}\n", name));
+    for range in ranges {
+        let hunk = &ops[range];
+        let old_count = hunk.iter().filter(|op| op.kind != Kind::Insert).count();
+        let new_count = hunk.iter().filter(|op| op.kind != Kind::Delete).count();
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_side(hunk[0].old, old_count),
+            hunk_side(hunk[0].new, new_count)
+        ));
+        for op in hunk {
+            match op.kind {
+                Kind::Equal => push_line(&mut out, ' ', a[op.old]),
+                Kind::Delete => push_line(&mut out, '-', a[op.old]),
+                Kind::Insert => push_line(&mut out, '+', b[op.new]),
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_get_context_and_close_ones_share_a_hunk() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n14\n15\n16\n";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\nnine\n10\n11\n12\n13\n14\n15\n16\nseventeen\n";
+        let patch = unified_diff("notes.md", Some(old), new);
+        assert_eq!(
+            patch,
+            "--- a/notes.md\n+++ b/notes.md\n\
+             @@ -1,12 +1,12 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n 7\n 8\n-9\n+nine\n 10\n 11\n 12\n\
+             @@ -14,3 +14,4 @@\n 14\n 15\n 16\n+seventeen\n"
+        );
+        assert_eq!(unified_diff("notes.md", Some(old), old), "");
+    }
+
+    #[test]
+    fn new_files_and_missing_final_newlines() {
+        assert_eq!(unified_diff("new.md", None, "a\nb\n"), "--- /dev/null\n+++ b/new.md\n@@ -0,0 +1,2 @@\n+a\n+b\n");
+        assert_eq!(
+            unified_diff("x.md", Some("a\nb"), "a\nb\n"),
+            "--- a/x.md\n+++ b/x.md\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+        );
+        assert_eq!(unified_diff("x.md", Some("a\n"), ""), "--- a/x.md\n+++ b/x.md\n@@ -1,1 +0,0 @@\n-a\n");
+    }
+
+    #[test]
+    fn finds_a_short_edit_in_reordered_lines() {
+        let old = ["a\n", "b\n", "c\n", "a\n", "b\n", "b\n", "a\n"];
+        let new = ["c\n", "b\n", "a\n", "b\n", "a\n", "c\n"];
+        let ops = edit_script(&old, &new);
+        // The classic example: five edits.
+        assert_eq!(ops.iter().filter(|op| op.kind != Kind::Equal).count(), 5);
+        let rebuilt: Vec<&str> = ops.iter().filter(|op| op.kind != Kind::Delete).map(|op| new[op.new]).collect();
+        assert_eq!(rebuilt, new);
+        let kept: Vec<&str> = ops.iter().filter(|op| op.kind != Kind::Insert).map(|op| old[op.old]).collect();
+        assert_eq!(kept, old);
+    }
+}
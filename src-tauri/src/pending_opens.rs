@@ -0,0 +1,73 @@
+//! Files to open on the next start, persisted to `<app_data>/pending-opens.json`.
+//! The updater queues the documents in play before it relaunches the app,
+//! and the main window drains the list through the normal open flow once the
+//! new version is up, so an update does not land on an empty editor.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::{AppError, AppResult};
+
+/// Enough for every tab of a busy session; anything beyond is dropped.
+const MAX_PENDING_OPENS: usize = 100;
+
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn store_file(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    Ok(dir.join("pending-opens.json"))
+}
+
+fn load_store(path: &Path) -> AppResult<Vec<String>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let bytes = std::fs::read(path).map_err(|e| AppError::io(path, e))?;
+    // A damaged queue only costs the reopen, never the start.
+    Ok(serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+/// Append `path` unless it is already queued, keeping the original order.
+fn push_back(queue: &mut Vec<String>, path: &str, cap: usize) {
+    if queue.len() < cap && !queue.iter().any(|p| p == path) {
+        queue.push(path.to_string());
+    }
+}
+
+pub fn queue(app: &tauri::AppHandle, path: &str) -> AppResult<()> {
+    if !Path::new(path).is_file() {
+        return Err(AppError::NotFound { path: path.to_string() });
+    }
+    let _g = STORE_LOCK.lock().unwrap();
+    let file = store_file(app)?;
+    let mut queue = load_store(&file)?;
+    push_back(&mut queue, path, MAX_PENDING_OPENS);
+    crate::files::write_atomic(&file, &serde_json::to_vec_pretty(&queue)?)
+}
+
+/// Empty the queue and return the files that still exist, oldest first.
+pub fn take(app: &tauri::AppHandle) -> AppResult<Vec<String>> {
+    let _g = STORE_LOCK.lock().unwrap();
+    let file = store_file(app)?;
+    let queue = load_store(&file)?;
+    if file.exists() {
+        std::fs::remove_file(&file).map_err(|e| AppError::io(&file, e))?;
+    }
+    Ok(queue.into_iter().filter(|p| Path::new(p).is_file()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_back_keeps_order_and_skips_duplicates() {
+        let mut queue = vec!["a.md".to_string()];
+        push_back(&mut queue, "b.md", 3);
+        push_back(&mut queue, "a.md", 3);
+        push_back(&mut queue, "c.md", 3);
+        push_back(&mut queue, "d.md", 3);
+        assert_eq!(queue, ["a.md", "b.md", "c.md"]);
+    }
+}
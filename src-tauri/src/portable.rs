@@ -0,0 +1,82 @@
+//! Portable mode, for running off a USB stick: with a `portable.txt` marker
+//! beside the executable, or `--portable` on the command line, everything
+//! the app stores goes to `data/` next to the binary instead of the OS
+//! folders. Decided once per run; every stored path resolves through
+//! `resolve_config_root` so the two modes never mix.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{Manager, Runtime, WebviewWindowBuilder};
+
+use crate::error::AppResult;
+
+const MARKER: &str = "portable.txt";
+const FLAG: &str = "--portable";
+const DATA_DIR: &str = "data";
+
+static ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// `<exe dir>/data` when the marker sits beside `exe` or `args` holds the flag.
+fn portable_root(exe: &Path, args: &[String]) -> Option<PathBuf> {
+    let dir = exe.parent()?;
+    (dir.join(MARKER).is_file() || args.iter().any(|a| a == FLAG)).then(|| dir.join(DATA_DIR))
+}
+
+fn root() -> Option<&'static Path> {
+    ROOT.get_or_init(|| {
+        let exe = std::env::current_exe().ok()?;
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        portable_root(&exe, &args)
+    })
+    .as_deref()
+}
+
+pub fn is_portable() -> bool {
+    root().is_some()
+}
+
+/// Folder holding settings, state stores, sessions, drafts, templates and
+/// AI data: `data/` in portable mode, the OS app data folder otherwise.
+pub fn resolve_config_root(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    match root() {
+        Some(root) => Ok(root.to_path_buf()),
+        None => Ok(app.path().app_data_dir()?),
+    }
+}
+
+pub fn log_dir(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    match root() {
+        Some(root) => Ok(root.join("logs")),
+        None => Ok(app.path().app_log_dir()?),
+    }
+}
+
+/// Point a new window's webview profile (local storage: themes, snippets,
+/// UI state) at `data/webview` in portable mode. Every window gets the same
+/// one, so they keep sharing storage; outside portable mode the platform
+/// default stays.
+pub fn with_data_dir<'a, R: Runtime, M: Manager<R>>(
+    builder: WebviewWindowBuilder<'a, R, M>,
+) -> WebviewWindowBuilder<'a, R, M> {
+    match root() {
+        Some(root) => builder.data_directory(root.join("webview")),
+        None => builder,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marker_or_flag_turns_portable_mode_on() {
+        let dir = std::env::temp_dir().join(format!("mermark-portable-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("mermark");
+        assert_eq!(portable_root(&exe, &["note.md".to_string()]), None);
+        assert_eq!(portable_root(&exe, &[FLAG.to_string()]), Some(dir.join(DATA_DIR)));
+        std::fs::write(dir.join(MARKER), "").unwrap();
+        assert_eq!(portable_root(&exe, &[]), Some(dir.join(DATA_DIR)));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,104 @@
+//! Remembered scroll/cursor position per document, across restarts, in
+//! `<app_data>/positions.json` keyed by absolute path. Opt-in through the
+//! `rememberPositions` setting; complements session restore for files that
+//! are opened standalone.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// Most documents remembered; the least recently saved are evicted.
+const MAX_POSITIONS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub scroll: f64,
+    /// Cursor offset in the document, as the editor reports it.
+    pub cursor: u64,
+    /// Milliseconds since the Unix epoch; drives eviction.
+    pub updated_at: i64,
+}
+
+pub(crate) type Store = HashMap<String, Position>;
+
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn store_file(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    Ok(dir.join("positions.json"))
+}
+
+fn load_store(app: &tauri::AppHandle) -> AppResult<Store> {
+    let path = store_file(app)?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let bytes = std::fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+    Ok(serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+fn save_store(app: &tauri::AppHandle, store: &Store) -> AppResult<()> {
+    let path = store_file(app)?;
+    let bytes = serde_json::to_vec(store)?;
+    crate::files::write_atomic(&path, &bytes)
+}
+
+/// Keep at most `max` entries, dropping the oldest first.
+fn cap(store: &mut Store, max: usize) {
+    if store.len() <= max {
+        return;
+    }
+    let mut by_age: Vec<(String, i64)> = store.iter().map(|(k, p)| (k.clone(), p.updated_at)).collect();
+    by_age.sort_by_key(|(_, at)| *at);
+    for (key, _) in by_age.into_iter().take(store.len() - max) {
+        store.remove(&key);
+    }
+}
+
+pub fn save(app: &tauri::AppHandle, path: &str, scroll: f64, cursor: u64) -> AppResult<()> {
+    let _g = STORE_LOCK.lock().unwrap();
+    let mut store = load_store(app)?;
+    let updated_at = chrono::Utc::now().timestamp_millis();
+    store.insert(path.to_string(), Position { scroll, cursor, updated_at });
+    cap(&mut store, MAX_POSITIONS);
+    save_store(app, &store)
+}
+
+pub fn get(app: &tauri::AppHandle, path: &str) -> AppResult<Option<Position>> {
+    let _g = STORE_LOCK.lock().unwrap();
+    Ok(load_store(app)?.get(path).copied())
+}
+
+/// Startup maintenance: forget files that no longer exist.
+pub fn prune(app: &tauri::AppHandle) -> AppResult<()> {
+    let _g = STORE_LOCK.lock().unwrap();
+    let mut store = load_store(app)?;
+    let before = store.len();
+    store.retain(|path, _| Path::new(path).is_file());
+    cap(&mut store, MAX_POSITIONS);
+    if store.len() != before {
+        save_store(app, &store)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cap_evicts_oldest_entries() {
+        let mut store: Store = (0..5)
+            .map(|i| (format!("/doc{}.md", i), Position { scroll: 0.0, cursor: 0, updated_at: i }))
+            .collect();
+        cap(&mut store, 3);
+        let mut kept: Vec<_> = store.keys().cloned().collect();
+        kept.sort();
+        assert_eq!(kept, ["/doc2.md", "/doc3.md", "/doc4.md"]);
+    }
+}
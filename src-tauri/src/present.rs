@@ -0,0 +1,92 @@
+//! Present mode: a read-only mirror window for walkthroughs. Each editor
+//! window has at most one, labeled `present-<source>`, that renders what
+//! the source pushes through `push_present_update`. The pairing lives here
+//! so updates reach only the right mirror, closing the source closes it,
+//! and a mirror opened late can ask for the last pushed content.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use serde::Serialize;
+
+pub const LABEL_PREFIX: &str = "present-";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresentUpdate {
+    pub file_path: Option<String>,
+    pub content: String,
+}
+
+#[derive(Default)]
+struct Mirror {
+    present: String,
+    last: Option<PresentUpdate>,
+}
+
+/// Mirrors by source window label.
+#[derive(Default)]
+pub struct PresentPairs(Mutex<HashMap<String, Mirror>>);
+
+pub fn is_present_label(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
+
+pub fn label_for(source: &str) -> String {
+    format!("{}{}", LABEL_PREFIX, source)
+}
+
+impl PresentPairs {
+    pub fn pair(&self, source: &str, present: &str) {
+        self.0.lock().unwrap().insert(source.to_string(), Mirror { present: present.to_string(), last: None });
+    }
+
+    pub fn present_for(&self, source: &str) -> Option<String> {
+        self.0.lock().unwrap().get(source).map(|m| m.present.clone())
+    }
+
+    /// Remember `update` as the latest and return the mirror to send it to.
+    pub fn push(&self, source: &str, update: PresentUpdate) -> Option<String> {
+        let mut pairs = self.0.lock().unwrap();
+        let mirror = pairs.get_mut(source)?;
+        mirror.last = Some(update);
+        Some(mirror.present.clone())
+    }
+
+    /// Last content pushed to the mirror `present`.
+    pub fn last_for(&self, present: &str) -> Option<PresentUpdate> {
+        let pairs = self.0.lock().unwrap();
+        pairs.values().find(|m| m.present == present).and_then(|m| m.last.clone())
+    }
+
+    /// Close hook for either side. For a source, returns its mirror so the
+    /// caller can close it.
+    pub fn forget(&self, label: &str) -> Option<String> {
+        let mut pairs = self.0.lock().unwrap();
+        pairs.retain(|_, m| m.present != label);
+        pairs.remove(label).map(|m| m.present)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(content: &str) -> PresentUpdate {
+        PresentUpdate { file_path: None, content: content.to_string() }
+    }
+
+    #[test]
+    fn updates_follow_the_pairing() {
+        let pairs = PresentPairs::default();
+        assert_eq!(pairs.push("main", update("a")), None);
+        pairs.pair("main", &label_for("main"));
+        assert_eq!(pairs.push("main", update("b")).as_deref(), Some("present-main"));
+        assert_eq!(pairs.last_for("present-main").unwrap().content, "b");
+
+        assert_eq!(pairs.forget("present-main"), None);
+        assert_eq!(pairs.present_for("main"), None);
+        pairs.pair("main", "present-main");
+        assert_eq!(pairs.forget("main").as_deref(), Some("present-main"));
+        assert!(is_present_label("present-window-2") && !is_present_label("window-2"));
+    }
+}
@@ -0,0 +1,83 @@
+//! Recently opened workspace folders, persisted to
+//! `<app_data>/recent-folders.json` (recent files stay in the frontend's own
+//! store). Powers the "Reopen recent folder" menu.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::{AppError, AppResult};
+
+/// Most folders kept; older entries fall off the end.
+const MAX_RECENT_FOLDERS: usize = 15;
+
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn store_file(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    Ok(dir.join("recent-folders.json"))
+}
+
+fn load_store(app: &tauri::AppHandle) -> AppResult<Vec<String>> {
+    let path = store_file(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let bytes = std::fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+    // A damaged list is not worth failing the menu over.
+    Ok(serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+fn save_store(app: &tauri::AppHandle, folders: &[String]) -> AppResult<()> {
+    let path = store_file(app)?;
+    let bytes = serde_json::to_vec_pretty(folders)?;
+    crate::files::write_atomic(&path, &bytes)
+}
+
+/// Move `path` to the front of `folders`, dropping duplicates and anything
+/// past `cap`.
+fn push_front(folders: &mut Vec<String>, path: &str, cap: usize) {
+    folders.retain(|f| f != path);
+    folders.insert(0, path.to_string());
+    folders.truncate(cap);
+}
+
+pub fn add_folder(app: &tauri::AppHandle, path: &str) -> AppResult<Vec<String>> {
+    if !Path::new(path).is_dir() {
+        return Err(AppError::NotFound { path: path.to_string() });
+    }
+    let _g = STORE_LOCK.lock().unwrap();
+    let mut folders = load_store(app)?;
+    push_front(&mut folders, path, MAX_RECENT_FOLDERS);
+    save_store(app, &folders)?;
+    Ok(folders)
+}
+
+/// Recent folders, most recent first, skipping ones that no longer exist.
+/// Missing folders stay in the store in case a drive is only unmounted.
+pub fn folders(app: &tauri::AppHandle) -> AppResult<Vec<String>> {
+    let _g = STORE_LOCK.lock().unwrap();
+    Ok(load_store(app)?
+        .into_iter()
+        .filter(|f| Path::new(f).is_dir())
+        .collect())
+}
+
+pub fn clear_folders(app: &tauri::AppHandle) -> AppResult<()> {
+    let _g = STORE_LOCK.lock().unwrap();
+    save_store(app, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_front_dedupes_and_caps() {
+        let mut folders = vec!["/a".to_string(), "/b".to_string(), "/c".to_string()];
+        push_front(&mut folders, "/c", 3);
+        assert_eq!(folders, ["/c", "/a", "/b"]);
+        push_front(&mut folders, "/d", 3);
+        assert_eq!(folders, ["/d", "/c", "/a"]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Find-and-replace across every note of a workspace, for renaming a term
+//! everywhere at once. Files are the ones backlinks and search walk (hidden
+//! folders such as `.git` and `node_modules` never included); each changed
+//! file is written atomically under its write lock and a run can be
+//! cancelled between files.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::files::FileLocks;
+use crate::links::{self, FailedRewrite, PlannedChange};
+
+/// Cancel flags of the replace runs in progress, by `replace_id`.
+#[derive(Default)]
+pub struct ReplaceRegistry(pub crate::scan::ScanRegistry);
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReplaceOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// `query` is a regular expression and `replacement` may use `$1`/`${name}`.
+    pub regex: bool,
+    /// Write nothing; return a preview per file instead.
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReplacement {
+    pub file: String,
+    pub replacements: u32,
+    /// Dry run only: each touched line (or run of lines, when a match spans
+    /// several) before and after.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<Vec<PlannedChange>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplaceInFiles {
+    pub files: Vec<FileReplacement>,
+    /// Files left as they were: changed on disk since they were read (an
+    /// editor save got in first) or could not be written.
+    pub failed: Vec<FailedRewrite>,
+}
+
+pub fn build_pattern(query: &str, options: ReplaceOptions) -> AppResult<Regex> {
+    if query.is_empty() {
+        return Err(AppError::InvalidInput("search text is empty".to_string()));
+    }
+    let mut pattern = if options.regex { query.to_string() } else { regex::escape(query) };
+    if options.whole_word {
+        pattern = format!(r"\b(?:{})\b", pattern);
+    }
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| AppError::InvalidInput(format!("invalid pattern: {}", e)))
+}
+
+/// Replace every non-empty match of `re`. Returns the new text, the number
+/// of replacements and the changed line blocks (1-based first line).
+pub fn replace_text(text: &str, re: &Regex, replacement: &str, expand: bool) -> (String, u32, Vec<(usize, String, String)>) {
+    let mut out = String::with_capacity(text.len());
+    let mut blocks: Vec<(usize, String, String)> = Vec::new();
+    let mut count = 0u32;
+    let mut copied = 0;
+    let mut matches = re.captures_iter(text).filter(|c| !c.get(0).unwrap().is_empty()).peekable();
+    while let Some(first) = matches.next() {
+        let m = first.get(0).unwrap();
+        let start = text[..m.start()].rfind('\n').map_or(0, |i| i + 1);
+        let mut end = text[m.end()..].find('\n').map_or(text.len(), |i| m.end() + i);
+        let mut block = vec![first];
+        // Matches on lines already in the block join it and may extend it.
+        while let Some(next) = matches.next_if(|c| c.get(0).unwrap().start() <= end) {
+            let next_end = next.get(0).unwrap().end();
+            if next_end > end {
+                end = text[next_end..].find('\n').map_or(text.len(), |i| next_end + i);
+            }
+            block.push(next);
+        }
+        let mut new_block = String::new();
+        let mut cursor = start;
+        for caps in &block {
+            let m = caps.get(0).unwrap();
+            new_block.push_str(&text[cursor..m.start()]);
+            if expand {
+                caps.expand(replacement, &mut new_block);
+            } else {
+                new_block.push_str(replacement);
+            }
+            cursor = m.end();
+        }
+        new_block.push_str(&text[cursor..end]);
+        count += block.len() as u32;
+        out.push_str(&text[copied..start]);
+        out.push_str(&new_block);
+        copied = end;
+        let line = text[..start].matches('\n').count() + 1;
+        blocks.push((line, text[start..end].trim_end_matches('\r').to_string(), new_block.trim_end_matches('\r').to_string()));
+    }
+    out.push_str(&text[copied..]);
+    (out, count, blocks)
+}
+
+/// Walk `root`, replacing in every file with a match. On a dry run nothing
+/// is written and each entry carries its preview. A file that changed since
+/// it was read is not overwritten but reported in `failed`, like one that
+/// can't be saved. Cancelling stops before the next file; the entries for
+/// files already written are returned.
+pub fn replace_in_files(
+    locks: &FileLocks,
+    root: &Path,
+    re: &Regex,
+    replacement: &str,
+    options: ReplaceOptions,
+    max_bytes: u64,
+    cancel: &AtomicBool,
+) -> AppResult<ReplaceInFiles> {
+    let dry_run = options.dry_run;
+    let mut results = ReplaceInFiles::default();
+    for file in links::collect_markdown_files(root, max_bytes) {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        let Ok(text) = std::fs::read_to_string(&file) else { continue };
+        let (replaced, replacements, blocks) = replace_text(&text, re, replacement, options.regex);
+        if replacements == 0 || replaced == text {
+            continue;
+        }
+        let path = file.to_string_lossy().into_owned();
+        let preview = dry_run.then(|| {
+            blocks
+                .into_iter()
+                .map(|(line, old, new)| PlannedChange { file: path.clone(), line, old, new })
+                .collect()
+        });
+        if !dry_run {
+            if let Err(e) = links::write_rewrite(locks, &file, &text, &replaced) {
+                results.failed.push(FailedRewrite { path, message: e.to_string() });
+                continue;
+            }
+        }
+        results.files.push(FileReplacement { file: path, replacements, preview });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(case_sensitive: bool, whole_word: bool, regex: bool) -> ReplaceOptions {
+        ReplaceOptions { case_sensitive, whole_word, regex, dry_run: false }
+    }
+
+    #[test]
+    fn options_shape_the_pattern() {
+        let text = "Cat catalog cat\r\nno match\nCAT\n";
+        let re = build_pattern("cat", options(false, true, false)).unwrap();
+        let (out, count, blocks) = replace_text(text, &re, "dog", false);
+        assert_eq!(out, "dog catalog dog\r\nno match\ndog\n");
+        assert_eq!(count, 3);
+        assert_eq!(blocks[0], (1, "Cat catalog cat".to_string(), "dog catalog dog".to_string()));
+        assert_eq!(blocks[1].0, 3);
+
+        let re = build_pattern("cat", options(true, false, false)).unwrap();
+        assert_eq!(replace_text(text, &re, "$1", false).0, "Cat $1alog $1\r\nno match\nCAT\n");
+
+        let re = build_pattern(r"(\w+)@(\w+)", options(true, false, true)).unwrap();
+        assert_eq!(replace_text("a@b, c@d", &re, "$2@$1", true).0, "b@a, d@c");
+        assert!(build_pattern("(", options(true, false, true)).is_err());
+        assert!(build_pattern("", options(true, false, false)).is_err());
+    }
+
+    #[test]
+    fn a_match_across_lines_is_one_block() {
+        let re = build_pattern(r"one\ntwo", options(true, false, true)).unwrap();
+        let (out, count, blocks) = replace_text("zero\nx one\ntwo y\nthree", &re, "1-2", true);
+        assert_eq!((out.as_str(), count), ("zero\nx 1-2 y\nthree", 1));
+        assert_eq!(blocks, [(2, "x one\ntwo y".to_string(), "x 1-2 y".to_string())]);
+    }
+
+    #[test]
+    fn replaces_in_workspace_files_but_not_hidden_folders() {
+        let root = std::env::temp_dir().join(format!("mermark-replace-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules")).unwrap();
+        std::fs::write(root.join("a.md"), "old term, old\n").unwrap();
+        std::fs::write(root.join("b.md"), "nothing\n").unwrap();
+        std::fs::write(root.join(".git/c.md"), "old\n").unwrap();
+        std::fs::write(root.join("node_modules/d.md"), "old\n").unwrap();
+        let re = build_pattern("old", ReplaceOptions::default()).unwrap();
+        let cancel = AtomicBool::new(false);
+        let locks = FileLocks::default();
+
+        let dry_run = ReplaceOptions { dry_run: true, ..ReplaceOptions::default() };
+        let preview = replace_in_files(&locks, &root, &re, "new", dry_run, u64::MAX, &cancel).unwrap();
+        assert_eq!(preview.files.len(), 1);
+        assert_eq!(preview.files[0].replacements, 2);
+        assert_eq!(preview.files[0].preview.as_ref().unwrap()[0].new, "new term, new");
+        assert_eq!(std::fs::read_to_string(root.join("a.md")).unwrap(), "old term, old\n");
+
+        let done = replace_in_files(&locks, &root, &re, "new", ReplaceOptions::default(), u64::MAX, &cancel).unwrap();
+        assert!(done.files[0].preview.is_none());
+        assert!(done.failed.is_empty());
+        assert_eq!(std::fs::read_to_string(root.join("a.md")).unwrap(), "new term, new\n");
+        assert_eq!(std::fs::read_to_string(root.join(".git/c.md")).unwrap(), "old\n");
+        assert_eq!(std::fs::read_to_string(root.join("node_modules/d.md")).unwrap(), "old\n");
+
+        cancel.store(true, Ordering::Relaxed);
+        std::fs::write(root.join("a.md"), "old\n").unwrap();
+        let cancelled = replace_in_files(&locks, &root, &re, "new", ReplaceOptions::default(), u64::MAX, &cancel).unwrap();
+        assert!(cancelled.files.is_empty());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
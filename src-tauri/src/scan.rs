@@ -0,0 +1,189 @@
+//! Streaming workspace scan for very large vaults.
+//!
+//! `read_workspace_tree` returns the whole tree in one response, which for
+//! tens of thousands of files means a long silent pause. The scan here walks
+//! the same entries (same hidden/visibility rules) but emits them in batches
+//! as `scan-entry` events, then one `scan-complete`, and can be cancelled
+//! between batches by `scan_id`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+
+/// Entries per `scan-entry` event.
+pub const BATCH_SIZE: usize = 200;
+
+/// Cancel flags of the scans currently running, by `scan_id`.
+#[derive(Default)]
+pub struct ScanRegistry(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+impl ScanRegistry {
+    pub fn register(&self, scan_id: &str) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        self.0.lock().unwrap().insert(scan_id.to_string(), flag.clone());
+        flag
+    }
+
+    pub fn finish(&self, scan_id: &str) {
+        self.0.lock().unwrap().remove(scan_id);
+    }
+
+    /// Returns false when no scan with that id is running.
+    pub fn cancel(&self, scan_id: &str) -> bool {
+        match self.0.lock().unwrap().get(scan_id) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanEntry {
+    pub name: String,
+    pub path: String,
+    /// Folder that contains this entry; the frontend hangs nodes off it.
+    pub parent: String,
+    /// "file" or "folder"
+    pub kind: &'static str,
+    /// Milliseconds since the Unix epoch (0 if unavailable).
+    pub modified: u64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanBatch {
+    pub scan_id: String,
+    pub entries: Vec<ScanEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanComplete {
+    pub scan_id: String,
+    pub total: usize,
+    pub cancelled: bool,
+}
+
+fn modified_ms(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn sort_key(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// Walk `root` breadth-first, handing `emit` full batches of entries (and a
+/// final partial one). A folder's entries always arrive after the folder.
+/// Returns how many entries were emitted and whether `cancel` stopped it.
+pub fn walk(root: &Path, cancel: &AtomicBool, mut emit: impl FnMut(Vec<ScanEntry>)) -> (usize, bool) {
+    let mut total = 0;
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut queue = std::collections::VecDeque::from([(root.to_path_buf(), 0usize)]);
+
+    while let Some((dir, depth)) = queue.pop_front() {
+        if cancel.load(Ordering::Relaxed) {
+            return (total, true);
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        let mut folders: Vec<PathBuf> = Vec::new();
+        let mut files: Vec<PathBuf> = Vec::new();
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if crate::is_workspace_hidden(&name) {
+                continue;
+            }
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => folders.push(entry.path()),
+                Ok(t) if t.is_file() && crate::is_workspace_visible(&name) => files.push(entry.path()),
+                _ => {}
+            }
+        }
+        folders.sort_by_key(|p| sort_key(p));
+        files.sort_by_key(|p| sort_key(p));
+
+        let parent = dir.to_string_lossy().into_owned();
+        for (path, kind) in folders
+            .iter()
+            .map(|p| (p, "folder"))
+            .chain(files.iter().map(|p| (p, "file")))
+        {
+            batch.push(ScanEntry {
+                name: path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+                path: path.to_string_lossy().into_owned(),
+                parent: parent.clone(),
+                kind,
+                modified: modified_ms(path),
+            });
+            if batch.len() == BATCH_SIZE {
+                total += batch.len();
+                emit(std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE)));
+                if cancel.load(Ordering::Relaxed) {
+                    return (total, true);
+                }
+            }
+        }
+        if depth + 1 < crate::WORKSPACE_TREE_MAX_DEPTH {
+            queue.extend(folders.into_iter().map(|f| (f, depth + 1)));
+        }
+    }
+    if !batch.is_empty() {
+        total += batch.len();
+        emit(batch);
+    }
+    (total, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mermark-scan-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn emits_batches_parents_first_and_skips_hidden() {
+        let root = temp_dir();
+        std::fs::create_dir_all(root.join("notes/.git")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules")).unwrap();
+        for i in 0..(BATCH_SIZE + 5) {
+            std::fs::write(root.join("notes").join(format!("{:03}.md", i)), "x").unwrap();
+        }
+        std::fs::write(root.join("readme.md"), "x").unwrap();
+        std::fs::write(root.join("data.json"), "{}").unwrap();
+
+        let mut batches = Vec::new();
+        let (total, cancelled) = walk(&root, &AtomicBool::new(false), |b| batches.push(b));
+        assert!(!cancelled);
+        assert_eq!(total, BATCH_SIZE + 7);
+        assert_eq!(batches.len(), 2);
+        let all: Vec<ScanEntry> = batches.concat();
+        assert_eq!((all[0].name.as_str(), all[0].kind), ("notes", "folder"));
+        assert_eq!(all[1].name, "readme.md");
+        assert!(all.iter().all(|e| e.name != ".git" && e.name != "node_modules" && e.name != "data.json"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let root = temp_dir();
+        std::fs::write(root.join("a.md"), "x").unwrap();
+        let (total, cancelled) = walk(&root, &AtomicBool::new(true), |_| panic!("no batches after cancel"));
+        assert_eq!((total, cancelled), (0, true));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,168 @@
+//! Hidden "Run self-test" diagnostic: runs each export stage on a built-in
+//! sample, times it and reports which optional tools are installed, so a bug
+//! report can say which pipeline is broken on the user's machine.
+//!
+//! Markdown-to-HTML and Mermaid rendering live in the editor webview; the
+//! frontend runs those stages itself and passes their results in, so the
+//! report covers the whole pipeline in one place.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{WebviewUrl, WebviewWindowBuilder};
+
+use crate::markdown;
+
+pub const WINDOW_LABEL: &str = "window-selftest";
+
+/// How long the hidden print webview may take to load the sample page.
+const WEBVIEW_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub const SAMPLE_MARKDOWN: &str = "\
+---
+title: Self-test
+---
+# Self-test
+
+Some *text* with a [link](https://example.com \"Example\").
+
+|a|b|
+|-|:-:|
+|1|2|
+
+```mermaid
+graph TD
+  A --> B
+```
+";
+
+/// Served at `mermarkprint://localhost/selftest` while the webview stage runs.
+pub const SAMPLE_HTML: &str = "<!doctype html><html><head><meta charset=\"utf-8\">\
+<title>MerMark self-test</title></head><body><h1>Self-test</h1>\
+<table><tr><th>a</th><th>b</th></tr><tr><td>1</td><td>2</td></tr></table></body></html>";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageResult {
+    pub name: String,
+    pub ok: bool,
+    pub millis: u64,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolInfo {
+    pub name: String,
+    pub path: Option<String>,
+    /// First line of `--version`.
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    pub app_version: String,
+    pub os: String,
+    pub stages: Vec<StageResult>,
+    pub tools: Vec<ToolInfo>,
+}
+
+fn timed(name: &str, run: impl FnOnce() -> Result<(), String>) -> StageResult {
+    let started = Instant::now();
+    let result = run();
+    StageResult {
+        name: name.to_string(),
+        ok: result.is_ok(),
+        millis: started.elapsed().as_millis() as u64,
+        detail: result.err(),
+    }
+}
+
+/// The backend Markdown transforms the exports run before rendering.
+pub fn markdown_stage() -> StageResult {
+    timed("markdownTransforms", || {
+        let (_, body) = markdown::frontmatter::split(SAMPLE_MARKDOWN).ok_or("front matter not detected")?;
+        let formatted = markdown::format::format(body, &Default::default());
+        if markdown::format::format(&formatted, &Default::default()) != formatted {
+            return Err("formatting is not stable".into());
+        }
+        if !formatted.contains("| a   |  b  |") {
+            return Err("table was not padded".into());
+        }
+        let section = markdown::range::slice(
+            SAMPLE_MARKDOWN,
+            markdown::range::LineRange { start_line: 13, end_line: 13 },
+        )
+        .map_err(|e| e.to_string())?;
+        if !section.contains("graph TD") {
+            return Err("section export lost the diagram".into());
+        }
+        Ok(())
+    })
+}
+
+/// Load the sample page through the print protocol in a hidden webview, the
+/// same path PDF export takes, and wait for it to finish loading.
+pub async fn webview_stage(app: &tauri::AppHandle) -> StageResult {
+    let started = Instant::now();
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let tx = Mutex::new(Some(tx));
+    let built = crate::print_url("selftest").and_then(|url| {
+        crate::portable::with_data_dir(WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::CustomProtocol(url)))
+            .title("MerMark — Self-test")
+            .visible(false)
+            .on_page_load(move |_, payload| {
+                if matches!(payload.event(), tauri::webview::PageLoadEvent::Finished) {
+                    if let Some(tx) = tx.lock().unwrap().take() {
+                        let _ = tx.send(());
+                    }
+                }
+            })
+            .build()
+            .map_err(Into::into)
+    });
+    let detail = match built {
+        Err(e) => Some(format!("could not create webview: {}", e)),
+        Ok(window) => {
+            let loaded = tokio::time::timeout(WEBVIEW_TIMEOUT, rx).await;
+            let _ = window.destroy();
+            match loaded {
+                Ok(Ok(())) => None,
+                _ => Some(format!("page did not load within {}s", WEBVIEW_TIMEOUT.as_secs())),
+            }
+        }
+    };
+    StageResult {
+        name: "printWebview".to_string(),
+        ok: detail.is_none(),
+        millis: started.elapsed().as_millis() as u64,
+        detail,
+    }
+}
+
+pub async fn tool(name: &str) -> ToolInfo {
+    let (program, path) = crate::ai::cli::resolve_info(name);
+    let version = match path {
+        None => None,
+        Some(_) => tokio::process::Command::new(&program)
+            .arg("--version")
+            .output()
+            .await
+            .ok()
+            .filter(|out| out.status.success())
+            .and_then(|out| String::from_utf8_lossy(&out.stdout).lines().next().map(|l| l.trim().to_string())),
+    };
+    ToolInfo { name: name.to_string(), path, version }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_stage_passes_on_the_sample() {
+        let stage = markdown_stage();
+        assert!(stage.ok, "{:?}", stage.detail);
+    }
+}
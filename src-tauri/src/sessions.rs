@@ -0,0 +1,244 @@
+//! Named session snapshots in `<app_data>/sessions/<name>.json`: every
+//! editor window with its open files and window state, so a whole set of
+//! windows can be saved and brought back later.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::error::{AppError, AppResult};
+use crate::window_state::{self, WindowState};
+
+pub const SESSIONS_DIR: &str = "sessions";
+
+const MAX_NAME_LEN: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionWindow {
+    /// In tab order.
+    pub files: Vec<String>,
+    /// Index into `files` of the tab that was active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<usize>,
+    #[serde(default)]
+    pub state: WindowState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedSession {
+    /// Milliseconds since the Unix epoch.
+    pub saved_at: i64,
+    pub windows: Vec<SessionWindow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub name: String,
+    pub saved_at: i64,
+    pub windows: usize,
+    pub files: usize,
+}
+
+/// A window's file tabs in tab-bar order and the index of the active one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TabOrder {
+    pub files: Vec<String>,
+    pub active: Option<usize>,
+}
+
+/// Tabs a window created by `load_session` should open once its frontend
+/// is up, keyed by window label. Taken (and cleared) by that window.
+#[derive(Default)]
+pub struct PendingTabs(pub Mutex<HashMap<String, TabOrder>>);
+
+/// Each editor window's tabs as its frontend last reported them, keyed by
+/// window label, so `save_as` keeps tab order and the active tab.
+#[derive(Default)]
+pub struct WindowTabs(pub Mutex<HashMap<String, TabOrder>>);
+
+/// Names become file names, so keep them to a portable character set.
+fn validate_name(name: &str) -> AppResult<&str> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.'));
+    if !valid {
+        return Err(AppError::InvalidInput(format!("invalid session name: {:?}", name)));
+    }
+    Ok(name)
+}
+
+fn sessions_dir(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?.join(SESSIONS_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    Ok(dir)
+}
+
+fn session_file(app: &tauri::AppHandle, name: &str) -> AppResult<PathBuf> {
+    Ok(sessions_dir(app)?.join(format!("{}.json", validate_name(name)?)))
+}
+
+fn summary(name: &str, session: &NamedSession) -> SessionSummary {
+    SessionSummary {
+        name: name.to_string(),
+        saved_at: session.saved_at,
+        windows: session.windows.len(),
+        files: session.windows.iter().map(|w| w.files.len()).sum(),
+    }
+}
+
+/// Group the open-files registry (`file -> window label`) into windows,
+/// in label order with each window's files sorted.
+fn group_by_window(open_files: &HashMap<String, String>) -> BTreeMap<String, Vec<String>> {
+    let mut windows: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (file, label) in open_files {
+        windows.entry(label.clone()).or_default().push(file.clone());
+    }
+    for files in windows.values_mut() {
+        files.sort();
+    }
+    windows
+}
+
+/// A window's `registered` files in the order its frontend `reported`, with
+/// any it did not report appended. The active tab is kept when it is still
+/// registered.
+fn ordered_tabs(registered: Vec<String>, reported: Option<&TabOrder>) -> TabOrder {
+    let Some(reported) = reported else { return TabOrder { files: registered, active: None } };
+    let mut files: Vec<String> = reported.files.iter().filter(|f| registered.contains(f)).cloned().collect();
+    files.extend(registered.into_iter().filter(|f| !reported.files.contains(f)));
+    let active = reported
+        .active
+        .and_then(|i| reported.files.get(i))
+        .and_then(|file| files.iter().position(|f| f == file));
+    TabOrder { files, active }
+}
+
+/// Snapshot the open windows under `name`, replacing an earlier snapshot
+/// with the same name. Windows without files are left out.
+pub fn save_as(
+    app: &tauri::AppHandle,
+    name: &str,
+    open_files: &HashMap<String, String>,
+    tabs: &HashMap<String, TabOrder>,
+) -> AppResult<SessionSummary> {
+    let path = session_file(app, name)?;
+    let windows = group_by_window(open_files)
+        .into_iter()
+        .filter(|(label, _)| app.get_webview_window(label).is_some())
+        .map(|(label, files)| {
+            let TabOrder { files, active } = ordered_tabs(files, tabs.get(&label));
+            SessionWindow { files, active, state: window_state::get(app, &label).unwrap_or_default() }
+        })
+        .collect();
+    let session = NamedSession { saved_at: chrono::Utc::now().timestamp_millis(), windows };
+    crate::files::write_atomic(&path, &serde_json::to_vec_pretty(&session)?)?;
+    Ok(summary(validate_name(name)?, &session))
+}
+
+pub fn read(app: &tauri::AppHandle, name: &str) -> AppResult<NamedSession> {
+    let path = session_file(app, name)?;
+    let bytes = std::fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+    serde_json::from_slice(&bytes).map_err(|e| AppError::InvalidInput(format!("{}: {}", path.display(), e)))
+}
+
+/// Saved sessions, most recent first. Unreadable files are skipped.
+pub fn list(app: &tauri::AppHandle) -> AppResult<Vec<SessionSummary>> {
+    let dir = sessions_dir(app)?;
+    let entries = std::fs::read_dir(&dir).map_err(|e| AppError::io(&dir, e))?;
+    let mut out: Vec<SessionSummary> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|p| {
+            let name = p.file_stem()?.to_str()?.to_string();
+            let session: NamedSession = serde_json::from_slice(&std::fs::read(&p).ok()?).ok()?;
+            Some(summary(&name, &session))
+        })
+        .collect();
+    out.sort_by(|a, b| b.saved_at.cmp(&a.saved_at).then_with(|| a.name.cmp(&b.name)));
+    Ok(out)
+}
+
+pub fn delete(app: &tauri::AppHandle, name: &str) -> AppResult<()> {
+    let path = session_file(app, name)?;
+    std::fs::remove_file(&path).map_err(|e| AppError::io(&path, e))
+}
+
+/// Split a window's files into the tabs still on disk and the missing
+/// files. The active tab is dropped if its file is gone.
+pub fn existing_files(window: &SessionWindow) -> (TabOrder, Vec<String>) {
+    let (files, missing): (Vec<String>, Vec<String>) = window.files.iter().cloned().partition(|f| Path::new(f).is_file());
+    let active = window
+        .active
+        .and_then(|i| window.files.get(i))
+        .and_then(|file| files.iter().position(|f| f == file));
+    (TabOrder { files, active }, missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_restricted_to_safe_file_names() {
+        assert_eq!(validate_name("  writing  ").unwrap(), "writing");
+        assert!(validate_name("code docs_2").is_ok());
+        for bad in ["", "../x", "a/b", "a\\b", ".hidden", "x:y"] {
+            assert!(validate_name(bad).is_err(), "{:?} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn registry_is_grouped_per_window_in_stable_order() {
+        let open: HashMap<String, String> = [("/b.md", "main"), ("/a.md", "main"), ("/c.md", "window-2")]
+            .into_iter()
+            .map(|(f, l)| (f.to_string(), l.to_string()))
+            .collect();
+        let grouped: Vec<(String, Vec<String>)> = group_by_window(&open).into_iter().collect();
+        assert_eq!(
+            grouped,
+            [
+                ("main".to_string(), vec!["/a.md".to_string(), "/b.md".to_string()]),
+                ("window-2".to_string(), vec!["/c.md".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn reported_tab_order_and_active_tab_are_kept() {
+        let registered = vec!["/a.md".to_string(), "/b.md".to_string(), "/c.md".to_string()];
+        assert_eq!(ordered_tabs(registered.clone(), None), TabOrder { files: registered.clone(), active: None });
+        let reported = TabOrder { files: vec!["/c.md".to_string(), "/gone.md".to_string(), "/a.md".to_string()], active: Some(2) };
+        assert_eq!(
+            ordered_tabs(registered, Some(&reported)),
+            TabOrder { files: vec!["/c.md".to_string(), "/a.md".to_string(), "/b.md".to_string()], active: Some(1) }
+        );
+    }
+
+    #[test]
+    fn missing_files_are_split_off() {
+        let dir = std::env::temp_dir().join(format!("mermark-session-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let kept = dir.join("kept.md");
+        std::fs::write(&kept, "# kept").unwrap();
+        let gone = dir.join("gone.md").to_string_lossy().into_owned();
+        let mut window = SessionWindow {
+            files: vec![gone.clone(), kept.to_string_lossy().into_owned()],
+            active: Some(1),
+            state: WindowState::default(),
+        };
+        let (existing, missing) = existing_files(&window);
+        assert_eq!(existing, TabOrder { files: vec![kept.to_string_lossy().into_owned()], active: Some(0) });
+        assert_eq!(missing, [gone]);
+        window.active = Some(0);
+        assert_eq!(existing_files(&window).0.active, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
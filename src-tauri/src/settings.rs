@@ -0,0 +1,141 @@
+//! Backend-side application settings, persisted to `<app_data>/settings.json`
+//! and mirrored in managed state so commands can read them without I/O.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreference {
+    Light,
+    Dark,
+    /// Follow the OS appearance, including live changes.
+    #[default]
+    Auto,
+}
+
+impl ThemePreference {
+    /// Theme to force on native windows; `None` lets them follow the OS.
+    pub fn window_theme(self) -> Option<tauri::Theme> {
+        match self {
+            ThemePreference::Light => Some(tauri::Theme::Light),
+            ThemePreference::Dark => Some(tauri::Theme::Dark),
+            ThemePreference::Auto => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    pub theme: ThemePreference,
+    /// Files larger than this are refused by `read_text_file` unless forced,
+    /// and skipped by workspace-wide scans.
+    pub max_open_bytes: u64,
+    /// Let `open_path_external` launch programs and scripts linked from a
+    /// document. Off by default.
+    pub allow_executables: bool,
+    /// Remember scroll/cursor per document across restarts (positions.json).
+    pub remember_positions: bool,
+    /// Kiosk/embedded deployments: one window only. New windows and tab
+    /// transfers are refused and every open lands in the main window.
+    pub single_window_mode: bool,
+    /// Close a window once its last tab is moved to another window. When
+    /// off, the window stays open on an empty document instead.
+    pub close_window_on_last_transfer: bool,
+    /// Hold autosave ticks and reload prompts while no window has focus.
+    /// Off keeps background work running at all times.
+    pub pause_background_when_unfocused: bool,
+    /// Time every command call for `get_command_metrics`. In memory only.
+    pub collect_metrics: bool,
+    /// Bundled Mermaid major version used for rendering, export and checks
+    /// unless a document pins its own with `mermaid_version:`.
+    pub mermaid_version: String,
+    /// Command run after a save, per workspace root, as an argv list (see
+    /// `on_save`). Set from the editor only; a workspace can't add one.
+    pub on_save_commands: BTreeMap<String, Vec<String>>,
+    /// Write the troubleshooting log (`mermark.log`). Paths and command
+    /// names only; turn off for privacy.
+    pub file_logging: bool,
+    pub log_level: crate::logging::LogLevel,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            theme: ThemePreference::default(),
+            max_open_bytes: crate::files::DEFAULT_MAX_OPEN_BYTES,
+            allow_executables: false,
+            remember_positions: false,
+            single_window_mode: false,
+            close_window_on_last_transfer: true,
+            pause_background_when_unfocused: true,
+            collect_metrics: false,
+            mermaid_version: crate::markdown::mermaid::DEFAULT_VERSION.to_string(),
+            on_save_commands: BTreeMap::new(),
+            file_logging: true,
+            log_level: crate::logging::LogLevel::default(),
+        }
+    }
+}
+
+pub struct SettingsState(pub Mutex<AppSettings>);
+
+fn settings_file(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    Ok(dir.join("settings.json"))
+}
+
+/// Read persisted settings; missing or unreadable files yield defaults, and
+/// a corrupt one is backed up first so the next save does not bury it.
+pub fn load(app: &tauri::AppHandle) -> AppSettings {
+    let Ok(path) = settings_file(app) else { return AppSettings::default() };
+    let Ok(bytes) = std::fs::read(&path) else { return AppSettings::default() };
+    serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+        crate::config_recovery::recover(app, &path, &e);
+        AppSettings::default()
+    })
+}
+
+pub fn save(app: &tauri::AppHandle, settings: &AppSettings) -> AppResult<()> {
+    let path = settings_file(app)?;
+    crate::files::write_atomic(&path, &serde_json::to_vec_pretty(settings)?)
+}
+
+/// Snapshot of the in-memory settings.
+pub fn current(app: &tauri::AppHandle) -> AppSettings {
+    app.state::<SettingsState>().0.lock().unwrap().clone()
+}
+
+pub fn theme_name(theme: tauri::Theme) -> &'static str {
+    match theme {
+        tauri::Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_auto_theme() {
+        let s: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(s.theme, ThemePreference::Auto);
+        assert!(s.theme.window_theme().is_none());
+        assert_eq!(s.max_open_bytes, crate::files::DEFAULT_MAX_OPEN_BYTES);
+        assert!(s.close_window_on_last_transfer);
+    }
+
+    #[test]
+    fn theme_preference_serializes_lowercase() {
+        let v = serde_json::to_value(AppSettings { theme: ThemePreference::Dark, ..Default::default() }).unwrap();
+        assert_eq!(v["theme"], "dark");
+    }
+}
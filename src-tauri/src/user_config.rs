@@ -0,0 +1,180 @@
+//! Hand-edited user configuration in `<app_data>`: `snippets.json`,
+//! `keymap.json` and the `templates/` folder. A watcher reloads them on
+//! change and emits `config-reloaded { which }`; malformed JSON emits
+//! `config-error { file, message }` and the last good copy stays in use.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::error::{AppError, AppResult};
+
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// JSON config files and the `which` name they are reported under.
+const JSON_FILES: &[(&str, &str)] = &[("snippets.json", "snippets"), ("keymap.json", "keymap")];
+
+/// Last successfully parsed copy of each JSON file, keyed by `which`.
+#[derive(Default)]
+pub struct UserConfig {
+    values: Mutex<HashMap<String, serde_json::Value>>,
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigReloaded {
+    pub which: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigError {
+    pub file: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Template {
+    pub name: String,
+    pub content: String,
+}
+
+fn which_for(path: &Path, dir: &Path) -> Option<&'static str> {
+    if path.parent() == Some(&dir.join(TEMPLATES_DIR)) {
+        return Some("templates");
+    }
+    if path.parent() != Some(dir) {
+        return None;
+    }
+    let name = path.file_name()?.to_str()?;
+    JSON_FILES.iter().find(|(file, _)| *file == name).map(|(_, which)| *which)
+}
+
+impl UserConfig {
+    /// Re-read one JSON file into the cache. A missing file clears the entry;
+    /// a malformed one leaves the previous value in place and errors.
+    fn reload_json(&self, which: &str, path: &Path) -> Result<(), String> {
+        let parsed = match std::fs::read(path) {
+            Ok(bytes) => Some(serde_json::from_slice::<serde_json::Value>(&bytes).map_err(|e| e.to_string())?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut values = self.values.lock().unwrap();
+        match parsed {
+            Some(value) => values.insert(which.to_string(), value),
+            None => values.remove(which),
+        };
+        Ok(())
+    }
+
+    pub fn get(&self, which: &str) -> Option<serde_json::Value> {
+        self.values.lock().unwrap().get(which).cloned()
+    }
+}
+
+fn config_dir(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?;
+    let templates = dir.join(TEMPLATES_DIR);
+    std::fs::create_dir_all(&templates).map_err(|e| AppError::io(&templates, e))?;
+    Ok(dir)
+}
+
+fn handle_change(app: &tauri::AppHandle, dir: &Path, path: &Path) {
+    let Some(which) = which_for(path, dir) else { return };
+    if which != "templates" {
+        if let Err(message) = app.state::<UserConfig>().reload_json(which, path) {
+            tracing::warn!(file = %path.display(), %message, "config file is malformed; keeping last good copy");
+            let file = path.to_string_lossy().into_owned();
+            let _ = app.emit("config-error", ConfigError { file, message });
+            return;
+        }
+    }
+    let _ = app.emit("config-reloaded", ConfigReloaded { which: which.to_string() });
+}
+
+/// Load the files once and start watching. Called from setup.
+pub fn start(app: &tauri::AppHandle) -> AppResult<()> {
+    let dir = config_dir(app)?;
+    let config = app.state::<UserConfig>();
+    for (file, which) in JSON_FILES {
+        if let Err(message) = config.reload_json(which, &dir.join(file)) {
+            tracing::warn!(file, %message, "config file is malformed");
+        }
+    }
+
+    let handle = app.clone();
+    let watch_dir = dir.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else { return };
+        if event.kind.is_access() {
+            return;
+        }
+        for path in &event.paths {
+            handle_change(&handle, &watch_dir, path);
+        }
+    })
+    .map_err(|e| AppError::Internal(format!("config watcher: {}", e)))?;
+    // Editors save by rename, so watch the folders rather than the files.
+    for path in [dir.clone(), dir.join(TEMPLATES_DIR)] {
+        watcher
+            .watch(&path, RecursiveMode::NonRecursive)
+            .map_err(|e| AppError::Internal(format!("watch {}: {}", path.display(), e)))?;
+    }
+    *config.watcher.lock().unwrap() = Some(watcher);
+    Ok(())
+}
+
+/// Text files in `templates/`, sorted by name.
+pub fn templates(app: &tauri::AppHandle) -> AppResult<Vec<Template>> {
+    let dir = config_dir(app)?.join(TEMPLATES_DIR);
+    let entries = std::fs::read_dir(&dir).map_err(|e| AppError::io(&dir, e))?;
+    let mut out: Vec<Template> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && !crate::is_workspace_hidden(&p.file_name().unwrap_or_default().to_string_lossy()))
+        .filter_map(|p| {
+            let content = crate::files::read_text(&p, Some(crate::files::DEFAULT_MAX_OPEN_BYTES)).ok()?;
+            Some(Template { name: p.file_name()?.to_string_lossy().into_owned(), content })
+        })
+        .collect();
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_json_keeps_last_good_copy() {
+        let dir = std::env::temp_dir().join(format!("mermark-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("keymap.json");
+        let config = UserConfig::default();
+
+        std::fs::write(&path, r#"{"save": "Ctrl+S"}"#).unwrap();
+        config.reload_json("keymap", &path).unwrap();
+        std::fs::write(&path, r#"{"save": "#).unwrap();
+        assert!(config.reload_json("keymap", &path).is_err());
+        assert_eq!(config.get("keymap").unwrap()["save"], "Ctrl+S");
+
+        std::fs::remove_file(&path).unwrap();
+        config.reload_json("keymap", &path).unwrap();
+        assert!(config.get("keymap").is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn changes_are_classified_by_location() {
+        let dir = Path::new("/data");
+        assert_eq!(which_for(Path::new("/data/snippets.json"), dir), Some("snippets"));
+        assert_eq!(which_for(Path::new("/data/templates/meeting.md"), dir), Some("templates"));
+        assert_eq!(which_for(Path::new("/data/settings.json"), dir), None);
+        assert_eq!(which_for(Path::new("/data/other/keymap.json"), dir), None);
+    }
+}
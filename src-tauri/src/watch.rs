@@ -0,0 +1,109 @@
+//! Per-window suspension of the external-change watcher, so bulk commands
+//! that rewrite many files do not set off a reload prompt for each one.
+//! Suspensions nest, and every suspend re-arms a safety timeout that resumes
+//! watching even if the caller never does.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::Serialize;
+
+/// Longest a window stays suspended without another `suspend_watch`.
+pub const SUSPEND_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy)]
+struct Suspension {
+    depth: u32,
+    /// Bumped on every suspend so only the newest timeout can expire it.
+    generation: u64,
+}
+
+#[derive(Default)]
+pub struct WatchSuspensions {
+    windows: Mutex<HashMap<String, Suspension>>,
+    next_generation: Mutex<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchSuspendedPayload {
+    pub suspended: bool,
+}
+
+impl WatchSuspensions {
+    /// Add one level of suspension. Returns the new depth and the generation
+    /// the caller's timeout should expire.
+    pub fn suspend(&self, label: &str) -> (u32, u64) {
+        let generation = {
+            let mut next = self.next_generation.lock().unwrap();
+            *next += 1;
+            *next
+        };
+        let mut windows = self.windows.lock().unwrap();
+        let entry = windows.entry(label.to_string()).or_insert(Suspension { depth: 0, generation });
+        entry.depth += 1;
+        entry.generation = generation;
+        (entry.depth, generation)
+    }
+
+    /// Drop one level. Returns the remaining depth; extra resumes are no-ops.
+    pub fn resume(&self, label: &str) -> u32 {
+        let mut windows = self.windows.lock().unwrap();
+        let Some(entry) = windows.get_mut(label) else { return 0 };
+        entry.depth -= 1;
+        let depth = entry.depth;
+        if depth == 0 {
+            windows.remove(label);
+        }
+        depth
+    }
+
+    /// Clear the suspension if `generation` is still the newest one, i.e.
+    /// nobody suspended again since that timeout was armed.
+    pub fn expire(&self, label: &str, generation: u64) -> bool {
+        let mut windows = self.windows.lock().unwrap();
+        if windows.get(label).is_some_and(|s| s.generation == generation) {
+            windows.remove(label);
+            return true;
+        }
+        false
+    }
+
+    pub fn is_suspended(&self, label: &str) -> bool {
+        self.windows.lock().unwrap().contains_key(label)
+    }
+
+    /// Close hook: a closed window keeps no suspension behind.
+    pub fn forget(&self, label: &str) {
+        self.windows.lock().unwrap().remove(label);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_suspensions_resume_at_the_outermost() {
+        let s = WatchSuspensions::default();
+        assert_eq!(s.suspend("main").0, 1);
+        assert_eq!(s.suspend("main").0, 2);
+        assert_eq!(s.resume("main"), 1);
+        assert!(s.is_suspended("main"));
+        assert_eq!(s.resume("main"), 0);
+        assert!(!s.is_suspended("main"));
+        assert_eq!(s.resume("main"), 0);
+        assert!(!s.is_suspended("window-2"));
+    }
+
+    #[test]
+    fn only_the_newest_timeout_expires() {
+        let s = WatchSuspensions::default();
+        let (_, first) = s.suspend("main");
+        let (_, second) = s.suspend("main");
+        assert!(!s.expire("main", first));
+        assert!(s.is_suspended("main"));
+        assert!(s.expire("main", second));
+        assert!(!s.is_suspended("main"));
+    }
+}
@@ -0,0 +1,346 @@
+//! Per-window UI state persisted across launches, keyed by window label, so
+//! session restore brings each window back the way it was left.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowState {
+    pub always_on_top: bool,
+    /// Distraction-free fullscreen; the frontend hides its chrome while on.
+    pub zen: bool,
+    pub layout: Layout,
+    /// Inner size in logical pixels when the window was last closed (or the
+    /// size it was created with); `None` keeps the default.
+    pub size: Option<WindowSize>,
+    /// Webview zoom (1.0 is 100%) last set on this window; `None` is 100%.
+    pub zoom: Option<f64>,
+    /// Zoom last set while on each display, by monitor name, so moving the
+    /// window to another screen brings back that screen's zoom.
+    pub monitor_zoom: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Panel arrangement of one window, owned by the frontend and persisted here.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Layout {
+    pub sidebar_visible: bool,
+    /// Pixels; kept within the sidebar's drag limits.
+    pub sidebar_width: f64,
+    pub outline_visible: bool,
+    /// Editor share of the editor/preview split, 0..1.
+    pub split_fraction: f64,
+}
+
+const SIDEBAR_WIDTH_MIN: f64 = 160.0;
+const SIDEBAR_WIDTH_MAX: f64 = 480.0;
+const SPLIT_FRACTION_MIN: f64 = 0.1;
+const SPLIT_FRACTION_MAX: f64 = 0.9;
+pub const ZOOM_MIN: f64 = 0.5;
+pub const ZOOM_MAX: f64 = 3.0;
+
+pub fn clamp_zoom(factor: f64) -> f64 {
+    if factor.is_finite() { factor.clamp(ZOOM_MIN, ZOOM_MAX) } else { 1.0 }
+}
+
+impl WindowState {
+    /// Zoom to use on `monitor`: the one set there, else the window's last.
+    pub fn zoom_for(&self, monitor: Option<&str>) -> f64 {
+        let on_monitor = monitor.and_then(|m| self.monitor_zoom.get(m)).copied();
+        clamp_zoom(on_monitor.or(self.zoom).unwrap_or(1.0))
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            sidebar_visible: true,
+            sidebar_width: 240.0,
+            outline_visible: false,
+            split_fraction: 0.5,
+        }
+    }
+}
+
+impl Layout {
+    /// Clamp sizes so a layout saved from a bigger screen (or a bad drag)
+    /// cannot restore with a panel squeezed to nothing.
+    pub fn sanitized(self) -> Self {
+        let defaults = Layout::default();
+        let width = if self.sidebar_width.is_finite() { self.sidebar_width } else { defaults.sidebar_width };
+        let split = if self.split_fraction.is_finite() { self.split_fraction } else { defaults.split_fraction };
+        Self {
+            sidebar_width: width.clamp(SIDEBAR_WIDTH_MIN, SIDEBAR_WIDTH_MAX),
+            split_fraction: split.clamp(SPLIT_FRACTION_MIN, SPLIT_FRACTION_MAX),
+            ..self
+        }
+    }
+}
+
+/// Labels of windows currently in zen mode. Kept in memory so the resize
+/// handler can spot an OS-initiated fullscreen exit without touching disk.
+#[derive(Default)]
+pub struct ZenWindows(pub Mutex<HashSet<String>>);
+
+/// Windows whose last tab was transferred out together with their layout.
+/// Until they close, their own layout saves are dropped and on close their
+/// state is forgotten, so a closing empty window cannot undo the handoff.
+#[derive(Default)]
+pub struct LayoutHandoffs(pub Mutex<HashSet<String>>);
+
+/// Display each window's zoom was last applied for, so moves within one
+/// display don't re-read the store.
+#[derive(Default)]
+pub struct ZoomMonitors(pub Mutex<HashMap<String, String>>);
+
+#[derive(Clone, Serialize)]
+pub struct ZenModePayload {
+    pub on: bool,
+}
+
+pub(crate) type Store = HashMap<String, WindowState>;
+
+/// Process-wide lock around read-modify-write of `window-state.json` so two
+/// windows updating their state at once cannot drop each other's entries.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn store_file(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    let dir = crate::portable::resolve_config_root(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    Ok(dir.join("window-state.json"))
+}
+
+fn load_store(app: &tauri::AppHandle) -> AppResult<Store> {
+    let path = store_file(app)?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let bytes = std::fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+    Ok(serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+        crate::config_recovery::recover(app, &path, &e);
+        HashMap::new()
+    }))
+}
+
+fn save_store(app: &tauri::AppHandle, store: &Store) -> AppResult<()> {
+    let path = store_file(app)?;
+    let bytes = serde_json::to_vec_pretty(store)?;
+    crate::files::write_atomic(&path, &bytes)
+}
+
+pub fn get(app: &tauri::AppHandle, label: &str) -> AppResult<WindowState> {
+    Ok(load_store(app)?.get(label).cloned().unwrap_or_default())
+}
+
+/// Apply `change` to the stored state of `label` and return the result.
+pub fn update(
+    app: &tauri::AppHandle,
+    label: &str,
+    change: impl FnOnce(&mut WindowState),
+) -> AppResult<WindowState> {
+    let _g = STORE_LOCK.lock().unwrap();
+    let mut store = load_store(app)?;
+    let entry = store.entry(label.to_string()).or_default();
+    change(entry);
+    let updated = entry.clone();
+    save_store(app, &store)?;
+    Ok(updated)
+}
+
+/// Drop everything stored for `label`.
+pub fn forget(app: &tauri::AppHandle, label: &str) -> AppResult<()> {
+    let _g = STORE_LOCK.lock().unwrap();
+    let mut store = load_store(app)?;
+    if store.remove(label).is_some() {
+        save_store(app, &store)?;
+    }
+    Ok(())
+}
+
+/// Copy the layout of `from` onto `to` when `from` gives up its last tab,
+/// and mark `from` as handed off. Returns the carried layout.
+pub fn hand_off_layout(app: &tauri::AppHandle, from: &str, to: &str) -> AppResult<Layout> {
+    let layout = get(app, from)?.layout.sanitized();
+    update(app, to, |s| s.layout = layout)?;
+    app.state::<LayoutHandoffs>().0.lock().unwrap().insert(from.to_string());
+    Ok(layout)
+}
+
+pub fn is_handed_off(app: &tauri::AppHandle, label: &str) -> bool {
+    app.state::<LayoutHandoffs>().0.lock().unwrap().contains(label)
+}
+
+/// Close hook: a handed-off window leaves no state behind.
+pub fn on_close(app: &tauri::AppHandle, label: &str) {
+    let handed_off = app.state::<LayoutHandoffs>().0.lock().unwrap().remove(label);
+    if handed_off {
+        let _ = forget(app, label);
+    }
+    app.state::<ZoomMonitors>().0.lock().unwrap().remove(label);
+}
+
+/// Close hook: remember the window's size for the next one with this label.
+/// Maximized and fullscreen windows keep the size stored before.
+pub fn remember_size(window: &tauri::WebviewWindow) {
+    if window.is_maximized().unwrap_or(true) || window.is_fullscreen().unwrap_or(true) {
+        return;
+    }
+    let (Ok(size), Ok(scale)) = (window.inner_size(), window.scale_factor()) else { return };
+    let size = size.to_logical::<f64>(scale);
+    let _ = update(window.app_handle(), window.label(), |s| {
+        s.size = Some(WindowSize { width: size.width, height: size.height });
+    });
+}
+
+fn monitor_name(window: &tauri::WebviewWindow) -> Option<String> {
+    window.current_monitor().ok().flatten().and_then(|m| m.name().cloned())
+}
+
+/// Zoom the window's webview, clamped to `ZOOM_MIN..=ZOOM_MAX`, and store the
+/// factor for the window and for the display it is on. Returns the factor.
+pub fn set_zoom(window: &tauri::WebviewWindow, factor: f64) -> AppResult<f64> {
+    let factor = clamp_zoom(factor);
+    window.set_zoom(factor)?;
+    let monitor = monitor_name(window);
+    update(window.app_handle(), window.label(), |s| {
+        s.zoom = Some(factor);
+        if let Some(monitor) = &monitor {
+            s.monitor_zoom.insert(monitor.clone(), factor);
+        }
+    })?;
+    if let Some(monitor) = monitor {
+        window.app_handle().state::<ZoomMonitors>().0.lock().unwrap().insert(window.label().to_string(), monitor);
+    }
+    Ok(factor)
+}
+
+/// Zoom the window should have on the display it is on now.
+pub fn current_zoom(window: &tauri::WebviewWindow) -> AppResult<f64> {
+    Ok(get(window.app_handle(), window.label())?.zoom_for(monitor_name(window).as_deref()))
+}
+
+/// Called on creation and on move: on a display the window wasn't zoomed
+/// for yet, apply the stored zoom for it.
+pub fn sync_zoom_with_monitor(window: &tauri::WebviewWindow) {
+    let monitor = monitor_name(window);
+    if let Some(monitor) = &monitor {
+        let seen = window.app_handle().state::<ZoomMonitors>();
+        let mut seen = seen.0.lock().unwrap();
+        if seen.get(window.label()) == Some(monitor) {
+            return;
+        }
+        seen.insert(window.label().to_string(), monitor.clone());
+    }
+    if let Ok(zoom) = current_zoom(window) {
+        let _ = window.set_zoom(zoom);
+    }
+}
+
+/// Enter or leave zen mode: toggles OS fullscreen, persists the flag and
+/// tells the window's frontend to hide or restore its chrome.
+pub fn set_zen(window: &tauri::WebviewWindow, on: bool) -> AppResult<bool> {
+    use tauri::Emitter;
+    let app = window.app_handle();
+    let label = window.label().to_string();
+    window.set_fullscreen(on)?;
+    {
+        let zen = app.state::<ZenWindows>();
+        let mut zen = zen.0.lock().unwrap();
+        if on {
+            zen.insert(label.clone());
+        } else {
+            zen.remove(&label);
+        }
+    }
+    update(app, &label, |s| s.zen = on)?;
+    window.emit("zen-mode", ZenModePayload { on })?;
+    Ok(on)
+}
+
+/// Called on resize: a zen window that is no longer fullscreen was taken out
+/// of it by the OS (Esc, green button, window manager), so leave zen too and
+/// let the frontend bring its chrome back.
+pub fn sync_zen_with_fullscreen(window: &tauri::WebviewWindow) {
+    let in_zen = window
+        .app_handle()
+        .state::<ZenWindows>()
+        .0
+        .lock()
+        .unwrap()
+        .contains(window.label());
+    if in_zen && !window.is_fullscreen().unwrap_or(true) {
+        let _ = set_zen(window, false);
+    }
+}
+
+/// Re-apply persisted state to a window that already exists (the `main`
+/// window created from `tauri.conf.json`). Never focuses the window: a
+/// restored always-on-top window must not grab focus on startup.
+pub fn restore(window: &tauri::WebviewWindow) {
+    let state = get(window.app_handle(), window.label()).unwrap_or_default();
+    if let Some(size) = state.size {
+        let _ = window.set_size(tauri::LogicalSize::new(size.width, size.height));
+        let _ = window.center();
+    }
+    if state.always_on_top {
+        let _ = window.set_always_on_top(true);
+    }
+    sync_zoom_with_monitor(window);
+    if state.zen {
+        let _ = set_zen(window, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let state: WindowState = serde_json::from_str("{}").unwrap();
+        assert_eq!(state, WindowState::default());
+    }
+
+    #[test]
+    fn serializes_camel_case() {
+        let state = WindowState { always_on_top: true, ..Default::default() };
+        let v: serde_json::Value = serde_json::to_value(&state).unwrap();
+        assert_eq!(v["alwaysOnTop"], true);
+        assert_eq!(v["layout"]["splitFraction"], 0.5);
+    }
+
+    #[test]
+    fn sanitized_layout_clamps_sizes() {
+        let layout = Layout { sidebar_width: 5000.0, split_fraction: f64::NAN, ..Default::default() }.sanitized();
+        assert_eq!(layout.sidebar_width, SIDEBAR_WIDTH_MAX);
+        assert_eq!(layout.split_fraction, 0.5);
+        let layout = Layout { sidebar_width: 0.0, split_fraction: 1.0, ..Default::default() }.sanitized();
+        assert_eq!((layout.sidebar_width, layout.split_fraction), (SIDEBAR_WIDTH_MIN, SPLIT_FRACTION_MAX));
+    }
+
+    #[test]
+    fn zoom_prefers_the_display_and_stays_in_range() {
+        let mut state = WindowState::default();
+        assert_eq!(state.zoom_for(Some("DP-1")), 1.0);
+        state.zoom = Some(1.5);
+        state.monitor_zoom.insert("eDP-1".to_string(), 2.0);
+        assert_eq!(state.zoom_for(Some("eDP-1")), 2.0);
+        assert_eq!(state.zoom_for(Some("DP-1")), 1.5);
+        assert_eq!(state.zoom_for(None), 1.5);
+        state.zoom = Some(9.0);
+        assert_eq!(state.zoom_for(None), ZOOM_MAX);
+        assert_eq!((clamp_zoom(0.1), clamp_zoom(f64::NAN)), (ZOOM_MIN, 1.0));
+    }
+}
@@ -1,5 +1,11 @@
-import { describe, it, expect, beforeEach } from 'vitest';
+import { describe, it, expect, beforeEach, vi } from 'vitest';
 import { mount } from '@vue/test-utils';
+
+const invokeMock = vi.fn();
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: (...args: unknown[]) => invokeMock(...args),
+}));
+
 import PdfExportDialog from '../../components/PdfExportDialog.vue';
 
 const CONTENT_HTML = '<p>Test</p>';
@@ -7,6 +13,7 @@ const CONTENT_HTML = '<p>Test</p>';
 describe('PdfExportDialog', () => {
   beforeEach(() => {
     localStorage.clear();
+    invokeMock.mockReset();
   });
 
   it('renders without crashing', () => {
@@ -71,4 +78,39 @@ describe('PdfExportDialog', () => {
     expect(values).toContain('builtin-notes');
     expect(values).toContain('builtin-draft');
   });
+
+  it('shows the line range inputs only when given the markdown', () => {
+    const plain = mount(PdfExportDialog, { props: { contentHtml: CONTENT_HTML } });
+    expect(plain.find('[data-testid="pdf-range-start"]').exists()).toBe(false);
+    const withMarkdown = mount(PdfExportDialog, {
+      props: { contentHtml: CONTENT_HTML, markdown: '# A\n\ntext\n' },
+    });
+    expect(withMarkdown.find('[data-testid="pdf-range-start"]').exists()).toBe(true);
+    expect(withMarkdown.find('[data-testid="pdf-range-end"]').exists()).toBe(true);
+  });
+
+  it('asks the backend for the slice of the chosen lines', async () => {
+    invokeMock.mockResolvedValue('## B\n\nonly this');
+    const markdown = '# A\n\n## B\n\nonly this\n';
+    const wrapper = mount(PdfExportDialog, { props: { contentHtml: CONTENT_HTML, markdown } });
+    await wrapper.find('[data-testid="pdf-range-start"]').setValue(3);
+    await wrapper.find('[data-testid="pdf-range-end"]').setValue(5);
+    await new Promise(resolve => setTimeout(resolve, 300));
+    expect(invokeMock).toHaveBeenCalledWith('export_range_markdown', {
+      markdown,
+      range: { startLine: 3, endLine: 5 },
+    });
+  });
+
+  it('shows why a range cannot be exported', async () => {
+    invokeMock.mockRejectedValue({ kind: 'invalidInput', message: 'invalid line range 9-2' });
+    const wrapper = mount(PdfExportDialog, {
+      props: { contentHtml: CONTENT_HTML, markdown: '# A\n' },
+    });
+    await wrapper.find('[data-testid="pdf-range-start"]').setValue(9);
+    await wrapper.find('[data-testid="pdf-range-end"]').setValue(2);
+    await new Promise(resolve => setTimeout(resolve, 300));
+    await wrapper.vm.$nextTick();
+    expect(wrapper.find('[data-testid="pdf-range-hint"]').text()).toContain('invalid line range 9-2');
+  });
 });
@@ -13,12 +13,17 @@ vi.mock('@tauri-apps/plugin-process', () => ({
   exit: vi.fn(() => Promise.resolve()),
 }));
 
-vi.mock('@tauri-apps/plugin-dialog', () => ({
-  save: vi.fn(() => Promise.resolve(null)),
+vi.mock('../../services/fileDialogs', () => ({
+  fileDialogs: {
+    save: vi.fn(() => Promise.resolve(null)),
+  },
+  MARKDOWN_SAVE_FILTERS: [],
 }));
 
-vi.mock('@tauri-apps/plugin-fs', () => ({
-  writeTextFile: vi.fn(() => Promise.resolve()),
+vi.mock('../../services/documentFs', () => ({
+  documentFs: {
+    save: vi.fn(() => Promise.resolve('0123456789abcdef')),
+  },
 }));
 
 vi.mock('../../utils/markdown-converter', () => ({
@@ -6,10 +6,9 @@ import type { Tab } from '../../composables/useTabs';
 // Mocks — must be hoisted above imports
 // ============================================================
 
-const mockReadTextFile = vi.fn();
-const mockWriteTextFile = vi.fn();
-const mockRename = vi.fn();
-const mockRemove = vi.fn();
+const mockSave = vi.fn();
+const mockRead = vi.fn();
+const mockAsk = vi.fn();
 const mockOpenDialog = vi.fn();
 const mockSaveDialog = vi.fn();
 const mockOpenShell = vi.fn();
@@ -19,16 +18,24 @@ const mockGetCurrentWindow = vi.fn(() => ({
   unmaximize: vi.fn(async () => {}),
 }));
 
-vi.mock('@tauri-apps/plugin-fs', () => ({
-  readTextFile: (...args: unknown[]) => mockReadTextFile(...args),
-  writeTextFile: (...args: unknown[]) => mockWriteTextFile(...args),
-  rename: (...args: unknown[]) => mockRename(...args),
-  remove: (...args: unknown[]) => mockRemove(...args),
+vi.mock('../../services/documentFs', () => ({
+  documentFs: {
+    save: (...args: unknown[]) => mockSave(...args),
+    read: (...args: unknown[]) => mockRead(...args),
+  },
+}));
+
+vi.mock('../../services/fileDialogs', () => ({
+  fileDialogs: {
+    open: (...args: unknown[]) => mockOpenDialog(...args),
+    save: (...args: unknown[]) => mockSaveDialog(...args),
+  },
+  MARKDOWN_OPEN_FILTERS: [],
+  MARKDOWN_SAVE_FILTERS: [],
 }));
 
 vi.mock('@tauri-apps/plugin-dialog', () => ({
-  open: (...args: unknown[]) => mockOpenDialog(...args),
-  save: (...args: unknown[]) => mockSaveDialog(...args),
+  ask: (...args: unknown[]) => mockAsk(...args),
 }));
 
 vi.mock('@tauri-apps/plugin-shell', () => ({
@@ -111,10 +118,8 @@ const makeOptions = (tabOverrides: Partial<Tab> = {}, extraOptions: Record<strin
 describe('useFileOperations', () => {
   beforeEach(() => {
     vi.clearAllMocks();
-    mockWriteTextFile.mockResolvedValue(undefined);
-    mockRename.mockResolvedValue(undefined);
-    mockRemove.mockResolvedValue(undefined);
-    mockReadTextFile.mockResolvedValue('# hello');
+    mockSave.mockResolvedValue('0123456789abcdef');
+    mockRead.mockResolvedValue({ content: '# hello', hash: '0123456789abcdef' });
   });
 
   // ----------------------------------------------------------
@@ -122,78 +127,96 @@ describe('useFileOperations', () => {
   // ----------------------------------------------------------
 
   describe('atomicWriteFile', () => {
-    it('writes to .tmp file first, then renames to final path', async () => {
-      // readTextFile is called twice: once for .tmp verification, once for pre-save conflict check
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return '# hello'; // disk content matches originalMarkdown → no conflict
-      });
+    it('saves through the backend atomic save', async () => {
+      mockRead.mockResolvedValue({ content: '# hello', hash: '0123456789abcdef' }); // disk content matches originalMarkdown → no conflict
 
       const { options } = makeOptions();
       const { saveFile } = useFileOperations(options);
 
       await saveFile();
 
-      const tmpPath = '/test/file.md.tmp';
-      expect(mockWriteTextFile).toHaveBeenCalledWith(tmpPath, expect.any(String));
-      expect(mockRename).toHaveBeenCalledWith(tmpPath, '/test/file.md');
+      expect(mockSave).toHaveBeenCalledWith('/test/file.md', 'md:<p>hello</p>', expect.objectContaining({ verify: true }));
     });
 
-    it('removes .tmp file when rename succeeds (no leftover temp)', async () => {
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return '# hello';
-      });
+    it('keeps the tab dirty and releases the watcher guard when the save fails', async () => {
+      mockSave.mockRejectedValue({ kind: 'busy', message: 'file is busy: /test/file.md', path: '/test/file.md' });
+      const markSaveEnd = vi.fn();
 
-      const { options } = makeOptions();
+      const { options, tabs } = makeOptions();
+      const { saveFile } = useFileOperations({ ...options, markSaveEnd });
+
+      // saveFile swallows errors internally (console.error)
+      await saveFile();
+
+      expect(markSaveEnd).toHaveBeenCalledWith('/test/file.md', expect.any(String));
+      expect(tabs.value[0].hasChanges).toBe(true);
+    });
+
+    it('sends the tab disk hash as expectedHash and keeps the new one', async () => {
+      mockSave.mockResolvedValue('fedcba9876543210');
+
+      const { options, tabs } = makeOptions({ diskHash: '0123456789abcdef' });
       const { saveFile } = useFileOperations(options);
 
       await saveFile();
 
-      // remove should NOT be called on success path
-      expect(mockRemove).not.toHaveBeenCalled();
+      expect(mockSave).toHaveBeenCalledWith('/test/file.md', expect.any(String), {
+        expectedHash: '0123456789abcdef',
+        verify: true,
+      });
+      expect(tabs.value[0].diskHash).toBe('fedcba9876543210');
     });
 
-    it('removes .tmp file and rethrows when writeTextFile fails', async () => {
-      mockWriteTextFile.mockRejectedValue(new Error('disk full'));
-      const markSaveEnd = vi.fn();
+    it('expects the disk version the pre-save check read when overwriting it', async () => {
+      mockRead.mockResolvedValue({ content: '# DIFFERENT disk content', hash: 'aaaaaaaaaaaaaaaa' });
+      const onPreSaveConflict = vi.fn(async () => 'save' as const);
+
+      const { options } = makeOptions({ diskHash: '0123456789abcdef' });
+      const { saveFile } = useFileOperations({ ...options, onPreSaveConflict });
+
+      await saveFile();
+
+      expect(mockSave).toHaveBeenCalledWith('/test/file.md', expect.any(String), expect.objectContaining({ expectedHash: 'aaaaaaaaaaaaaaaa' }));
+    });
+
+    it('reports a failed read-back to onSaveError', async () => {
+      const error = { kind: 'verificationFailed', message: 'verification failed: /test/file.md', path: '/test/file.md' };
+      mockSave.mockRejectedValue(error);
+      const onSaveError = vi.fn();
 
       const { options, tabs } = makeOptions();
-      const { saveFile } = useFileOperations({ ...options, markSaveEnd });
+      const { saveFile } = useFileOperations({ ...options, onSaveError });
 
-      // saveFile swallows errors internally (console.error) — verify .tmp cleanup
       await saveFile();
 
-      expect(mockRemove).toHaveBeenCalledWith('/test/file.md.tmp');
-      // tab should remain unchanged (hasChanges still true)
+      expect(onSaveError).toHaveBeenCalledWith('/test/file.md', error);
       expect(tabs.value[0].hasChanges).toBe(true);
     });
 
-    it('removes .tmp and throws when verification fails (written !== content)', async () => {
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'CORRUPTED_CONTENT';
-        return '# hello';
+    it('saves with editor markers stripped once the user agrees', async () => {
+      mockSave.mockImplementation(async (_path: string, _content: string, opts: { sanitize?: boolean }) => {
+        if (!opts.sanitize) throw { kind: 'sentinelLeak', message: 'editor markers in content', path: '/test/file.md', markers: ['__CURSOR__'] };
+        return 'fedcba9876543210';
       });
+      mockAsk.mockResolvedValue(true);
 
       const { options, tabs } = makeOptions();
       const { saveFile } = useFileOperations(options);
 
       await saveFile();
 
-      // .tmp should be cleaned up on verification failure
-      expect(mockRemove).toHaveBeenCalledWith('/test/file.md.tmp');
-      // tab should remain unsaved
-      expect(tabs.value[0].hasChanges).toBe(true);
+      expect(mockAsk).toHaveBeenCalledWith(expect.stringContaining('__CURSOR__'), expect.any(Object));
+      expect(mockSave).toHaveBeenLastCalledWith('/test/file.md', expect.any(String), expect.objectContaining({ sanitize: true }));
+      expect(tabs.value[0].hasChanges).toBe(false);
     });
 
-    it('calls markSaveStart before write and markSaveEnd after rename', async () => {
+    it('calls markSaveStart before write and markSaveEnd after it', async () => {
       const calls: string[] = [];
       const markSaveStart = vi.fn(() => calls.push('start'));
       const markSaveEnd = vi.fn(() => calls.push('end'));
-
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return '# hello';
+      mockSave.mockImplementation(async () => {
+        calls.push('save');
+        return '0123456789abcdef';
       });
 
       const { options } = makeOptions();
@@ -201,7 +224,7 @@ describe('useFileOperations', () => {
 
       await saveFile();
 
-      expect(calls).toEqual(['start', 'end']);
+      expect(calls).toEqual(['start', 'save', 'end']);
       expect(markSaveStart).toHaveBeenCalledWith('/test/file.md');
       expect(markSaveEnd).toHaveBeenCalledWith('/test/file.md', expect.any(String));
     });
@@ -216,10 +239,7 @@ describe('useFileOperations', () => {
       const rawMarkdown = '# Raw from code editor\n\nNo conversion needed.';
       const getMarkdownOverride = vi.fn(() => rawMarkdown);
 
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return rawMarkdown;
-        return '# hello'; // disk matches → no conflict
-      });
+      mockRead.mockResolvedValue({ content: '# hello', hash: '0123456789abcdef' }); // disk matches → no conflict
 
       const { options } = makeOptions();
       const { saveFile } = useFileOperations({ ...options, getMarkdownOverride });
@@ -227,17 +247,14 @@ describe('useFileOperations', () => {
       await saveFile();
 
       // Should write the raw override content, NOT the HTML→markdown conversion
-      expect(mockWriteTextFile).toHaveBeenCalledWith('/test/file.md.tmp', rawMarkdown);
+      expect(mockSave).toHaveBeenCalledWith('/test/file.md', rawMarkdown, expect.any(Object));
       expect(htmlToMarkdown).not.toHaveBeenCalled();
     });
 
     it('falls back to HTML→markdown when override returns null (visual mode)', async () => {
       const getMarkdownOverride = vi.fn(() => null);
 
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return '# hello';
-      });
+      mockRead.mockResolvedValue({ content: '# hello', hash: '0123456789abcdef' });
 
       const { options } = makeOptions();
       const { saveFile } = useFileOperations({ ...options, getMarkdownOverride });
@@ -251,10 +268,7 @@ describe('useFileOperations', () => {
       const rawMarkdown = '# Saved from code view';
       const getMarkdownOverride = vi.fn(() => rawMarkdown);
 
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return rawMarkdown;
-        return '# hello';
-      });
+      mockRead.mockResolvedValue({ content: '# hello', hash: '0123456789abcdef' });
 
       const { options, tabs } = makeOptions();
       const { saveFile } = useFileOperations({ ...options, getMarkdownOverride });
@@ -270,10 +284,7 @@ describe('useFileOperations', () => {
       const getMarkdownOverride = vi.fn(() => rawMarkdown);
       const originalContent = '<p>hello</p>';
 
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return rawMarkdown;
-        return '# hello';
-      });
+      mockRead.mockResolvedValue({ content: '# hello', hash: '0123456789abcdef' });
 
       const { options, tabs } = makeOptions({ content: originalContent });
       const { saveFile } = useFileOperations({ ...options, getMarkdownOverride });
@@ -292,10 +303,7 @@ describe('useFileOperations', () => {
   describe('checkPreSaveConflict', () => {
     it('skips save when conflict detected and user cancels', async () => {
       // Disk content differs from originalMarkdown → conflict
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return '# DIFFERENT disk content'; // conflict!
-      });
+      mockRead.mockResolvedValue({ content: '# DIFFERENT disk content', hash: '0123456789abcdef' }); // conflict!
 
       const onPreSaveConflict = vi.fn(async () => 'cancel' as const);
       const { options, tabs } = makeOptions();
@@ -305,15 +313,12 @@ describe('useFileOperations', () => {
 
       expect(onPreSaveConflict).toHaveBeenCalledWith('/test/file.md', '# DIFFERENT disk content', 'md:<p>hello</p>');
       // File should NOT be written since user cancelled
-      expect(mockWriteTextFile).not.toHaveBeenCalled();
+      expect(mockSave).not.toHaveBeenCalled();
       expect(tabs.value[0].hasChanges).toBe(true);
     });
 
     it('proceeds with save when conflict detected but user confirms', async () => {
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return '# DIFFERENT disk content'; // conflict
-      });
+      mockRead.mockResolvedValue({ content: '# DIFFERENT disk content', hash: '0123456789abcdef' }); // conflict
 
       const onPreSaveConflict = vi.fn(async () => 'save' as const);
       const { options, tabs } = makeOptions();
@@ -322,16 +327,13 @@ describe('useFileOperations', () => {
       await saveFile();
 
       expect(onPreSaveConflict).toHaveBeenCalled();
-      expect(mockWriteTextFile).toHaveBeenCalled();
+      expect(mockSave).toHaveBeenCalled();
       expect(tabs.value[0].hasChanges).toBe(false);
     });
 
     it('does not call onPreSaveConflict when disk matches originalMarkdown', async () => {
       // Disk content matches originalMarkdown → no conflict
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return '# hello'; // matches originalMarkdown
-      });
+      mockRead.mockResolvedValue({ content: '# hello', hash: '0123456789abcdef' }); // matches originalMarkdown
 
       const onPreSaveConflict = vi.fn(async () => 'save' as const);
       const { options } = makeOptions({ originalMarkdown: '# hello' });
@@ -343,10 +345,7 @@ describe('useFileOperations', () => {
     });
 
     it('does not call onPreSaveConflict when tab has no originalMarkdown (new file)', async () => {
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return 'some disk content';
-      });
+      mockRead.mockResolvedValue({ content: 'some disk content', hash: '0123456789abcdef' });
 
       const onPreSaveConflict = vi.fn(async () => 'cancel' as const);
       const { options } = makeOptions({ originalMarkdown: null });
@@ -369,15 +368,12 @@ describe('useFileOperations', () => {
 
       await saveFile();
 
-      expect(mockWriteTextFile).not.toHaveBeenCalled();
+      expect(mockSave).not.toHaveBeenCalled();
     });
 
     it('shows save dialog when file has no path yet', async () => {
       mockSaveDialog.mockResolvedValue('/new/path/file.md');
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return ''; // no disk conflict
-      });
+      mockRead.mockResolvedValue({ content: '', hash: '0123456789abcdef' }); // no disk conflict
 
       const { options } = makeOptions({ filePath: null });
       const { saveFile } = useFileOperations(options);
@@ -385,14 +381,11 @@ describe('useFileOperations', () => {
       await saveFile();
 
       expect(mockSaveDialog).toHaveBeenCalled();
-      expect(mockWriteTextFile).toHaveBeenCalledWith('/new/path/file.md.tmp', expect.any(String));
+      expect(mockSave).toHaveBeenCalledWith('/new/path/file.md', expect.any(String), expect.any(Object));
     });
 
     it('updates tab state after successful save', async () => {
-      mockReadTextFile.mockImplementation(async (path: string) => {
-        if (path.endsWith('.tmp')) return 'md:<p>hello</p>';
-        return '# hello';
-      });
+      mockRead.mockResolvedValue({ content: '# hello', hash: '0123456789abcdef' });
 
       const { options, tabs } = makeOptions();
       const { saveFile } = useFileOperations(options);
@@ -418,11 +411,11 @@ describe('useFileOperations', () => {
       await openFileFromPath('/test/file.md');
 
       expect(switchToTab).toHaveBeenCalledWith('existing-tab');
-      expect(mockReadTextFile).not.toHaveBeenCalled();
+      expect(mockRead).not.toHaveBeenCalled();
     });
 
     it('loads file content and calls onFileOpened callback', async () => {
-      mockReadTextFile.mockResolvedValue('# new file content');
+      mockRead.mockResolvedValue({ content: '# new file content', hash: '0123456789abcdef' });
       const onFileOpened = vi.fn();
 
       const { options } = makeOptions({ filePath: null, hasChanges: false, content: '<p></p>' });
@@ -430,8 +423,57 @@ describe('useFileOperations', () => {
 
       await openFileFromPath('/other/file.md');
 
-      expect(mockReadTextFile).toHaveBeenCalledWith('/other/file.md');
+      expect(mockRead).toHaveBeenCalledWith('/other/file.md');
       expect(onFileOpened).toHaveBeenCalledWith('/other/file.md', '# new file content');
     });
+
+    it('asks before opening a file over the size limit and forces the read when confirmed', async () => {
+      const tooLarge = { kind: 'fileTooLarge', message: 'file too large', path: '/big.md', size: 80 * 1024 * 1024, limit: 50 * 1024 * 1024 };
+      mockRead.mockImplementation(async (_path: string, force?: boolean) => {
+        if (!force) throw tooLarge;
+        return { content: '# big', hash: '0123456789abcdef' };
+      });
+      mockAsk.mockResolvedValue(true);
+      const onFileOpened = vi.fn();
+
+      const { options } = makeOptions({ filePath: null, hasChanges: false, content: '<p></p>' });
+      const { openFileFromPath } = useFileOperations({ ...options, onFileOpened });
+
+      await openFileFromPath('/big.md');
+
+      expect(mockAsk).toHaveBeenCalledWith(expect.stringContaining('80.0'), expect.any(Object));
+      expect(mockRead).toHaveBeenLastCalledWith('/big.md', true);
+      expect(onFileOpened).toHaveBeenCalledWith('/big.md', '# big');
+    });
+
+    it('asks before opening a binary-looking file', async () => {
+      mockRead.mockImplementation(async (_path: string, force?: boolean) => {
+        if (!force) throw { kind: 'notTextFile', message: 'not a text file: /logo.md', path: '/logo.md' };
+        return { content: 'PNG', hash: '0123456789abcdef' };
+      });
+      mockAsk.mockResolvedValue(true);
+
+      const { options } = makeOptions({ filePath: null, hasChanges: false, content: '<p></p>' });
+      const { openFileFromPath } = useFileOperations(options);
+
+      await openFileFromPath('/logo.md');
+
+      expect(mockAsk).toHaveBeenCalledWith(expect.stringContaining('logo.md'), expect.any(Object));
+      expect(mockRead).toHaveBeenLastCalledWith('/logo.md', true);
+    });
+
+    it('does not open an oversized file when the user declines', async () => {
+      mockRead.mockRejectedValue({ kind: 'fileTooLarge', message: 'file too large', path: '/big.md', size: 2, limit: 1 });
+      mockAsk.mockResolvedValue(false);
+      const onFileOpened = vi.fn();
+
+      const { options } = makeOptions({ filePath: null, hasChanges: false, content: '<p></p>' });
+      const { openFileFromPath } = useFileOperations({ ...options, onFileOpened });
+
+      await openFileFromPath('/big.md');
+
+      expect(mockRead).toHaveBeenCalledTimes(1);
+      expect(onFileOpened).not.toHaveBeenCalled();
+    });
   });
 });
@@ -9,6 +9,10 @@ vi.mock('@tauri-apps/plugin-fs', () => ({
   readTextFile: vi.fn(async () => 'disk content'),
 }));
 
+vi.mock('../../services/documentFs', () => ({
+  rebaseline: vi.fn(),
+}));
+
 vi.mock('../../utils/markdown-converter', () => ({
   markdownToHtml: vi.fn((md: string) => `<p>${md}</p>`),
   htmlToMarkdown: vi.fn((html: string) => html.replace(/<[^>]*>/g, '')),
@@ -43,6 +47,7 @@ vi.mock('../../i18n', () => ({
 
 import { useFileReload } from '../../composables/useFileReload';
 import { readTextFile } from '@tauri-apps/plugin-fs';
+import { rebaseline } from '../../services/documentFs';
 import { markdownToHtml } from '../../utils/markdown-converter';
 
 describe('useFileReload', () => {
@@ -202,6 +207,19 @@ describe('useFileReload', () => {
       // Tab content should remain as-is (local changes preserved)
       expect(mockTab.content).toBe('<p>my local content</p>');
     });
+
+    it('should check the next save against the external version', async () => {
+      mockHasChanges.value = true;
+      mockTab.hasChanges = true;
+      vi.mocked(readTextFile).mockResolvedValueOnce('different content');
+
+      const { manualReload, handleConflictKeepLocal } = createReload();
+      await manualReload();
+
+      handleConflictKeepLocal();
+
+      expect(rebaseline).toHaveBeenCalledWith(mockTab, 'different content');
+    });
   });
 
   describe('handleConflictLoadExternal', () => {
@@ -167,16 +167,37 @@ describe('useFileWatcher', () => {
       expect(onExternalChange).toHaveBeenCalledWith('/test/file.md', 'changed');
     });
 
-    it('should call onFileDeleted when readTextFile throws', async () => {
+    it('should call onFileDeleted when the file is still missing after the grace period', async () => {
       const watcher = createWatcher();
       await watcher.watchFile('/test/file.md', 'content');
 
-      vi.mocked(readTextFile).mockRejectedValueOnce(new Error('file not found'));
+      vi.mocked(readTextFile)
+        .mockRejectedValueOnce(new Error('file not found'))
+        .mockRejectedValueOnce(new Error('file not found'));
 
       capturedWatchHandler?.({ type: { modify: { kind: 'data', mode: 'content' } }, paths: ['/test/file.md'], attrs: {} });
+      await vi.advanceTimersByTimeAsync(0);
+      expect(onFileDeleted).not.toHaveBeenCalled();
+
       await vi.runAllTimersAsync();
 
       expect(onFileDeleted).toHaveBeenCalledWith('/test/file.md');
+      expect(onExternalChange).not.toHaveBeenCalled();
+    });
+
+    it('should report a change instead of a deletion when the file is recreated', async () => {
+      const watcher = createWatcher();
+      await watcher.watchFile('/test/file.md', 'content');
+
+      vi.mocked(readTextFile)
+        .mockRejectedValueOnce(new Error('file not found'))
+        .mockResolvedValueOnce('rewritten');
+
+      capturedWatchHandler?.({ type: { remove: { kind: 'file' } }, paths: ['/test/file.md'], attrs: {} });
+      await vi.runAllTimersAsync();
+
+      expect(onFileDeleted).not.toHaveBeenCalled();
+      expect(onExternalChange).toHaveBeenCalledWith('/test/file.md', 'rewritten');
     });
 
     it('should call onWatchError when readTextFile throws and no onFileDeleted handler', async () => {
@@ -383,4 +404,24 @@ describe('useFileWatcher', () => {
       expect(onExternalChange).toHaveBeenCalledWith('/test/file2.md', 'changed content2');
     });
   });
+
+  describe('suspension', () => {
+    it('should hold events while suspended and check each file once on resume', async () => {
+      const watcher = createWatcher();
+      await watcher.watchFile('/test/file.md', 'old');
+
+      watcher.setSuspended(true);
+      capturedWatchHandler?.({ type: { modify: { kind: 'data', mode: 'content' } }, paths: ['/test/file.md'], attrs: {} });
+      capturedWatchHandler?.({ type: { modify: { kind: 'data', mode: 'content' } }, paths: ['/test/file.md'], attrs: {} });
+      await vi.runAllTimersAsync();
+      expect(readTextFile).not.toHaveBeenCalled();
+
+      vi.mocked(readTextFile).mockResolvedValueOnce('bulk edit');
+      watcher.setSuspended(false);
+      await vi.runAllTimersAsync();
+
+      expect(readTextFile).toHaveBeenCalledTimes(1);
+      expect(onExternalChange).toHaveBeenCalledWith('/test/file.md', 'bulk edit');
+    });
+  });
 });
@@ -14,6 +14,16 @@ vi.mock('@tauri-apps/api/window', () => ({
   })),
 }));
 
+const webviewListeners = vi.hoisted(() => new Map<string, (event: { payload: unknown }) => void>());
+vi.mock('@tauri-apps/api/webviewWindow', () => ({
+  getCurrentWebviewWindow: vi.fn(() => ({
+    listen: vi.fn(async (name: string, handler: (event: { payload: unknown }) => void) => {
+      webviewListeners.set(name, handler);
+      return () => webviewListeners.delete(name);
+    }),
+  })),
+}));
+
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
@@ -60,6 +70,28 @@ describe('useWindowManager', () => {
       });
       expect(result).toBe('window-3');
     });
+
+    it('should pass the source window when carrying its layout', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce('window-4');
+
+      await windowManager.createNewWindow('/path/to/file.md', 'main');
+
+      expect(invoke).toHaveBeenCalledWith('create_new_window', {
+        filePath: '/path/to/file.md',
+        carryLayoutFrom: 'main',
+      });
+    });
+
+    it('should pass a size preset', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce('window-5');
+
+      await windowManager.createNewWindow(null, null, { preset: 'slides-16-9' });
+
+      expect(invoke).toHaveBeenCalledWith('create_new_window', {
+        filePath: null,
+        preset: 'slides-16-9',
+      });
+    });
   });
 
   describe('getFilePathFromUrl', () => {
@@ -144,6 +176,75 @@ describe('useWindowManager', () => {
         targetWindow: 'window-1',
       });
     });
+
+    it('should ask to carry the layout for the last tab', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce(undefined);
+
+      await windowManager.transferTabToWindow('/path/to/file.md', 'window-2', 'main', true);
+
+      expect(invoke).toHaveBeenCalledWith('transfer_tab_to_window', {
+        filePath: '/path/to/file.md',
+        sourceWindow: 'window-2',
+        targetWindow: 'main',
+        carryLayout: true,
+      });
+    });
+
+    it('should pass shouldActivate only when the tab stays in the background', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce('t-1');
+
+      await windowManager.transferTabToWindow('/path/to/file.md', 'main', 'window-1', false, false);
+
+      expect(invoke).toHaveBeenCalledWith('transfer_tab_to_window', {
+        filePath: '/path/to/file.md',
+        sourceWindow: 'main',
+        targetWindow: 'window-1',
+        shouldActivate: false,
+      });
+    });
+  });
+
+  describe('transferTabAndWait', () => {
+    const ack = {
+      transfer_id: 't-1',
+      file_path: '/path/to/file.md',
+      source_window: 'main',
+      target_window: 'window-1',
+      tab_index: 2,
+      activated: true,
+    };
+
+    it('should resolve with the matching acknowledgement, even one sent before the id is known', async () => {
+      vi.mocked(invoke).mockImplementationOnce(async () => {
+        webviewListeners.get('tab-transfer-complete')?.({ payload: { ...ack, transfer_id: 'other' } });
+        webviewListeners.get('tab-transfer-complete')?.({ payload: ack });
+        return 't-1';
+      });
+
+      const result = await windowManager.transferTabAndWait('/path/to/file.md', 'main', 'window-1');
+
+      expect(result).toEqual(ack);
+      expect(webviewListeners.has('tab-transfer-complete')).toBe(false);
+    });
+
+    it('should resolve with null when the target never answers', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce('t-1');
+
+      const result = await windowManager.transferTabAndWait('/path/to/file.md', 'main', 'window-1', false, true, 10);
+
+      expect(result).toBeNull();
+    });
+  });
+
+  describe('getRuntimeMode', () => {
+    it('should invoke get_runtime_mode', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce({ singleWindow: true });
+
+      const result = await windowManager.getRuntimeMode();
+
+      expect(invoke).toHaveBeenCalledWith('get_runtime_mode');
+      expect(result).toEqual({ singleWindow: true });
+    });
   });
 
   describe('onTabTransfer', () => {
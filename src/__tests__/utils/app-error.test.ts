@@ -0,0 +1,23 @@
+import { describe, it, expect } from 'vitest';
+import { errorMessage, isAppError } from '../../utils/app-error';
+
+describe('isAppError', () => {
+  it('recognizes serialized backend errors', () => {
+    expect(isAppError({ kind: 'notFound', message: 'not found: /a.md', path: '/a.md' })).toBe(true);
+  });
+  it('rejects strings and plain errors', () => {
+    expect(isAppError('boom')).toBe(false);
+    expect(isAppError(new Error('boom'))).toBe(false);
+    expect(isAppError(null)).toBe(false);
+  });
+});
+
+describe('errorMessage', () => {
+  it('uses the backend message', () => {
+    expect(errorMessage({ kind: 'busy', message: 'file is busy: /a.md' })).toBe('file is busy: /a.md');
+  });
+  it('falls back to Error.message and String()', () => {
+    expect(errorMessage(new Error('boom'))).toBe('boom');
+    expect(errorMessage('plain')).toBe('plain');
+  });
+});
@@ -0,0 +1,25 @@
+import { describe, it, expect } from 'vitest';
+import { splitArgs, joinArgs } from '../../utils/command-line';
+
+describe('splitArgs', () => {
+  it('splits on whitespace and keeps quoted arguments together', () => {
+    expect(splitArgs('  mkdocs build  --config-file "{root}/my docs.yml" \'\'')).toEqual([
+      'mkdocs',
+      'build',
+      '--config-file',
+      '{root}/my docs.yml',
+      '',
+    ]);
+  });
+
+  it('returns nothing for a blank line', () => {
+    expect(splitArgs('   ')).toEqual([]);
+  });
+});
+
+describe('joinArgs', () => {
+  it('round-trips through splitArgs', () => {
+    const args = ['npm', 'run', 'build', 'a b', 'say "hi"', ''];
+    expect(splitArgs(joinArgs(args))).toEqual(args);
+  });
+});
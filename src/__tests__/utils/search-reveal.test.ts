@@ -0,0 +1,17 @@
+import { describe, it, expect } from 'vitest';
+import { revealRange } from '../../utils/search-reveal';
+
+describe('revealRange', () => {
+  it('recounts a byte range as UTF-16 offsets', () => {
+    const text = 'zażółć\nłódź mango 🥭 x\n';
+    // "mango" is at column 6 of line 2, five bytes long.
+    expect(revealRange(text, { line: 2, column: 6, start: 19, end: 24 })).toEqual({ from: 12, to: 17 });
+    const { from, to } = revealRange(text, { line: 2, column: 12, start: 25, end: 29 });
+    expect(text.slice(from, to)).toBe('🥭');
+  });
+
+  it('falls back to the line start without a range', () => {
+    expect(revealRange('a\nb\nc', { line: 3, column: 1 })).toEqual({ from: 4, to: 4 });
+    expect(revealRange('a', { line: 9, column: 1 })).toEqual({ from: 1, to: 1 });
+  });
+});
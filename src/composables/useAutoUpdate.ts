@@ -1,4 +1,6 @@
 import { ref } from 'vue';
+import { invoke } from '@tauri-apps/api/core';
+import { useSplitView } from './useSplitView';
 
 export interface UpdateInfo {
   version: string;
@@ -75,6 +77,20 @@ async function runCheck(ignoreDismissed: boolean): Promise<void> {
   }
 }
 
+/** Queue this window's documents so the relaunched app reopens them. */
+async function queueOpenDocuments(): Promise<void> {
+  const { splitState } = useSplitView();
+  const paths = splitState.value.panes.flatMap(pane => pane.tabs.map(tab => tab.filePath));
+  for (const path of paths) {
+    if (!path) continue;
+    try {
+      await invoke('queue_pending_open', { path });
+    } catch (error) {
+      console.warn('Could not queue', path, 'for reopen:', error);
+    }
+  }
+}
+
 export function useAutoUpdate(): UseAutoUpdateReturn {
   const checkForUpdates = (): Promise<void> => runCheck(false);
   const checkForUpdatesManual = (): Promise<void> => runCheck(true);
@@ -108,6 +124,7 @@ export function useAutoUpdate(): UseAutoUpdateReturn {
 
         // Clear dismissal on successful install + relaunch
         localStorage.removeItem(DISMISSED_KEY);
+        await queueOpenDocuments();
         const { relaunch } = await import('@tauri-apps/plugin-process');
         await relaunch();
       }
@@ -1,9 +1,9 @@
 import { ref, type Ref } from 'vue';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { exit } from '@tauri-apps/plugin-process';
-import { save } from '@tauri-apps/plugin-dialog';
-import { writeTextFile } from '@tauri-apps/plugin-fs';
 import { htmlToMarkdown } from '../utils/markdown-converter';
+import { documentFs } from '../services/documentFs';
+import { fileDialogs, MARKDOWN_SAVE_FILTERS } from '../services/fileDialogs';
 import type { Tab } from './useTabs';
 
 export interface TabToSave {
@@ -17,6 +17,8 @@ export interface UseCloseConfirmationOptions {
   getEditorHtml: () => string;
   switchToTab: (tabId: string, preserveHasChanges?: boolean) => Promise<void>;
   syncActiveTabContent?: () => void;
+  /** Called when writing `filePath` failed; the dialog stays on that tab. */
+  onSaveError?: (filePath: string, error: unknown) => void;
 }
 
 export interface UseCloseConfirmationReturn {
@@ -31,7 +33,7 @@ export interface UseCloseConfirmationReturn {
 }
 
 export function useCloseConfirmation(options: UseCloseConfirmationOptions): UseCloseConfirmationReturn {
-  const { tabs, activeTabId, getEditorHtml, switchToTab, syncActiveTabContent } = options;
+  const { tabs, activeTabId, getEditorHtml, switchToTab, syncActiveTabContent, onSaveError } = options;
 
   const showSaveConfirmDialog = ref(false);
   const currentTabToSave = ref<TabToSave | null>(null);
@@ -77,20 +79,25 @@ export function useCloseConfirmation(options: UseCloseConfirmationOptions): UseC
       let filePath = tab.filePath;
 
       if (!filePath) {
-        filePath = await save({
-          filters: [{ name: 'Markdown', extensions: ['md'] }],
-          defaultPath: `${tab.fileName.replace(/\.[^.]+$/, '')}.md`,
-        });
+        filePath = await fileDialogs.save(`${tab.fileName.replace(/\.[^.]+$/, '')}.md`, MARKDOWN_SAVE_FILTERS);
       }
 
       if (filePath) {
         // Get current content - if this is the active tab, get from editor
         const html = tab.id === activeTabId.value ? getEditorHtml() : tab.content;
         const markdown = htmlToMarkdown(html).trimEnd();
-        await writeTextFile(filePath, markdown);
+        const expectedHash = filePath === tab.filePath ? tab.diskHash : null;
+        let savedHash: string;
+        try {
+          savedHash = await documentFs.save(filePath, markdown, { expectedHash, verify: true });
+        } catch (error) {
+          onSaveError?.(filePath, error);
+          throw error;
+        }
 
         // Update the tab
         tab.filePath = filePath;
+        tab.diskHash = savedHash;
         tab.fileName = filePath.split(/[/\\]/).pop() || 'Dokument';
         tab.hasChanges = false;
         return true;
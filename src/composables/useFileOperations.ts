@@ -1,9 +1,12 @@
 import { ref, computed, type Ref, type ComputedRef } from 'vue';
-import { open, save } from '@tauri-apps/plugin-dialog';
-import { readTextFile, writeTextFile, rename, remove } from '@tauri-apps/plugin-fs';
+import { ask } from '@tauri-apps/plugin-dialog';
 import { open as openExternal } from '@tauri-apps/plugin-shell';
 import { htmlToMarkdown, markdownToHtml, detectLineEnding, applyLineEnding } from '../utils/markdown-converter';
 import { aiCommands } from '../services/aiCommands';
+import { documentFs, type LoadedText } from '../services/documentFs';
+import { fileDialogs, MARKDOWN_OPEN_FILTERS, MARKDOWN_SAVE_FILTERS } from '../services/fileDialogs';
+import { isAppError } from '../utils/app-error';
+import { t } from '../i18n';
 import type { Tab } from './useTabs';
 import { EMPTY_TAB_CONTENT, DEFAULT_FILE_NAME, DOM_SELECTORS } from '../constants';
 
@@ -26,6 +29,8 @@ export interface UseFileOperationsOptions {
    *  file watcher for new paths. Safe to call repeatedly — the watcher
    *  layer ignores already-watched files. */
   onAfterSave?: (filePath: string, content: string) => void;
+  /** Called when writing `filePath` failed; the tab stays dirty. */
+  onSaveError?: (filePath: string, error: unknown) => void;
   /** Returns 'save' | 'cancel' | mergedMarkdownString (to save the merged version).
    *  localMarkdown is the current editor content (used to compute a local→disk diff). */
   onPreSaveConflict?: (filePath: string, diskContent: string, localMarkdown: string) => Promise<'save' | 'cancel' | string>;
@@ -60,6 +65,7 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
     markSaveStart,
     markSaveEnd,
     onAfterSave,
+    onSaveError,
     onFileOpened,
     onPreSaveConflict,
   } = options;
@@ -86,6 +92,30 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
   const findActiveTabIndex = (): number =>
     tabs.value.findIndex(t => t.id === activeTabId.value);
 
+  // A file over the size limit or that looks binary is only opened after the
+  // user agreed to; null when they declined.
+  const readDocument = async (filePath: string): Promise<LoadedText | null> => {
+    try {
+      return await documentFs.read(filePath);
+    } catch (error) {
+      if (!isAppError(error)) throw error;
+      const fileName = extractFileName(filePath);
+      const megabytes = (bytes = 0) => (bytes / (1024 * 1024)).toFixed(1);
+      let confirmed: boolean;
+      if (error.kind === 'fileTooLarge') {
+        confirmed = await ask(t.value.fileTooLargeMessage(fileName, megabytes(error.size), megabytes(error.limit)), {
+          title: t.value.fileTooLargeTitle,
+          kind: 'warning',
+        });
+      } else if (error.kind === 'notTextFile') {
+        confirmed = await ask(t.value.notTextFileMessage(fileName), { title: t.value.notTextFileTitle, kind: 'warning' });
+      } else {
+        throw error;
+      }
+      return confirmed ? documentFs.read(filePath, true) : null;
+    }
+  };
+
   const loadFileIntoTab = async (filePath: string): Promise<void> => {
     // Check if file is already open
     const existingTab = findTabByFilePath(filePath);
@@ -94,7 +124,9 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
       return;
     }
 
-    const fileContent = await readTextFile(filePath);
+    const loaded = await readDocument(filePath);
+    if (!loaded) return;
+    const fileContent = loaded.content;
     const htmlContent = markdownToHtml(fileContent);
     const fileName = extractFileName(filePath);
 
@@ -105,12 +137,16 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
       tabs.value[activeIdx].content = htmlContent;
       tabs.value[activeIdx].hasChanges = false;
       tabs.value[activeIdx].originalMarkdown = fileContent;
+      tabs.value[activeIdx].diskHash = loaded.hash;
       setEditorContent(htmlContent);
     } else {
       const newTabId = createNewTab(filePath, htmlContent, fileName);
       if (!newTabId) return;
       const newTab = tabs.value.find(t => t.id === newTabId);
-      if (newTab) newTab.originalMarkdown = fileContent;
+      if (newTab) {
+        newTab.originalMarkdown = fileContent;
+        newTab.diskHash = loaded.hash;
+      }
       await switchToTab(newTabId);
     }
 
@@ -119,16 +155,10 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
 
   const openFile = async (): Promise<void> => {
     try {
-      const selected = await open({
-        multiple: false,
-        filters: [
-          { name: 'Markdown', extensions: ['md', 'markdown'] },
-          { name: 'Wszystkie pliki', extensions: ['*'] },
-        ],
-      });
+      const selected = await fileDialogs.open(MARKDOWN_OPEN_FILTERS);
 
       if (selected) {
-        await loadFileIntoTab(selected as string);
+        await loadFileIntoTab(selected);
       }
     } catch (error) {
       console.error('Error opening file:', error);
@@ -143,36 +173,46 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
     }
   };
 
-  // Returns disk content if a conflict is detected, null otherwise.
-  const checkPreSaveConflict = async (filePath: string, originalMarkdown: string | null): Promise<string | null> => {
-    if (!originalMarkdown || !onPreSaveConflict) return null;
+  // `conflict` is the disk content if it no longer matches what the tab was
+  // loaded from; `hash` is the hash of what was read, which the save may overwrite.
+  const checkPreSaveConflict = async (
+    filePath: string,
+    originalMarkdown: string | null,
+  ): Promise<{ conflict: string | null; hash: string | null }> => {
+    if (!originalMarkdown || !onPreSaveConflict) return { conflict: null, hash: null };
     try {
-      const currentDiskContent = await readTextFile(filePath);
+      const disk = await documentFs.read(filePath, true);
       // Normalize line endings for comparison
-      const normalizedDisk = currentDiskContent.replace(/\r\n/g, '\n');
+      const normalizedDisk = disk.content.replace(/\r\n/g, '\n');
       const normalizedOriginal = originalMarkdown.replace(/\r\n/g, '\n');
-      return normalizedDisk !== normalizedOriginal ? currentDiskContent : null;
+      return { conflict: normalizedDisk !== normalizedOriginal ? disk.content : null, hash: disk.hash };
     } catch {
-      return null; // File might not exist yet (new file)
+      return { conflict: null, hash: null }; // File might not exist yet (new file)
     }
   };
 
-  const atomicWriteFile = async (filePath: string, content: string): Promise<void> => {
-    const tmpPath = filePath + '.tmp';
+  // The backend writes a temp file, renames it over the target under the
+  // path's write lock and reads it back before reporting success. Content
+  // holding editor markers is refused; the user can save it with them removed.
+  const atomicWriteFile = async (filePath: string, content: string, expectedHash: string | null): Promise<string> => {
+    markSaveStart?.(filePath);
     try {
-      markSaveStart?.(filePath);
-      await writeTextFile(tmpPath, content);
-      // Verify written content matches expected
-      const written = await readTextFile(tmpPath);
-      if (written !== content) {
-        throw new Error('Atomic save verification failed: written content does not match');
+      try {
+        return await documentFs.save(filePath, content, { expectedHash, verify: true });
+      } catch (error) {
+        if (!isAppError(error) || error.kind !== 'sentinelLeak') throw error;
+        const strip = await ask(t.value.sentinelLeakMessage((error.markers ?? []).join(', ')), {
+          title: t.value.sentinelLeakTitle,
+          kind: 'warning',
+        });
+        if (!strip) throw error;
+        return await documentFs.save(filePath, content, { expectedHash, sanitize: true, verify: true });
       }
-      await rename(tmpPath, filePath);
-      markSaveEnd?.(filePath, content);
     } catch (error) {
-      markSaveEnd?.(filePath, content); // release watcher guard even on failure
-      try { await remove(tmpPath); } catch { /* temp file may not exist */ }
+      onSaveError?.(filePath, error);
       throw error;
+    } finally {
+      markSaveEnd?.(filePath, content); // release watcher guard even on failure
     }
   };
 
@@ -193,10 +233,17 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
 
     markdown = markdown.trimEnd();
 
+    // The save only replaces the disk version this edit is based on: the one
+    // the tab was loaded from, or the one the pre-save check just showed.
+    let expectedHash = tabIndex !== -1 && tabs.value[tabIndex].filePath === filePath
+      ? tabs.value[tabIndex].diskHash ?? null
+      : null;
+
     // Pre-save conflict check
     let mergedContentApplied = false;
     if (tabIndex !== -1 && onPreSaveConflict) {
-      const diskContent = await checkPreSaveConflict(filePath, tabs.value[tabIndex].originalMarkdown);
+      const { conflict: diskContent, hash } = await checkPreSaveConflict(filePath, tabs.value[tabIndex].originalMarkdown);
+      if (hash !== null) expectedHash = hash;
       if (diskContent !== null) {
         const decision = await onPreSaveConflict(filePath, diskContent, markdown);
         if (decision === 'cancel') return;
@@ -210,12 +257,13 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
       }
     }
 
-    await atomicWriteFile(filePath, markdown);
+    const savedHash = await atomicWriteFile(filePath, markdown, expectedHash);
 
     if (tabIndex !== -1) {
       tabs.value[tabIndex].filePath = filePath;
       tabs.value[tabIndex].fileName = extractFileName(filePath);
       tabs.value[tabIndex].hasChanges = false;
+      tabs.value[tabIndex].readonly = false;
       // Only update cached HTML when saving from visual mode — in code view the HTML
       // will be regenerated from the saved markdown when switching back to visual mode.
       // Skip when merged content was applied: the conflict handler already set tab.content
@@ -224,6 +272,7 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
         tabs.value[tabIndex].content = html;
       }
       tabs.value[tabIndex].originalMarkdown = markdown;
+      tabs.value[tabIndex].diskHash = savedHash;
     }
 
     // Tell host to start watching this path (no-op if already watched).
@@ -235,16 +284,19 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
       let filePath = currentFile.value;
       const tabIndex = findActiveTabIndex();
 
+      // Read-only tabs never write back to their own path
+      if (tabIndex !== -1 && tabs.value[tabIndex].readonly) {
+        await saveFileAs();
+        return;
+      }
+
       // Skip save if file exists and has no changes
       if (filePath && tabIndex !== -1 && !tabs.value[tabIndex].hasChanges) {
         return;
       }
 
       if (!filePath) {
-        filePath = await save({
-          filters: [{ name: 'Markdown', extensions: ['md'] }],
-          defaultPath: 'dokument.md',
-        });
+        filePath = await fileDialogs.save('dokument.md', MARKDOWN_SAVE_FILTERS);
       }
 
       if (filePath) {
@@ -258,10 +310,7 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
   const saveFileAs = async (): Promise<void> => {
     try {
       const oldPath = currentFile.value;
-      const filePath = await save({
-        filters: [{ name: 'Markdown', extensions: ['md'] }],
-        defaultPath: oldPath?.split(/[/\\]/).pop() || 'dokument.md',
-      });
+      const filePath = await fileDialogs.save(oldPath?.split(/[/\\]/).pop() || 'dokument.md', MARKDOWN_SAVE_FILTERS);
 
       if (filePath) {
         await writeAndUpdateTab(filePath);
@@ -323,7 +372,12 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
       }
 
       // Read the file
-      const fileContent = await readTextFile(fullPath);
+      const loaded = await readDocument(fullPath);
+      if (!loaded) {
+        isLoadingFile.value = false;
+        return;
+      }
+      const fileContent = loaded.content;
       const htmlContent = markdownToHtml(fileContent);
       const fileName = extractFileName(fullPath);
 
@@ -332,6 +386,7 @@ export function useFileOperations(options: UseFileOperationsOptions): UseFileOpe
       const newTab = tabs.value.find(t => t.id === newTabId);
       if (newTab) {
         newTab.originalMarkdown = fileContent;
+        newTab.diskHash = loaded.hash;
       }
       await switchToTab(newTabId);
       onFileOpened?.(fullPath, fileContent);
@@ -1,6 +1,7 @@
 import { ref, computed, type Ref, type ComputedRef } from 'vue';
 import { readTextFile } from '@tauri-apps/plugin-fs';
 import { markdownToHtml, htmlToMarkdown } from '../utils/markdown-converter';
+import { rebaseline } from '../services/documentFs';
 import { generateDiff, type DiffLine, type DiffStats } from './useDiffPreview';
 import { useFileWatcher } from './useFileWatcher';
 import { scrollTopFromRatio } from './useScrollSync';
@@ -96,7 +97,9 @@ export function useFileReload(options: UseFileReloadOptions) {
     },
   });
 
-  const reloadTabContent = (filePath: string, newContent: string) => {
+  // `diskContent` is what the file holds now when the tab gets something else
+  // (a merge); the next save is checked against it.
+  const reloadTabContent = (filePath: string, newContent: string, diskContent: string = newContent) => {
     const result = findTabByFilePathSplit(filePath);
     if (!result) return;
 
@@ -111,6 +114,7 @@ export function useFileReload(options: UseFileReloadOptions) {
     tab.content = htmlContent;
     tab.originalMarkdown = newContent;
     tab.hasChanges = false;
+    rebaseline(tab, diskContent);
 
     fileWatcher.updateKnownContent(filePath, newContent);
 
@@ -144,6 +148,9 @@ export function useFileReload(options: UseFileReloadOptions) {
 
   const handleConflictKeepLocal = () => {
     fileWatcher.updateKnownContent(conflictFilePath.value, conflictNewContent.value);
+    // Saving the local version now replaces the external one on purpose.
+    const result = findTabByFilePathSplit(conflictFilePath.value);
+    if (result) rebaseline(result.tab, conflictNewContent.value);
     showConflictModal.value = false;
   };
 
@@ -153,7 +160,7 @@ export function useFileReload(options: UseFileReloadOptions) {
   };
 
   const handleConflictMerge = (mergedContent: string) => {
-    reloadTabContent(conflictFilePath.value, mergedContent);
+    reloadTabContent(conflictFilePath.value, mergedContent, conflictNewContent.value);
     showConflictModal.value = false;
   };
 
@@ -181,6 +188,7 @@ export function useFileReload(options: UseFileReloadOptions) {
     showToast: computed(() => showToast.value),
     toastMessage: computed(() => toastMessage.value),
     toastType: computed(() => toastType.value),
+    showToastNotification,
     dismissToast,
 
     // Conflict modal
@@ -205,5 +213,6 @@ export function useFileReload(options: UseFileReloadOptions) {
     unwatchAll: fileWatcher.unwatchAll,
     markSaveStart: fileWatcher.markSaveStart,
     markSaveEnd: fileWatcher.markSaveEnd,
+    setWatchSuspended: fileWatcher.setSuspended,
   };
 }
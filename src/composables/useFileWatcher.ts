@@ -14,6 +14,7 @@ export interface UseFileWatcherReturn {
   markSaveStart: (filePath: string) => void;
   markSaveEnd: (filePath: string, newContent: string) => void;
   updateKnownContent: (filePath: string, content: string) => void;
+  setSuspended: (suspended: boolean) => void;
 }
 
 export function useFileWatcher(options: UseFileWatcherOptions): UseFileWatcherReturn {
@@ -27,6 +28,12 @@ export function useFileWatcher(options: UseFileWatcherOptions): UseFileWatcherRe
   const recentOwnSaves = new Map<string, number>();
   // Last known disk content per file (to detect actual content changes)
   const lastKnownDiskContent = new Map<string, string>();
+  // Files that vanished and are waiting out the delete grace period
+  const pendingDeletes = new Set<string>();
+  // While a bulk command runs (suspend_watch), events only mark the file so
+  // it is checked once on resume instead of prompting per write.
+  let suspended = false;
+  const changedWhileSuspended = new Set<string>();
 
   const isOwnSave = (filePath: string): boolean => {
     // Only skip events while OUR save is literally in progress (markSaveStart → markSaveEnd).
@@ -41,26 +48,49 @@ export function useFileWatcher(options: UseFileWatcherOptions): UseFileWatcherRe
 
   const handleWatchEvent = async (filePath: string) => {
     if (isOwnSave(filePath)) return;
+    if (suspended) {
+      changedWhileSuspended.add(filePath);
+      return;
+    }
 
     try {
-      const newContent = await readTextFile(filePath);
-      const knownContent = lastKnownDiskContent.get(filePath);
-
-      // Content unchanged — spurious event
-      if (knownContent !== undefined && newContent === knownContent) return;
-
-      lastKnownDiskContent.set(filePath, newContent);
-      onExternalChange(filePath, newContent);
+      reportContent(filePath, await readTextFile(filePath));
     } catch (error) {
       // File might have been deleted
       if (onFileDeleted) {
-        onFileDeleted(filePath);
+        confirmDeletion(filePath);
       } else {
         onWatchError?.(filePath, error);
       }
     }
   };
 
+  const reportContent = (filePath: string, newContent: string) => {
+    const knownContent = lastKnownDiskContent.get(filePath);
+
+    // Content unchanged — spurious event
+    if (knownContent !== undefined && newContent === knownContent) return;
+
+    lastKnownDiskContent.set(filePath, newContent);
+    onExternalChange(filePath, newContent);
+  };
+
+  // Sync tools and editors often save by deleting and re-creating the file,
+  // so only report a deletion if the path is still gone after a grace period.
+  // If it came back, treat it as an ordinary external change.
+  const confirmDeletion = (filePath: string) => {
+    if (pendingDeletes.has(filePath)) return;
+    pendingDeletes.add(filePath);
+    setTimeout(async () => {
+      if (!pendingDeletes.delete(filePath) || !watchers.has(filePath)) return;
+      try {
+        reportContent(filePath, await readTextFile(filePath));
+      } catch {
+        onFileDeleted?.(filePath);
+      }
+    }, TIMING.FILE_DELETE_GRACE);
+  };
+
   const watchFile = async (filePath: string, initialContent: string): Promise<void> => {
     // Already watching this file
     if (watchers.has(filePath)) return;
@@ -96,6 +126,7 @@ export function useFileWatcher(options: UseFileWatcherOptions): UseFileWatcherRe
       watchers.delete(filePath);
     }
     lastKnownDiskContent.delete(filePath);
+    pendingDeletes.delete(filePath);
     recentOwnSaves.delete(filePath);
     ownSavesInProgress.delete(filePath);
   };
@@ -106,8 +137,20 @@ export function useFileWatcher(options: UseFileWatcherOptions): UseFileWatcherRe
     }
     watchers.clear();
     lastKnownDiskContent.clear();
+    pendingDeletes.clear();
     recentOwnSaves.clear();
     ownSavesInProgress.clear();
+    changedWhileSuspended.clear();
+  };
+
+  const setSuspended = (on: boolean): void => {
+    suspended = on;
+    if (on) return;
+    const changed = [...changedWhileSuspended];
+    changedWhileSuspended.clear();
+    for (const filePath of changed) {
+      if (watchers.has(filePath)) handleWatchEvent(filePath);
+    }
   };
 
   const markSaveStart = (filePath: string): void => {
@@ -131,5 +174,6 @@ export function useFileWatcher(options: UseFileWatcherOptions): UseFileWatcherRe
     markSaveStart,
     markSaveEnd,
     updateKnownContent,
+    setSuspended,
   };
 }
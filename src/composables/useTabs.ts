@@ -16,6 +16,17 @@ export interface Tab {
    * with the session by useSessionRestore.
    */
   pinned?: boolean;
+  /**
+   * Opened via `open_readonly`: editing is disabled, autosave skips it and
+   * saving goes through Save As. Cleared once saved under a new path.
+   */
+  readonly?: boolean;
+  /**
+   * Content hash of the file as last read or saved. Saves send it as
+   * `expectedHash`, so a file changed on disk since is not overwritten.
+   * Null when unknown (new document), which skips that check.
+   */
+  diskHash?: string | null;
 }
 
 export interface UseTabsOptions {
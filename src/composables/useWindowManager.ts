@@ -1,20 +1,75 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+
+export interface WindowLayout {
+  sidebarVisible: boolean;
+  sidebarWidth: number;
+  outlineVisible: boolean;
+  splitFraction: number;
+}
 
 export interface TabTransferPayload {
+  transfer_id: string;
+  file_path: string;
+  source_window: string;
+  target_window: string;
+  /** Make the tab active in the target; otherwise the target keeps its tab. */
+  should_activate: boolean;
+  /** Present when this was the source window's last tab. */
+  layout?: WindowLayout;
+}
+
+/** Sent by the target back to the source once the tab is open. */
+export interface TabTransferAck {
+  transfer_id: string;
   file_path: string;
   source_window: string;
   target_window: string;
+  /** Index in the target pane, or null if the target could not open the file. */
+  tab_index: number | null;
+  activated: boolean;
+}
+
+export interface RuntimeMode {
+  /** Kiosk mode: no new windows, no tab transfer between windows. */
+  singleWindow: boolean;
+  /** Close a window whose last tab was moved out, else leave an empty document. */
+  closeWindowOnLastTransfer: boolean;
+}
+
+/** Initial size of a new window: a preset id, explicit pixels, or both. */
+export interface WindowSizeRequest {
+  preset?: string;
+  width?: number;
+  height?: number;
+}
+
+export interface WindowPreset {
+  id: string;
+  width: number;
+  height: number;
 }
 
 export function useWindowManager() {
-  const createNewWindow = async (filePath?: string | null): Promise<string> => {
+  const createNewWindow = async (
+    filePath?: string | null,
+    carryLayoutFrom?: string | null,
+    size?: WindowSizeRequest,
+  ): Promise<string> => {
     return invoke<string>('create_new_window', {
       filePath: filePath || null,
+      ...(carryLayoutFrom ? { carryLayoutFrom } : {}),
+      ...(size ?? {}),
     });
   };
 
+  /** Sizes offered for "New slide-sized window" and similar. */
+  const listWindowPresets = async (): Promise<WindowPreset[]> => {
+    return invoke<WindowPreset[]>('list_window_presets');
+  };
+
   const getFilePathFromUrl = (): string | null => {
     const urlParams = new URLSearchParams(window.location.search);
     const filePath = urlParams.get('file');
@@ -32,15 +87,63 @@ export function useWindowManager() {
   const transferTabToWindow = async (
     filePath: string,
     sourceWindow: string,
-    targetWindow: string
-  ): Promise<void> => {
-    return invoke('transfer_tab_to_window', {
+    targetWindow: string,
+    carryLayout = false,
+    shouldActivate = true
+  ): Promise<string> => {
+    return invoke<string>('transfer_tab_to_window', {
       filePath,
       sourceWindow,
       targetWindow,
+      ...(carryLayout ? { carryLayout } : {}),
+      ...(shouldActivate ? {} : { shouldActivate }),
     });
   };
 
+  const completeTabTransfer = async (ack: TabTransferAck): Promise<void> => {
+    return invoke('complete_tab_transfer', { ack });
+  };
+
+  /**
+   * Transfer a tab and wait for the target to report where it landed. The
+   * listener is in place before the transfer starts so a fast answer is not
+   * missed. Resolves with null if no answer arrives within `timeoutMs`.
+   */
+  const transferTabAndWait = async (
+    filePath: string,
+    sourceWindow: string,
+    targetWindow: string,
+    carryLayout = false,
+    shouldActivate = true,
+    timeoutMs = 5000
+  ): Promise<TabTransferAck | null> => {
+    const acks: TabTransferAck[] = [];
+    let onAck: (() => void) | null = null;
+    const unlisten = await getCurrentWebviewWindow().listen<TabTransferAck>('tab-transfer-complete', (event) => {
+      acks.push(event.payload);
+      onAck?.();
+    });
+    let timer: ReturnType<typeof setTimeout> | undefined;
+    try {
+      const transferId = await transferTabToWindow(filePath, sourceWindow, targetWindow, carryLayout, shouldActivate);
+      return await new Promise<TabTransferAck | null>((resolve) => {
+        onAck = () => {
+          const found = acks.find(a => a.transfer_id === transferId);
+          if (found) resolve(found);
+        };
+        onAck();
+        timer = setTimeout(() => resolve(null), timeoutMs);
+      });
+    } finally {
+      clearTimeout(timer);
+      unlisten();
+    }
+  };
+
+  const getRuntimeMode = async (): Promise<RuntimeMode> => {
+    return invoke<RuntimeMode>('get_runtime_mode');
+  };
+
   const onTabTransfer = async (
     callback: (payload: TabTransferPayload) => void
   ): Promise<UnlistenFn> => {
@@ -67,6 +170,11 @@ export function useWindowManager() {
     return invoke('unregister_window_files', { windowLabel });
   };
 
+  /** This window's file tabs in tab order, kept by the backend for named sessions. */
+  const reportWindowTabs = async (files: string[], active: number | null): Promise<void> => {
+    return invoke('report_window_tabs', { files, active });
+  };
+
   const checkFileOpen = async (filePath: string): Promise<string | null> => {
     return invoke<string | null>('check_file_open', { filePath });
   };
@@ -85,16 +193,21 @@ export function useWindowManager() {
 
   return {
     createNewWindow,
+    listWindowPresets,
     getFilePathFromUrl,
     getAllWindows,
     getCurrentWindowLabel,
     transferTabToWindow,
+    completeTabTransfer,
+    transferTabAndWait,
+    getRuntimeMode,
     onTabTransfer,
     closeCurrentWindow,
     // File registry
     registerOpenFile,
     unregisterOpenFile,
     unregisterWindowFiles,
+    reportWindowTabs,
     checkFileOpen,
     focusWindowWithFile,
     onFocusFile,
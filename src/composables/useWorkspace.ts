@@ -8,6 +8,7 @@ import {
 } from './useSettings';
 import { workspaceFs, type WorkspaceNode } from '../services/workspaceFs';
 import { basenameOf, isAncestor } from '../utils/path-utils';
+import { errorMessage } from '../utils/app-error';
 import {
   sortNodes,
   resolveSortMode,
@@ -154,7 +155,7 @@ export function useWorkspace() {
       autoExpandTopLevel(node);
       return node;
     } catch (e) {
-      const msg = String(e);
+      const msg = errorMessage(e);
       errorById.value[entry.id] = msg;
       treesById.value[entry.id] = null;
       throw new Error(msg);
@@ -336,6 +337,15 @@ export function useWorkspace() {
     await workspaceFs.reveal(path);
   }
 
+  /** On-save command of a workspace root, as an argv list (null if none). */
+  async function getOnSaveCommand(rootPath: string): Promise<string[] | null> {
+    return workspaceFs.getOnSaveCommand(rootPath);
+  }
+
+  async function setOnSaveCommand(rootPath: string, argv: string[]): Promise<void> {
+    await workspaceFs.setOnSaveCommand(rootPath, argv);
+  }
+
   /** Workspace whose root contains the given path (active set only). */
   function findOwningWorkspace(path: string): OpenWorkspaceEntry | null {
     if (!path) return null;
@@ -598,6 +608,8 @@ export function useWorkspace() {
     renamePath,
     deletePath,
     revealInOs,
+    getOnSaveCommand,
+    setOnSaveCommand,
     findOwningWorkspace,
 
     // Tree view
@@ -28,6 +28,8 @@ export const TIMING = {
   FILE_WATCH_DEBOUNCE: 500,
   /** Grace period to ignore file watch events after our own save */
   OWN_SAVE_GRACE_PERIOD: 2000,
+  /** How long a watched file may be missing before it counts as deleted (delete-then-write saves) */
+  FILE_DELETE_GRACE: 1500,
   /** Duration for toast notification auto-dismiss */
   TOAST_DURATION: 3000,
 } as const;
@@ -113,6 +113,7 @@ export interface Translations {
   toggleCodeView: string;
   zoomInOut: string;
   resetZoom: string;
+  windowZoom: string;
   findInCurrentDocument: string;
   searchWorkspace: string;
   documentSearch: string;
@@ -183,6 +184,9 @@ export interface Translations {
   autoSave: string;
   autoSaveOn: string;
   autoSaveOff: string;
+  defaultEditor: string;
+  makeDefaultEditor: string;
+  isDefaultEditor: string;
   wordWrap: string;
   dropFilesHere: string;
   editorFont: string;
@@ -222,6 +226,7 @@ export interface Translations {
   updateNow: string;
   whatsNew: string;
   whatsNewIn: string;
+  portableMode: string;
   noReleaseNotesForBuild: string;
   fullChangelog: string;
   changelog: string;
@@ -270,6 +275,16 @@ export interface Translations {
   preSaveConflictMessage: string;
   saveAnyway: string;
   fileDeletedExternally: (fileName: string) => string;
+  saveFailed: (fileName: string, message: string) => string;
+  saveVerificationFailed: (fileName: string) => string;
+  saveConflict: (fileName: string) => string;
+  saveSentinelLeak: (fileName: string, markers: string) => string;
+  sentinelLeakTitle: string;
+  sentinelLeakMessage: (markers: string) => string;
+  fileTooLargeTitle: string;
+  fileTooLargeMessage: (fileName: string, sizeMb: string, limitMb: string) => string;
+  notTextFileTitle: string;
+  notTextFileMessage: (fileName: string) => string;
 
   // Table of Contents
   tableOfContents: string;
@@ -487,6 +502,11 @@ export interface Translations {
   workspaceNewFilePrompt: string;
   workspaceNewFolderPrompt: string;
   workspaceRenamePrompt: string;
+  workspaceContextOnSaveCommand: string;
+  workspaceOnSaveCommandPrompt: string;
+  workspaceOnSaveCommandHelp: string;
+  buildFailed: (code: string) => string;
+  buildNotStarted: (message: string) => string;
   workspaceConfirmDelete: (name: string) => string;
   workspaceEmptyHint: string;
   workspaceErrorLoad: string;
@@ -563,6 +583,10 @@ export interface Translations {
   pdfTabTypography: string;
   pdfTabHeader: string;
   pdfTabWatermark: string;
+  pdfRange: string;
+  pdfRangeFrom: string;
+  pdfRangeTo: string;
+  pdfRangeHint: string;
   pdfFontSize: string;
   pdfFontSizeXs: string;
   pdfFontSizeS: string;
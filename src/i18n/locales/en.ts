@@ -107,6 +107,7 @@ const en: Translations = {
   toggleCodeView: 'Toggle Code / Visual view',
   zoomInOut: 'Zoom in / out',
   resetZoom: 'Reset zoom',
+  windowZoom: 'Zoom whole window / reset',
   findInCurrentDocument: 'Find in current document',
   searchWorkspace: 'Search workspace (files & content)',
   documentSearch: 'Document search',
@@ -177,6 +178,9 @@ const en: Translations = {
   autoSave: 'Auto-save',
   autoSaveOn: 'On',
   autoSaveOff: 'Off',
+  defaultEditor: 'Default editor',
+  makeDefaultEditor: 'Make MerMark the default Markdown editor',
+  isDefaultEditor: 'MerMark opens Markdown files',
   wordWrap: 'Word wrap',
   dropFilesHere: 'Drop .md files here',
   editorFont: 'Editor font',
@@ -216,6 +220,7 @@ const en: Translations = {
   updateNow: 'Update Now',
   whatsNew: "What's New",
   whatsNewIn: "What's New in",
+  portableMode: 'Portable',
   noReleaseNotesForBuild: 'No release notes for this build yet.',
   fullChangelog: 'Full changelog',
   changelog: 'Changelog',
@@ -264,6 +269,19 @@ const en: Translations = {
   preSaveConflictMessage: 'The file has been modified externally since you last loaded or saved it.',
   saveAnyway: 'Save Anyway',
   fileDeletedExternally: (fileName: string) => `"${fileName}" was deleted externally.`,
+  saveFailed: (fileName: string, message: string) => `Could not save "${fileName}": ${message}`,
+  saveVerificationFailed: (fileName: string) => `"${fileName}" was saved, but reading it back did not match. Check the file before closing it.`,
+  saveConflict: (fileName: string) => `"${fileName}" changed on disk since it was loaded, so it was not saved. Reload it or save again to overwrite.`,
+  saveSentinelLeak: (fileName: string, markers: string) =>
+    `"${fileName}" was not saved: it contains internal editor markers (${markers}). Save it manually to remove them.`,
+  sentinelLeakTitle: 'Editor Markers Found',
+  sentinelLeakMessage: (markers: string) =>
+    `The document contains internal editor markers that should never be saved (${markers}). Remove them and save?`,
+  fileTooLargeTitle: 'Large File',
+  fileTooLargeMessage: (fileName: string, sizeMb: string, limitMb: string) =>
+    `"${fileName}" is ${sizeMb} MB, over the ${limitMb} MB limit for opening files. Opening it may make the editor slow. Open anyway?`,
+  notTextFileTitle: 'Not a Text File',
+  notTextFileMessage: (fileName: string) => `"${fileName}" doesn't look like a Markdown file. Open anyway?`,
 
   // Table of Contents
   tableOfContents: 'Table of Contents',
@@ -481,6 +499,11 @@ const en: Translations = {
   workspaceNewFilePrompt: 'New file name:',
   workspaceNewFolderPrompt: 'New folder name:',
   workspaceRenamePrompt: 'New name:',
+  workspaceContextOnSaveCommand: 'On-save command…',
+  workspaceOnSaveCommandPrompt: 'Command:',
+  workspaceOnSaveCommandHelp: 'Runs after each save of a file in this workspace. {file} and {root} are filled in; without them both are added at the end. Leave empty to turn it off.',
+  buildFailed: (code: string) => `On-save command failed (${code}).`,
+  buildNotStarted: (message: string) => `On-save command could not run: ${message}`,
   workspaceConfirmDelete: (name: string) => `Delete "${name}"? This cannot be undone.`,
   workspaceEmptyHint: 'Open a folder to browse its markdown files here.',
   workspaceErrorLoad: 'Could not load workspace.',
@@ -553,6 +576,10 @@ const en: Translations = {
   pdfTabTypography: 'Typography',
   pdfTabHeader: 'Header/Footer',
   pdfTabWatermark: 'Watermark',
+  pdfRange: 'Lines',
+  pdfRangeFrom: 'From',
+  pdfRangeTo: 'To',
+  pdfRangeHint: 'Leave empty to export the whole document.',
   pdfFontSize: 'Font size',
   pdfFontSizeXs: 'XS — 8pt',
   pdfFontSizeS: 'S — 9pt',
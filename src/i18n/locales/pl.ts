@@ -107,6 +107,7 @@ const pl: Translations = {
   toggleCodeView: 'Przełącz widok Kod / Wizualny',
   zoomInOut: 'Powiększ / pomniejsz',
   resetZoom: 'Reset powiększenia',
+  windowZoom: 'Powiększ całe okno / reset',
   findInCurrentDocument: 'Znajdź w bieżącym dokumencie',
   searchWorkspace: 'Szukaj w obszarze (pliki i treść)',
   documentSearch: 'Wyszukiwanie w dokumencie',
@@ -177,6 +178,9 @@ const pl: Translations = {
   autoSave: 'Autozapis',
   autoSaveOn: 'Wł.',
   autoSaveOff: 'Wył.',
+  defaultEditor: 'Domyślny edytor',
+  makeDefaultEditor: 'Ustaw MerMark jako domyślny edytor Markdown',
+  isDefaultEditor: 'MerMark otwiera pliki Markdown',
   wordWrap: 'Zawijanie wierszy',
   dropFilesHere: 'Upuść pliki .md tutaj',
   editorFont: 'Czcionka edytora',
@@ -216,6 +220,7 @@ const pl: Translations = {
   updateNow: 'Aktualizuj teraz',
   whatsNew: 'Co nowego',
   whatsNewIn: 'Co nowego w',
+  portableMode: 'Przenośna',
   noReleaseNotesForBuild: 'Brak notatek do tej wersji.',
   fullChangelog: 'Pełna historia zmian',
   changelog: 'Historia zmian',
@@ -264,6 +269,19 @@ const pl: Translations = {
   preSaveConflictMessage: 'Plik został zmodyfikowany zewnętrznie od ostatniego wczytania lub zapisania.',
   saveAnyway: 'Zapisz mimo to',
   fileDeletedExternally: (fileName: string) => `"${fileName}" został usunięty zewnętrznie.`,
+  saveFailed: (fileName: string, message: string) => `Nie udało się zapisać "${fileName}": ${message}`,
+  saveVerificationFailed: (fileName: string) => `"${fileName}" został zapisany, ale odczytana zawartość się nie zgadza. Sprawdź plik przed zamknięciem.`,
+  saveConflict: (fileName: string) => `"${fileName}" zmienił się na dysku od wczytania, więc nie został zapisany. Wczytaj go ponownie lub zapisz jeszcze raz, aby nadpisać.`,
+  saveSentinelLeak: (fileName: string, markers: string) =>
+    `"${fileName}" nie został zapisany: zawiera wewnętrzne znaczniki edytora (${markers}). Zapisz go ręcznie, aby je usunąć.`,
+  sentinelLeakTitle: 'Znaleziono znaczniki edytora',
+  sentinelLeakMessage: (markers: string) =>
+    `Dokument zawiera wewnętrzne znaczniki edytora, które nie powinny trafić na dysk (${markers}). Usunąć je i zapisać?`,
+  fileTooLargeTitle: 'Duży plik',
+  fileTooLargeMessage: (fileName: string, sizeMb: string, limitMb: string) =>
+    `"${fileName}" ma ${sizeMb} MB, więcej niż limit ${limitMb} MB dla otwieranych plików. Otwarcie go może spowolnić edytor. Otworzyć mimo to?`,
+  notTextFileTitle: 'To nie jest plik tekstowy',
+  notTextFileMessage: (fileName: string) => `"${fileName}" nie wygląda na plik Markdown. Otworzyć mimo to?`,
 
   // Table of Contents
   tableOfContents: 'Spis treści',
@@ -481,6 +499,11 @@ const pl: Translations = {
   workspaceNewFilePrompt: 'Nazwa nowego pliku:',
   workspaceNewFolderPrompt: 'Nazwa nowego folderu:',
   workspaceRenamePrompt: 'Nowa nazwa:',
+  workspaceContextOnSaveCommand: 'Polecenie po zapisie…',
+  workspaceOnSaveCommandPrompt: 'Polecenie:',
+  workspaceOnSaveCommandHelp: 'Uruchamiane po każdym zapisie pliku w tym obszarze roboczym. {file} i {root} zostaną podstawione; bez nich oba trafią na koniec. Zostaw puste, aby wyłączyć.',
+  buildFailed: (code: string) => `Polecenie po zapisie nie powiodło się (${code}).`,
+  buildNotStarted: (message: string) => `Nie można uruchomić polecenia po zapisie: ${message}`,
   workspaceConfirmDelete: (name: string) => `Usunąć "${name}"? Tej operacji nie można cofnąć.`,
   workspaceEmptyHint: 'Otwórz folder, aby przeglądać jego pliki markdown.',
   workspaceErrorLoad: 'Nie udało się wczytać obszaru roboczego.',
@@ -553,6 +576,10 @@ const pl: Translations = {
   pdfTabTypography: 'Typografia',
   pdfTabHeader: 'Nagłówek/Stopka',
   pdfTabWatermark: 'Watermark',
+  pdfRange: 'Wiersze',
+  pdfRangeFrom: 'Od',
+  pdfRangeTo: 'Do',
+  pdfRangeHint: 'Pozostaw puste, aby wyeksportować cały dokument.',
   pdfFontSize: 'Rozmiar czcionki',
   pdfFontSizeXs: 'XS - 8pt',
   pdfFontSizeS: 'S - 9pt',
@@ -107,6 +107,7 @@ const zhCN: Translations = {
   toggleCodeView: '切换代码 / 可视视图',
   zoomInOut: '放大 / 缩小',
   resetZoom: '重置缩放',
+  windowZoom: '缩放整个窗口 / 重置',
   findInCurrentDocument: '在当前文档中查找',
   searchWorkspace: '搜索工作区（文件和内容）',
   documentSearch: '文档内搜索',
@@ -177,6 +178,9 @@ const zhCN: Translations = {
   autoSave: '自动保存',
   autoSaveOn: '开',
   autoSaveOff: '关',
+  defaultEditor: '默认编辑器',
+  makeDefaultEditor: '将 MerMark 设为默认 Markdown 编辑器',
+  isDefaultEditor: 'MerMark 已是 Markdown 文件的默认程序',
   wordWrap: '自动换行',
   dropFilesHere: '将 .md 文件拖放至此',
   editorFont: '编辑器字体',
@@ -216,6 +220,7 @@ const zhCN: Translations = {
   updateNow: '立即更新',
   whatsNew: '新功能',
   whatsNewIn: '新功能 -',
+  portableMode: '便携版',
   noReleaseNotesForBuild: '此版本尚无发行说明。',
   fullChangelog: '完整更新日志',
   changelog: '更新日志',
@@ -264,6 +269,18 @@ const zhCN: Translations = {
   preSaveConflictMessage: '自上次加载或保存以来，文件已被外部修改。',
   saveAnyway: '仍然保存',
   fileDeletedExternally: (fileName: string) => `"${fileName}" 已被外部删除。`,
+  saveFailed: (fileName: string, message: string) => `无法保存 "${fileName}"：${message}`,
+  saveVerificationFailed: (fileName: string) => `"${fileName}" 已保存，但读回的内容不一致。请在关闭前检查该文件。`,
+  saveConflict: (fileName: string) => `"${fileName}" 自加载后已在磁盘上更改，因此未保存。请重新加载，或再次保存以覆盖。`,
+  saveSentinelLeak: (fileName: string, markers: string) =>
+    `"${fileName}" 未保存：其中包含编辑器内部标记（${markers}）。请手动保存以移除它们。`,
+  sentinelLeakTitle: '发现编辑器标记',
+  sentinelLeakMessage: (markers: string) => `文档包含不应保存的编辑器内部标记（${markers}）。移除它们并保存吗？`,
+  fileTooLargeTitle: '文件过大',
+  fileTooLargeMessage: (fileName: string, sizeMb: string, limitMb: string) =>
+    `"${fileName}" 大小为 ${sizeMb} MB，超过了 ${limitMb} MB 的打开限制。打开它可能会使编辑器变慢。仍要打开吗？`,
+  notTextFileTitle: '不是文本文件',
+  notTextFileMessage: (fileName: string) => `"${fileName}" 看起来不像 Markdown 文件。仍要打开吗？`,
 
   // Table of Contents
   tableOfContents: '目录',
@@ -481,6 +498,11 @@ const zhCN: Translations = {
   workspaceNewFilePrompt: '新文件名：',
   workspaceNewFolderPrompt: '新文件夹名：',
   workspaceRenamePrompt: '新名称：',
+  workspaceContextOnSaveCommand: '保存后命令…',
+  workspaceOnSaveCommandPrompt: '命令：',
+  workspaceOnSaveCommandHelp: '每次保存此工作区中的文件后运行。{file} 和 {root} 会被替换；若未使用，两者会附加在末尾。留空即关闭。',
+  buildFailed: (code: string) => `保存后命令失败（${code}）。`,
+  buildNotStarted: (message: string) => `无法运行保存后命令：${message}`,
   workspaceConfirmDelete: (name: string) => `删除 "${name}"? 此操作无法撤销。`,
   workspaceEmptyHint: '打开一个文件夹以浏览其中的 Markdown 文件。',
   workspaceErrorLoad: '无法加载工作区。',
@@ -553,6 +575,10 @@ const zhCN: Translations = {
   pdfTabTypography: '排版',
   pdfTabHeader: '页眉/页脚',
   pdfTabWatermark: '水印',
+  pdfRange: '行范围',
+  pdfRangeFrom: '从',
+  pdfRangeTo: '到',
+  pdfRangeHint: '留空则导出整个文档。',
   pdfFontSize: '字体大小',
   pdfFontSizeXs: 'XS — 8pt',
   pdfFontSizeS: 'S — 9pt',
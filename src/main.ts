@@ -1,6 +1,13 @@
 import { createApp } from "vue";
 import App from "./App.vue";
+import PresentView from "./components/PresentView.vue";
 import { vTooltip } from "./directives/tooltip";
+import { installCommandMetrics } from "./utils/command-metrics";
 import "./styles/main.css";
 
-createApp(App).directive('tooltip', vTooltip).mount("#app");
+void installCommandMetrics();
+
+// A present mirror window (`?present=<source>`) gets the read-only view only.
+const isPresent = new URLSearchParams(window.location.search).has("present");
+
+createApp(isPresent ? PresentView : App).directive('tooltip', vTooltip).mount("#app");
@@ -0,0 +1,59 @@
+/**
+ * Export selected notes as a read-only HTML mini-site.
+ *
+ * The backend plans the bundle (output names, rewritten links, assets); each
+ * page is rendered here with the editor's own Markdown pipeline so the bundle
+ * looks like the preview, then the backend writes the pages and assets.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { markdownToHtml } from '../utils/markdown-converter';
+
+export interface BundleOptions {
+  /** Sidebar heading; defaults to the output folder name. */
+  title?: string;
+  copyAssets?: boolean;
+}
+
+export interface BundlePage {
+  source: string;
+  output: string;
+  title: string;
+  markdown: string;
+}
+
+export interface BundlePlan {
+  pages: BundlePage[];
+  assets: { source: string; target: string }[];
+}
+
+export interface BundleProgress {
+  done: number;
+  total: number;
+  current: string;
+}
+
+export interface BundleResult {
+  index: string;
+  pages: number;
+  assets: number;
+}
+
+export async function exportBundle(
+  paths: string[],
+  outputDir: string,
+  options: BundleOptions = {},
+  onProgress?: (progress: BundleProgress) => void,
+): Promise<BundleResult> {
+  const plan = await invoke<BundlePlan>('plan_bundle', { paths, options });
+  const html = plan.pages.map(page => markdownToHtml(page.markdown));
+  const unlisten = onProgress
+    ? await listen<BundleProgress>('bundle-progress', event => onProgress(event.payload))
+    : null;
+  try {
+    return await invoke<BundleResult>('export_bundle', { paths, outputDir, options, html });
+  } finally {
+    unlisten?.();
+  }
+}
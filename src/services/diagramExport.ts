@@ -0,0 +1,86 @@
+/**
+ * Extract a document's Mermaid diagrams to image files.
+ *
+ * The backend finds the blocks and writes the files; each diagram is
+ * rendered here with the bundled Mermaid version the document pins in its
+ * front matter (or the one chosen in settings). A diagram that fails to
+ * render is left as a code block; a pin to a version that isn't bundled
+ * fails the whole export.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { mermaidFor, type MermaidApi } from '../utils/mermaid-versions';
+
+export type DiagramFormat = 'svg' | 'png';
+
+export interface DocumentDiagram {
+  line: number;
+  diagramType: string | null;
+  source: string;
+}
+
+export interface ExtractedDiagrams {
+  images: string[];
+  content: string;
+}
+
+async function svgToPng(svg: string): Promise<Uint8Array> {
+  const url = URL.createObjectURL(new Blob([svg], { type: 'image/svg+xml' }));
+  try {
+    const img = new Image();
+    await new Promise<void>((resolve, reject) => {
+      img.onload = () => resolve();
+      img.onerror = () => reject(new Error('SVG could not be loaded'));
+      img.src = url;
+    });
+    const scale = 2;
+    const canvas = document.createElement('canvas');
+    canvas.width = Math.max(1, Math.ceil(img.width * scale));
+    canvas.height = Math.max(1, Math.ceil(img.height * scale));
+    const ctx = canvas.getContext('2d');
+    if (!ctx) throw new Error('canvas unavailable');
+    ctx.drawImage(img, 0, 0, canvas.width, canvas.height);
+    const blob = await new Promise<Blob | null>(resolve => canvas.toBlob(resolve, 'image/png'));
+    if (!blob) throw new Error('PNG encoding failed');
+    return new Uint8Array(await blob.arrayBuffer());
+  } finally {
+    URL.revokeObjectURL(url);
+  }
+}
+
+async function renderDiagram(
+  mermaid: MermaidApi,
+  source: string,
+  index: number,
+  format: DiagramFormat,
+): Promise<number[] | null> {
+  try {
+    const { svg } = await mermaid.render(`mermaid-extract-${Date.now()}-${index}`, source);
+    const bytes = format === 'svg' ? new TextEncoder().encode(svg) : await svgToPng(svg);
+    return Array.from(bytes);
+  } catch (e) {
+    console.warn(`Diagram ${index + 1} could not be rendered:`, e);
+    return null;
+  }
+}
+
+export async function extractDiagramsToImages(
+  content: string,
+  docPath: string,
+  format: DiagramFormat,
+  replace: boolean,
+): Promise<ExtractedDiagrams> {
+  const mermaid = await mermaidFor(content);
+  const diagrams = await invoke<DocumentDiagram[]>('document_diagrams', { content });
+  const rendered: (number[] | null)[] = [];
+  for (const [i, diagram] of diagrams.entries()) {
+    rendered.push(await renderDiagram(mermaid, diagram.source, i, format));
+  }
+  return invoke<ExtractedDiagrams>('extract_diagrams_to_images', {
+    content,
+    docPath,
+    format,
+    replace,
+    rendered,
+  });
+}
@@ -0,0 +1,65 @@
+/**
+ * Document reads and saves through the backend commands.
+ *
+ * Reads are refused over the `maxOpenBytes` setting or for binary files. Saves hold the per-path
+ * write lock, write atomically and tell the watcher about our own writes.
+ * Opening and saving documents goes through here instead of plugin-fs.
+ * Errors are `AppError`s (see `utils/app-error`).
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface LoadedText {
+  content: string;
+  hash: string;
+}
+
+export interface SaveOptions {
+  /** Hash the file must still have on disk, or the save fails with `conflict`. */
+  expectedHash?: string | null;
+  /**
+   * Strip editor markers (`__CURSOR__`, ...) from the content. Without it,
+   * content holding them fails with `sentinelLeak`.
+   */
+  sanitize?: boolean;
+  /** Read the file back after writing; a mismatch fails with `verificationFailed`. */
+  verify?: boolean;
+}
+
+export const documentFs = {
+  /**
+   * Read a document for the editor. Fails with `fileTooLarge` over the size
+   * limit and `notTextFile` for binary content; `force` skips both checks
+   * once the user has agreed to open anyway.
+   */
+  read: (path: string, force = false): Promise<LoadedText> =>
+    invoke<LoadedText>('read_text_file', { path, force }),
+
+  /** Save `content` over `path` atomically. Resolves with the new content hash. */
+  save: (path: string, content: string, options: SaveOptions = {}): Promise<string> =>
+    invoke<string>('save_file_atomic', {
+      path,
+      content,
+      expectedHash: options.expectedHash ?? null,
+      sanitize: options.sanitize ?? false,
+      verify: options.verify ?? false,
+    }),
+
+  /** The same hash reads and saves return, for content already in memory. */
+  hash: (content: string): Promise<string> => invoke<string>('content_hash', { content }),
+};
+
+/**
+ * Point `tab.diskHash` at `diskContent` after the tab was reset to (or kept
+ * against) a version read elsewhere. The hash arrives asynchronously and is
+ * dropped if a save set a newer one in the meantime.
+ */
+export function rebaseline(tab: { diskHash?: string | null }, diskContent: string): void {
+  tab.diskHash = null;
+  documentFs
+    .hash(diskContent)
+    .then((hash) => {
+      if (tab.diskHash === null) tab.diskHash = hash;
+    })
+    .catch(() => {});
+}
@@ -0,0 +1,45 @@
+/**
+ * Export one section of a document through the print pipeline.
+ *
+ * The backend cuts the line range out of the Markdown (widened to whole
+ * fenced blocks, with its enclosing heading); it is rendered to HTML here,
+ * with its diagrams drawn by the Mermaid version the document pins, so the
+ * PDF dialog can print it like the whole document.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { markdownToHtml } from '../utils/markdown-converter';
+import { mermaidFor } from '../utils/mermaid-versions';
+
+/** 1-based, inclusive. */
+export interface LineRange {
+  startLine: number;
+  endLine: number;
+}
+
+let renderId = 0;
+
+/**
+ * HTML for `range` of `markdown`. Fails with `invalidInput` when the range
+ * is empty or starts past the end of the document.
+ */
+export async function renderRange(markdown: string, range: LineRange): Promise<string> {
+  const slice = await invoke<string>('export_range_markdown', { markdown, range });
+  const root = document.createElement('div');
+  root.innerHTML = markdownToHtml(slice);
+  const blocks = root.querySelectorAll<HTMLElement>('[data-type="mermaid"]');
+  if (blocks.length > 0) {
+    const mermaid = await mermaidFor(markdown);
+    renderId += 1;
+    for (const [i, block] of Array.from(blocks).entries()) {
+      const code = decodeURIComponent(block.dataset.code ?? '');
+      try {
+        const { svg } = await mermaid.render(`range-diagram-${renderId}-${i}`, code);
+        block.innerHTML = svg;
+      } catch {
+        block.textContent = code;
+      }
+    }
+  }
+  return root.innerHTML;
+}
@@ -0,0 +1,38 @@
+/**
+ * Open/save dialogs for documents through the backend commands.
+ *
+ * The backend starts them in the folder the user last worked in (the last
+ * dialog choice or opened file) and remembers each choice, so every entry
+ * point opens in the same place. Use these instead of plugin-dialog's
+ * `open`/`save` for documents.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface FileFilter {
+  name: string;
+  extensions: string[];
+}
+
+export const MARKDOWN_OPEN_FILTERS: FileFilter[] = [
+  { name: 'Markdown', extensions: ['md', 'markdown'] },
+  { name: 'Wszystkie pliki', extensions: ['*'] },
+];
+
+export const MARKDOWN_SAVE_FILTERS: FileFilter[] = [{ name: 'Markdown', extensions: ['md'] }];
+
+export const fileDialogs = {
+  /** Pick one file; `null` when cancelled. Starts in `startDir` when it is a folder. */
+  open: async (filters: FileFilter[], startDir?: string): Promise<string | null> => {
+    const picked = await invoke<string[] | null>('open_file_dialog', {
+      startDir: startDir ?? null,
+      multiple: false,
+      filters,
+    });
+    return picked?.[0] ?? null;
+  },
+
+  /** Pick where to save, offering `suggestedName`; `null` when cancelled. */
+  save: (suggestedName: string, filters: FileFilter[], startDir?: string): Promise<string | null> =>
+    invoke<string | null>('save_file_dialog', { suggestedName, startDir: startDir ?? null, filters }),
+};
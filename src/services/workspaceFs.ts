@@ -22,10 +22,47 @@ export interface ContentSearchHit {
   path: string;
   /** 1-based line number of the matching line. */
   line: number;
+  /** 1-based character column of the match within the line. */
+  column: number;
+  /** Byte range of the match in the file. */
+  start: number;
+  end: number;
+  /** The matched text as it appears in the file. */
+  matched: string;
   /** Trimmed and length-capped match line for UI display. */
   snippet: string;
 }
 
+export interface RenameWithRefs {
+  newPath: string;
+  /** Files whose links were rewritten (on a dry run: would be). */
+  updatedFiles: string[];
+  /** Saved after the rename failed; these still link to the old name. */
+  failed: { path: string; message: string }[];
+  changes: { file: string; line: number; old: string; new: string }[];
+}
+
+export interface ReplaceOptions {
+  caseSensitive?: boolean;
+  wholeWord?: boolean;
+  /** `query` is a regex; `replacement` may use `$1`. */
+  regex?: boolean;
+  /** Write nothing; each result carries a `preview` instead. */
+  dryRun?: boolean;
+}
+
+export interface FileReplacement {
+  file: string;
+  replacements: number;
+  preview?: { file: string; line: number; old: string; new: string }[];
+}
+
+export interface ReplaceInFiles {
+  files: FileReplacement[];
+  /** Left unchanged: saved elsewhere since they were read, or not writable. */
+  failed: { path: string; message: string }[];
+}
+
 export const workspaceFs = {
   /** Read the full markdown-only tree rooted at `root`. May be slow for large folders. */
   readTree: (root: string): Promise<WorkspaceNode> =>
@@ -43,6 +80,42 @@ export const workspaceFs = {
   rename: (from: string, to: string): Promise<void> =>
     invoke<void>('rename_path', { from, to }),
 
+  /**
+   * Rename a note in place and rewrite `[[wikilinks]]` and relative links to
+   * it under `root`. `dryRun` only reports the files that would change.
+   */
+  renameWithRefs: (oldPath: string, newName: string, root: string, dryRun = false): Promise<RenameWithRefs> =>
+    invoke<RenameWithRefs>('rename_with_refs', { oldPath, newName, root, dryRun }),
+
+  /**
+   * Find-and-replace across the notes under `root` (hidden folders and
+   * `node_modules` excluded). Pass the same `replaceId` to `cancelReplace`
+   * to stop between files.
+   */
+  replaceInFiles: (
+    root: string,
+    query: string,
+    replacement: string,
+    options: ReplaceOptions,
+    replaceId: string,
+  ): Promise<ReplaceInFiles> =>
+    invoke<ReplaceInFiles>('replace_in_files', { root, query, replacement, options, replaceId }),
+
+  cancelReplace: (replaceId: string): Promise<boolean> =>
+    invoke<boolean>('cancel_replace', { replaceId }),
+
+  /**
+   * Argv run after saves under `root` (`{file}` and `{root}` are filled in
+   * per run), or null when none is set. Kept in the app settings, not in
+   * the workspace folder.
+   */
+  getOnSaveCommand: (root: string): Promise<string[] | null> =>
+    invoke<string[] | null>('get_on_save_command', { root }),
+
+  /** Set the on-save command of `root`; an empty list removes it. */
+  setOnSaveCommand: (root: string, argv: string[]): Promise<void> =>
+    invoke<void>('set_on_save_command', { root, argv }),
+
   /** Delete a file or recursively a folder. */
   remove: (path: string): Promise<void> =>
     invoke<void>('delete_path', { path }),
@@ -59,4 +132,11 @@ export const workspaceFs = {
    */
   searchContent: (roots: string[], query: string): Promise<ContentSearchHit[]> =>
     invoke<ContentSearchHit[]>('search_workspace_content', { roots, query }),
+
+  /**
+   * Open (or focus) the file of a hit and scroll to the match. The window
+   * that ends up showing it gets an `open-file` event with the position.
+   */
+  openAtMatch: (hit: ContentSearchHit): Promise<void> =>
+    invoke<void>('open_at_match', { hit }),
 };
@@ -0,0 +1,58 @@
+/**
+ * Errors returned by Tauri commands.
+ *
+ * The backend serializes every command error as `{ kind, message, path? }`
+ * (plus `size`/`limit` for `fileTooLarge`), so callers can branch on `kind`
+ * instead of matching message text.
+ */
+
+export type AppErrorKind =
+  | 'notFound'
+  | 'permissionDenied'
+  | 'alreadyExists'
+  | 'conflict'
+  | 'refused'
+  | 'busy'
+  | 'fileTooLarge'
+  | 'sentinelLeak'
+  | 'notTextFile'
+  | 'verificationFailed'
+  | 'monitorNotFound'
+  | 'network'
+  | 'invalidInput'
+  | 'disabled'
+  | 'io'
+  | 'internal';
+
+export interface AppError {
+  kind: AppErrorKind;
+  message: string;
+  path?: string;
+  size?: number;
+  limit?: number;
+  /** `sentinelLeak`: the editor markers found in the content. */
+  markers?: string[];
+  /** `verificationFailed`: hash of the intended content and of the file read back. */
+  expected?: string;
+  actual?: string;
+  /** `monitorNotFound`: the index that no longer exists. */
+  index?: number;
+  /** `network`: the HTTP status, absent when the service was unreachable. */
+  status?: number;
+}
+
+export function isAppError(e: unknown): e is AppError {
+  return (
+    typeof e === 'object' &&
+    e !== null &&
+    typeof (e as AppError).kind === 'string' &&
+    typeof (e as AppError).message === 'string'
+  );
+}
+
+/** Human-readable message for anything thrown by `invoke` or plain JS code. */
+export function errorMessage(e: unknown): string {
+  if (isAppError(e)) return e.message;
+  if (e instanceof Error) return e.message;
+  return String(e);
+}
@@ -0,0 +1,43 @@
+/**
+ * Editing an argv list as one line of text, for settings such as the
+ * workspace on-save command. The backend stores and spawns the list as is —
+ * no shell ever sees it — so this is only about how the user types it:
+ * whitespace separates arguments, and single or double quotes keep an
+ * argument with spaces together.
+ */
+
+/** Split a typed command line into arguments. Unclosed quotes run to the end. */
+export function splitArgs(line: string): string[] {
+  const args: string[] = [];
+  let current = '';
+  let inArg = false;
+  let quote: '"' | "'" | null = null;
+  for (const ch of line) {
+    if (quote) {
+      if (ch === quote) quote = null;
+      else current += ch;
+    } else if (ch === '"' || ch === "'") {
+      quote = ch;
+      inArg = true;
+    } else if (/\s/.test(ch)) {
+      if (inArg) args.push(current);
+      current = '';
+      inArg = false;
+    } else {
+      current += ch;
+      inArg = true;
+    }
+  }
+  if (inArg) args.push(current);
+  return args;
+}
+
+/** The line `splitArgs` turns back into `args`. */
+export function joinArgs(args: string[]): string {
+  return args
+    .map((arg) => {
+      if (arg !== '' && !/[\s"']/.test(arg)) return arg;
+      return arg.includes('"') ? `'${arg}'` : `"${arg}"`;
+    })
+    .join(' ');
+}
@@ -0,0 +1,53 @@
+// Round-trip timing for every `invoke`, reported to the backend in batches
+// for `get_command_metrics`. Tauri answers async commands outside the
+// backend's invoke handler, so this is the only place a command's full
+// duration is visible. Installed at startup only when `collectMetrics` is on.
+
+import { invoke } from '@tauri-apps/api/core';
+
+const FLUSH_INTERVAL_MS = 5000;
+const MAX_PENDING = 1000;
+const OWN_COMMANDS = new Set(['record_command_timings', 'get_command_metrics', 'clear_metrics']);
+
+interface CommandTiming {
+  command: string;
+  ms: number;
+}
+
+type InvokeFn = (cmd: string, ...rest: unknown[]) => Promise<unknown>;
+
+let installed = false;
+
+export async function installCommandMetrics(): Promise<void> {
+  if (installed) return;
+  const internals = (window as unknown as { __TAURI_INTERNALS__?: { invoke: InvokeFn } }).__TAURI_INTERNALS__;
+  if (!internals) return;
+  try {
+    const settings = await invoke<{ collectMetrics?: boolean }>('get_settings');
+    if (!settings.collectMetrics) return;
+  } catch {
+    return;
+  }
+  installed = true;
+
+  const original = internals.invoke.bind(internals);
+  let pending: CommandTiming[] = [];
+
+  internals.invoke = (cmd, ...rest) => {
+    if (OWN_COMMANDS.has(cmd)) return original(cmd, ...rest);
+    const start = performance.now();
+    const record = () => {
+      if (pending.length < MAX_PENDING) pending.push({ command: cmd, ms: performance.now() - start });
+    };
+    const result = original(cmd, ...rest);
+    result.then(record, record);
+    return result;
+  };
+
+  setInterval(() => {
+    if (pending.length === 0) return;
+    const timings = pending;
+    pending = [];
+    original('record_command_timings', { timings }).catch(() => {});
+  }, FLUSH_INTERVAL_MS);
+}
@@ -0,0 +1,28 @@
+/**
+ * Mermaid builds bundled with the app, by major version. The backend's
+ * `BUNDLED_VERSIONS` lists the same keys and decides which one a document
+ * uses (its `mermaid_version:` pin, else the setting); this maps the
+ * answer to the library. Shipping another version means adding its
+ * package under an npm alias and an entry here.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type MermaidApi = typeof import('mermaid').default;
+
+const LOADERS: Record<string, () => Promise<MermaidApi>> = {
+  '11': () => import('mermaid').then((m) => m.default),
+};
+
+export async function loadMermaid(version: string): Promise<MermaidApi> {
+  const load = LOADERS[version];
+  if (!load) {
+    throw new Error(`Mermaid ${version} is not bundled; available: ${Object.keys(LOADERS).join(', ')}`);
+  }
+  return load();
+}
+
+/** The Mermaid build `content` should render with; rejects when it pins a version that isn't bundled. */
+export async function mermaidFor(content: string): Promise<MermaidApi> {
+  return loadMermaid(await invoke<string>('mermaid_version_for', { content }));
+}
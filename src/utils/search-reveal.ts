@@ -0,0 +1,47 @@
+// Maps the `reveal` of an `open_at_match` open onto editor text. The backend
+// reports 1-based line/column (in characters) and a UTF-8 byte range; the
+// editors index UTF-16 code units, so offsets are recounted here.
+
+export interface RevealMatch {
+  line: number;
+  column: number;
+  /** Byte range of the match; absent when the file changed since the search. */
+  start?: number;
+  end?: number;
+}
+
+function utf8Length(ch: string): number {
+  const code = ch.codePointAt(0) ?? 0;
+  return code < 0x80 ? 1 : code < 0x800 ? 2 : code < 0x10000 ? 3 : 4;
+}
+
+/**
+ * UTF-16 `[from, to)` of the match in `text`. Without a byte range this is
+ * an empty range at the start of the line; lines past the end clamp to it.
+ */
+export function revealRange(text: string, reveal: RevealMatch): { from: number; to: number } {
+  let lineStart = 0;
+  for (let line = 1; line < reveal.line; line++) {
+    const next = text.indexOf('\n', lineStart);
+    if (next < 0) return { from: text.length, to: text.length };
+    lineStart = next + 1;
+  }
+  if (reveal.start === undefined || reveal.end === undefined) {
+    return { from: lineStart, to: lineStart };
+  }
+  let from = lineStart;
+  let column = 1;
+  for (const ch of text.slice(lineStart)) {
+    if (column === reveal.column || ch === '\n') break;
+    from += ch.length;
+    column++;
+  }
+  let to = from;
+  let bytes = reveal.end - reveal.start;
+  for (const ch of text.slice(from)) {
+    if (bytes <= 0) break;
+    bytes -= utf8Length(ch);
+    to += ch.length;
+  }
+  return { from, to };
+}